@@ -0,0 +1,22 @@
+/// The form chunk.
+pub(super) const FORM: u32 = u32::from_be_bytes(*b"FORM");
+/// AIFF form.
+pub const AIFF: u32 = u32::from_be_bytes(*b"AIFF");
+/// AIFF-C form.
+pub const AIFC: u32 = u32::from_be_bytes(*b"AIFC");
+/// Common chunk.
+pub(super) const COMM: u32 = u32::from_be_bytes(*b"COMM");
+/// Name.
+pub(super) const NAME: u32 = u32::from_be_bytes(*b"NAME");
+/// Author.
+pub(super) const AUTH: u32 = u32::from_be_bytes(*b"AUTH");
+/// Copyright.
+pub(super) const COPY: u32 = u32::from_be_bytes(*b"(c) ");
+/// Annotation.
+pub(super) const ANNO: u32 = u32::from_be_bytes(*b"ANNO");
+/// Comments.
+pub(super) const COMT: u32 = u32::from_be_bytes(*b"COMT");
+/// ID3 tag.
+pub(super) const ID3: u32 = u32::from_be_bytes(*b"ID3 ");
+/// ID3 tag (alternative lower case id).
+pub(super) const ID3_LOWER: u32 = u32::from_be_bytes(*b"id3 ");
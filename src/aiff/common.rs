@@ -0,0 +1,89 @@
+use crate::{AudioProperties, Codec, parsers};
+
+#[derive(Debug)]
+pub struct Common {
+    pub channels: u16,
+    pub sample_frames: u32,
+    pub sample_size: u16,
+    pub sample_rate: f64,
+    /// Compression type. Present only in AIFF-C.
+    pub compression: Option<[u8; 4]>,
+}
+
+impl Common {
+    pub fn from_bytes(d: &[u8; 18]) -> Self {
+        // 0..2: number of channels
+        // 2..6: number of sample frames
+        // 6..8: sample size
+        // 8..18: sample rate
+        Self {
+            channels: u16::from_be_bytes(d[0..2].try_into().unwrap()),
+            sample_frames: u32::from_be_bytes(d[2..6].try_into().unwrap()),
+            sample_size: u16::from_be_bytes(d[6..8].try_into().unwrap()),
+            sample_rate: parsers::be_f80(d[8..].try_into().unwrap()),
+            compression: None,
+        }
+    }
+
+    pub fn properties(&self) -> AudioProperties {
+        let codec = match self.compression.as_ref().unwrap_or(b"NONE") {
+            b"NONE" | b"none" | b"twos" | b"sowt" | b"raw " => Codec::Pcm,
+            b"fl32" | b"FL32" | b"fl64" | b"FL64" => Codec::FloatPcm,
+            b"alaw" | b"ALAW" => Codec::ALaw,
+            b"ulaw" | b"ULAW" => Codec::MuLaw,
+            c => Codec::Other(String::from_utf8_lossy(c).into_owned()),
+        };
+        let pcm = matches!(codec, Codec::Pcm | Codec::FloatPcm);
+
+        let mut res = AudioProperties::new(codec);
+        res.channels = Some(self.channels);
+        if self.sample_rate >= 1. && self.sample_rate <= u32::MAX as f64 {
+            res.sample_rate = Some(self.sample_rate as u32);
+        }
+        res.bits_per_sample = Some(self.sample_size).filter(|a| *a != 0);
+        // Bitrate can be computed only for uncompressed data.
+        if pcm {
+            res.bitrate = res.sample_rate.and_then(|r| {
+                r.checked_mul(self.channels as u32)?
+                    .checked_mul(self.sample_size as u32)
+            });
+        }
+        res
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn common(rate: [u8; 10]) -> Common {
+        // 2 channels, 88200 sample frames, 16 bits
+        let mut d = [0, 2, 0, 1, 0x58, 0x88, 0, 16].to_vec();
+        d.extend(rate);
+        Common::from_bytes(d[..].try_into().unwrap())
+    }
+
+    #[test]
+    fn sample_rate_80_bit() {
+        let c = common([0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(c.sample_rate, 44100.);
+        assert_eq!(c.sample_frames, 88200);
+        let c = common([0x40, 0x0D, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(c.sample_rate, 22050.);
+        let c = common([0x40, 0x0E, 0xBB, 0x80, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(c.sample_rate, 48000.);
+        let c = common([0; 10]);
+        assert_eq!(c.sample_rate, 0.);
+    }
+
+    #[test]
+    fn pcm_properties() {
+        let p =
+            common([0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]).properties();
+        assert_eq!(p.codec, Some(Codec::Pcm));
+        assert_eq!(p.sample_rate, Some(44100));
+        assert_eq!(p.channels, Some(2));
+        assert_eq!(p.bits_per_sample, Some(16));
+        assert_eq!(p.bitrate, Some(1_411_200));
+    }
+}
@@ -0,0 +1,284 @@
+/// Chunks within aiff.
+pub mod chunk;
+mod chunk_header;
+mod common;
+
+use self::{chunk_header::*, common::*};
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    Comment, DataType, Error, Result, TagRead, TagStore, TagType,
+    bread::Bread,
+    id3, parsers,
+    trap::{Trap, TrapExt},
+};
+
+// Implementation is based on:
+// - https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/AIFF/Docs/AIFF-1.3.pdf
+// - https://www.mmsp.ece.mcgill.ca/Documents/AudioFormats/AIFF/Docs/AIFF-C.9.26.91.pdf
+
+/// Aiff tag reader. Supports both AIFF and AIFF-C.
+#[derive(Debug)]
+pub struct Aiff;
+
+impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Aiff {
+    fn extensions(&self) -> &[&str] {
+        &["aif", "aiff", "aifc"]
+    }
+
+    fn store(&self, r: &mut R, store: &mut S, trap: &T) -> Result<()> {
+        from_seek(r, store, trap)
+    }
+}
+
+/// Read aiff tag from stream. Will seek to correct position before reading.
+pub fn from_seek(
+    mut r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    r.rewind()?;
+    from_read(r, store, trap)
+}
+
+/// Read aiff tags from file.
+pub fn from_file(
+    f: impl AsRef<Path>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_read(BufReader::new(File::open(f)?), store, trap)
+}
+
+/// Read aiff tags from stream. Doesn't seek before reading.
+pub fn from_read(
+    r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    let mut r = Bread::new(r);
+
+    let header: ChunkHeader = r.get()?;
+    if header.id != chunk::FORM {
+        return Err(Error::NoTag);
+    }
+
+    let typ: u32 = r.get_be()?;
+    if typ != chunk::AIFF && typ != chunk::AIFC {
+        return Err(Error::NoTag);
+    }
+
+    store.set_tag_type(TagType::Aiff(typ));
+
+    // Position is u64, so that it cannot overflow with large chunk sizes.
+    let mut pos = 4u64;
+    let mut comments = vec![];
+
+    while !store.done() && pos + 8 < header.size as u64 {
+        let header: ChunkHeader = r.get()?;
+        pos += header.size as u64 + (header.size & 1) as u64 + 8;
+
+        let hsize = header.size as usize;
+
+        match header.id {
+            chunk::COMM
+                if store.stores_data(DataType::Length)
+                    || store.stores_data(DataType::AudioProperties) =>
+            {
+                if let Some(c) = r.witht(hsize, trap, read_common)? {
+                    if store.stores_data(DataType::AudioProperties) {
+                        store.set_audio_properties(c.properties());
+                    }
+                    if store.stores_data(DataType::Length) {
+                        if c.sample_rate > 0. {
+                            store.set_length(Duration::from_secs_f64(
+                                c.sample_frames as f64 / c.sample_rate,
+                            ));
+                        } else {
+                            trap.error(Error::InvalidLength)?;
+                        }
+                    }
+                }
+            }
+            chunk::NAME if store.stores_data(DataType::Title) => {
+                if let Some(t) = r.witht(hsize, trap, read_text)? {
+                    store.set_title(t);
+                }
+            }
+            chunk::AUTH if store.stores_data(DataType::Artists) => {
+                if let Some(a) = r.witht(hsize, trap, read_text)? {
+                    store.set_artists(vec![a]);
+                }
+            }
+            chunk::COPY if store.stores_data(DataType::Copyright) => {
+                if let Some(c) = r.witht(hsize, trap, read_text)? {
+                    store.set_copyright(c);
+                }
+            }
+            chunk::ANNO if store.stores_data(DataType::Comments) => {
+                if let Some(c) = r.witht(hsize, trap, read_text)? {
+                    comments.push(Comment::from_value(c));
+                }
+            }
+            chunk::COMT if store.stores_data(DataType::Comments) => {
+                if let Some(c) = r.witht(hsize, trap, read_comments)? {
+                    comments.extend(c);
+                }
+            }
+            chunk::ID3 | chunk::ID3_LOWER => {
+                let start = r.seek(SeekFrom::Current(0))?;
+                match id3::v2::from_read(r.get_mut(), store, trap) {
+                    Err(Error::NoTag) => {}
+                    res => trap.prop(res)?,
+                }
+                r.seek(SeekFrom::Start(start + header.size as u64))?;
+            }
+            _ => r.seek_by(header.size as i64)?,
+        }
+
+        r.seek_by(header.size as i64 & 1)?;
+    }
+
+    if !comments.is_empty() {
+        store.set_comments(comments);
+    }
+
+    Ok(())
+}
+
+fn read_common(d: &[u8], _: &impl Trap) -> Result<Common> {
+    if d.len() < 18 {
+        return Err(Error::InvalidLength);
+    }
+    let mut res = Common::from_bytes(d[..18].try_into().unwrap());
+    // 18..22: compression type (AIFF-C only)
+    // 22..: compression name
+    if d.len() >= 22 {
+        res.compression = Some(d[18..22].try_into().unwrap());
+    }
+    Ok(res)
+}
+
+fn read_text(d: &[u8], trap: &impl Trap) -> Result<String> {
+    Ok(parsers::iso_8859_1_mnt(d, trap)?.1)
+}
+
+fn read_comments(mut d: &[u8], trap: &impl Trap) -> Result<Vec<Comment>> {
+    if d.len() < 2 {
+        return Err(Error::InvalidLength);
+    }
+
+    let cnt = u16::from_be_bytes(d[..2].try_into().unwrap());
+    d = &d[2..];
+
+    let mut res = vec![];
+    for _ in 0..cnt {
+        // 0..4: time stamp
+        // 4..6: marker id
+        // 6..8: count
+        if d.len() < 8 {
+            return Err(Error::InvalidLength);
+        }
+        let len = u16::from_be_bytes(d[6..8].try_into().unwrap()) as usize;
+        d = &d[8..];
+        if d.len() < len {
+            return Err(Error::InvalidLength);
+        }
+        res.extend(
+            trap.res(read_text(&d[..len], trap))?
+                .map(Comment::from_value),
+        );
+        d = &d[(len + (len & 1)).min(d.len())..];
+    }
+
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::Strict;
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        artists: Vec<String>,
+        length: Option<Duration>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Title | DataType::Artists | DataType::Length
+            )
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn set_artists(&mut self, artists: Vec<String>) {
+            self.artists = artists;
+        }
+
+        fn set_length(&mut self, length: Duration) {
+            self.length = Some(length);
+        }
+    }
+
+    fn chunk(id: &[u8; 4], d: &[u8]) -> Vec<u8> {
+        let mut res = id.to_vec();
+        res.extend((d.len() as u32).to_be_bytes());
+        res.extend_from_slice(d);
+        if d.len() & 1 == 1 {
+            res.push(0);
+        }
+        res
+    }
+
+    /// Create AIFF-C file with the given chunks after `COMM`.
+    fn file(chunks: &[Vec<u8>]) -> Vec<u8> {
+        // 1 channel, 22050 sample frames, 16 bits, 44100 Hz, sowt
+        let mut comm = vec![0, 1, 0, 0, 0x56, 0x22, 0, 16];
+        comm.extend([0x40, 0x0E, 0xAC, 0x44, 0, 0, 0, 0, 0, 0]);
+        comm.extend(b"sowt\0\0");
+
+        let mut form = b"AIFC".to_vec();
+        form.extend(chunk(b"COMM", &comm));
+        form.extend(chunks.concat());
+        chunk(b"FORM", &form)
+    }
+
+    #[test]
+    fn read_chunks() {
+        let mut id3 = b"ID3\x04\0\0\0\0\0\x0C".to_vec();
+        id3.extend(b"TPE1\0\0\0\x02\0\0\x03A");
+        let data = file(&[chunk(b"NAME", b"Odd"), chunk(b"ID3 ", &id3)]);
+
+        let mut store = Store::default();
+        from_read(Cursor::new(&data), &mut store, &Strict).unwrap();
+        assert_eq!(store.title.as_deref(), Some("Odd"));
+        assert_eq!(store.artists, ["A"]);
+        assert_eq!(store.length, Some(Duration::from_millis(500)));
+    }
+
+    #[test]
+    fn large_chunk_size() {
+        let mut data = file(&[chunk(b"NAME", b"Title")]);
+        data.extend(b"JUNK\xFF\xFF\xFF\xFF");
+        data[4..8].copy_from_slice(&u32::MAX.to_be_bytes());
+
+        let mut store = Store::default();
+        from_read(Cursor::new(&data), &mut store, &Strict).unwrap();
+        assert_eq!(store.title.as_deref(), Some("Title"));
+    }
+}
@@ -0,0 +1,447 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::{
+    Comment, CustomField, DataType, Error, Picture, PictureKind, Result,
+    TagStore, TagStoreExt, TagType,
+    ape::footer::Footer,
+    parsers,
+    replay_gain::{ReplayGain, ReplayGainField},
+    trap::{Trap, TrapExt},
+};
+
+/// Data stored in APEv1 or APEv2 tag.
+#[derive(Debug)]
+pub struct ApeTag {
+    /// Version of the tag. `1000` for APEv1 and `2000` for APEv2.
+    pub version: u32,
+    /// Items of the tag in the order in which they are in the file.
+    pub items: Vec<ApeItem>,
+}
+
+/// Single item in APE tag.
+#[derive(Debug)]
+pub struct ApeItem {
+    /// Key of the item. Keys are case insensitive.
+    pub key: String,
+    /// Value of the item.
+    pub value: ApeValue,
+    /// The item is marked as read only.
+    pub read_only: bool,
+}
+
+/// Value of APE item.
+#[derive(Debug)]
+pub enum ApeValue {
+    /// UTF-8 text. There may be multiple values separated by null.
+    Text(Vec<String>),
+    /// Binary data.
+    Binary(Vec<u8>),
+    /// Link to external resource.
+    Locator(String),
+}
+
+impl ApeTag {
+    const READ_ONLY: u32 = 0x1;
+    const TYPE_MASK: u32 = 0x6;
+    const TYPE_BINARY: u32 = 0x2;
+    const TYPE_LOCATOR: u32 = 0x4;
+
+    /// Keys (uppercase) of the text items that are not read as custom fields.
+    const KNOWN_KEYS: &[&str] = &[
+        "TITLE",
+        "ARTIST",
+        "ALBUM",
+        "ALBUM ARTIST",
+        "ALBUMARTIST",
+        "GENRE",
+        "YEAR",
+        "TRACK",
+        "DISC",
+        "COMMENT",
+        "COPYRIGHT",
+    ];
+
+    /// Read APE tag from file.
+    pub fn from_file(p: impl AsRef<Path>, trap: &impl Trap) -> Result<Self> {
+        Self::from_seek(File::open(p)?, trap)
+    }
+
+    /// Read APE tag from the end of the reader. The tag may be either at the
+    /// end or just before ID3v1 tag.
+    pub fn from_seek(
+        mut read: impl Read + Seek,
+        trap: &impl Trap,
+    ) -> Result<Self> {
+        let Some((pos, footer)) = Footer::find(&mut read)? else {
+            return Err(Error::NoTag);
+        };
+
+        let len = footer.size.saturating_sub(Footer::SIZE);
+        if len as u64 > pos {
+            return Err(Error::InvalidLength);
+        }
+
+        read.seek(SeekFrom::Start(pos - len as u64))?;
+        let mut data = vec![0; len as usize];
+        read.read_exact(&mut data)?;
+
+        let mut items = vec![];
+        let mut d = data.as_slice();
+        for _ in 0..footer.item_cnt {
+            if d.is_empty() {
+                break;
+            }
+            match Self::read_item(d, footer.is_v1(), trap) {
+                Ok((l, item)) => {
+                    d = &d[l..];
+                    items.extend(item);
+                }
+                Err(e) => {
+                    trap.error(e)?;
+                    break;
+                }
+            }
+        }
+
+        Ok(Self {
+            version: footer.version,
+            items,
+        })
+    }
+
+    fn read_item(
+        d: &[u8],
+        v1: bool,
+        trap: &impl Trap,
+    ) -> Result<(usize, Option<ApeItem>)> {
+        if d.len() < 9 {
+            return Err(Error::InvalidLength);
+        }
+
+        let len = u32::from_le_bytes(d[..4].try_into().unwrap()) as usize;
+        let flags = u32::from_le_bytes(d[4..8].try_into().unwrap());
+        let Some(klen) = d[8..].iter().position(|a| *a == 0) else {
+            return Err(Error::StringNotTerminated);
+        };
+        let start = 9 + klen;
+        if d.len() - start < len {
+            return Err(Error::InvalidLength);
+        }
+
+        let total = start + len;
+        let Some(key) = trap.res(parsers::ascii(&d[8..start - 1], trap))?
+        else {
+            return Ok((total, None));
+        };
+        let value = &d[start..total];
+
+        let typ = if v1 { 0 } else { flags & Self::TYPE_MASK };
+        let value = match typ {
+            Self::TYPE_BINARY => ApeValue::Binary(value.to_vec()),
+            Self::TYPE_LOCATOR => {
+                let Some(l) = trap.res(parsers::utf_8(value, trap))? else {
+                    return Ok((total, None));
+                };
+                ApeValue::Locator(l)
+            }
+            _ => {
+                let Some(s) = trap.res(parsers::utf_8(value, trap))? else {
+                    return Ok((total, None));
+                };
+                ApeValue::Text(s.split('\0').map(|a| a.to_string()).collect())
+            }
+        };
+
+        let item = ApeItem {
+            key,
+            value,
+            read_only: !v1 && (flags & Self::READ_ONLY) == Self::READ_ONLY,
+        };
+
+        Ok((total, Some(item)))
+    }
+
+    /// Store the APE data into a tag storage.
+    pub fn store(
+        self,
+        store: &mut impl TagStore,
+        trap: &impl Trap,
+    ) -> Result<()> {
+        store.set_tag_type(TagType::Ape((self.version / 1000) as u8));
+
+        fn last(v: Vec<String>) -> String {
+            v.into_iter().next_back().unwrap_or_default()
+        }
+
+        let mut comments = vec![];
+        let mut replay_gain = ReplayGain::default();
+
+        for item in self.items {
+            let key = item.key.to_ascii_uppercase();
+            let v = match item.value {
+                ApeValue::Text(v) => v,
+                ApeValue::Binary(d) => {
+                    if let Some(kind) = picture_kind(&key)
+                        && store.stores_data(DataType::Picture(kind))
+                        && let Some(p) =
+                            trap.res(read_picture(&d, kind, trap))?
+                    {
+                        store.add_picture(p);
+                    }
+                    continue;
+                }
+                ApeValue::Locator(_) => continue,
+            };
+
+            if let Some(f) = ReplayGainField::from_key(&key)
+                && store.stores_data(f.data_type())
+                && let Some(l) = v.last()
+            {
+                replay_gain.set(f, l, trap)?;
+            }
+
+            match key.as_str() {
+                "TITLE" if store.stores_data(DataType::Title) => {
+                    store.set_title(last(v));
+                }
+                "ARTIST" if store.stores_data(DataType::Artists) => {
+                    store.set_artists(v);
+                }
+                "ALBUM" if store.stores_data(DataType::Album) => {
+                    store.set_album(last(v));
+                }
+                "ALBUM ARTIST" | "ALBUMARTIST"
+                    if store.stores_data(DataType::AlbumArtist) =>
+                {
+                    store.set_album_artist(last(v));
+                }
+                "GENRE" if store.stores_data(DataType::Genres) => {
+                    store.set_genres(v);
+                }
+                "YEAR"
+                    if store.stores_data(DataType::Year)
+                        || store.stores_data(DataType::Date)
+                        || store.stores_data(DataType::Time) =>
+                {
+                    if let Some(d) = trap.res(parsers::year(&last(v), trap))? {
+                        store.set_date_time(d);
+                    }
+                }
+                "TRACK"
+                    if store.stores_data(DataType::Track)
+                        || store.stores_data(DataType::TrackCount) =>
+                {
+                    if let Some((t, c)) =
+                        trap.res(parsers::num_of(&last(v), trap))?
+                    {
+                        store.set_track(t);
+                        if let Some(c) = c {
+                            store.set_track_count(c);
+                        }
+                    }
+                }
+                "DISC"
+                    if store.stores_data(DataType::Disc)
+                        || store.stores_data(DataType::DiscCount) =>
+                {
+                    if let Some((d, c)) =
+                        trap.res(parsers::num_of(&last(v), trap))?
+                    {
+                        store.set_disc(d);
+                        if let Some(c) = c {
+                            store.set_disc_count(c);
+                        }
+                    }
+                }
+                "COMMENT" if store.stores_data(DataType::Comments) => {
+                    comments.extend(v.into_iter().map(Comment::from_value));
+                }
+                "COPYRIGHT" if store.stores_data(DataType::Copyright) => {
+                    store.set_copyright(last(v));
+                }
+                k if store.stores_data(DataType::CustomFields)
+                    && !Self::KNOWN_KEYS.contains(&k) =>
+                {
+                    for v in v {
+                        store.add_custom_field(CustomField::text(
+                            item.key.clone(),
+                            v,
+                        ));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if !comments.is_empty() {
+            store.set_comments(comments);
+        }
+
+        replay_gain.store(store);
+
+        Ok(())
+    }
+}
+
+fn picture_kind(key: &str) -> Option<PictureKind> {
+    let kind = key.strip_prefix("COVER ART (")?.strip_suffix(')')?;
+    Some(match kind {
+        "OTHER" => PictureKind::OTHER,
+        "ICON" => PictureKind::ICON32,
+        "OTHER ICON" => PictureKind::OTHER_ICON,
+        "FRONT" => PictureKind::FRONT_COVER,
+        "BACK" => PictureKind::BACK_COVER,
+        "LEAFLET" => PictureKind::LEAFLET_PAGE,
+        "MEDIA" => PictureKind::MEDIA,
+        "LEAD ARTIST" => PictureKind::LEAD_ARTIST,
+        "ARTIST" => PictureKind::ARTIST,
+        "CONDUCTOR" => PictureKind::CONDUCTOR,
+        "BAND" => PictureKind::BAND,
+        "COMPOSER" => PictureKind::COMPOSER,
+        "LYRICIST" => PictureKind::LYRICIST,
+        "RECORDING LOCATION" => PictureKind::RECORDING_LOCATION,
+        "DURING RECORDING" => PictureKind::DURING_RECORDING,
+        "DURING PERFORMANCE" => PictureKind::DURING_PERFORMANCE,
+        "VIDEO CAPTURE" => PictureKind::MOVIE_CAPTURE,
+        "FISH" => PictureKind::BRIGHT_COLOURED_FISH,
+        "ILLUSTRATION" => PictureKind::ILLUSTRATION,
+        "BAND LOGOTYPE" => PictureKind::ARTIST_LOGOTYPE,
+        "PUBLISHER LOGOTYPE" => PictureKind::PUBLISHER_LOGOTYPE,
+        _ => return None,
+    })
+}
+
+/// Binary cover art contains file name terminated with null followed by the
+/// image data.
+fn read_picture(
+    d: &[u8],
+    kind: PictureKind,
+    trap: &impl Trap,
+) -> Result<Picture> {
+    let (len, name) = parsers::utf_8_nt(d, trap)?;
+    let ext = name.rsplit_once('.').map(|(_, e)| e.to_ascii_lowercase());
+    let mime = match ext.as_deref() {
+        Some("jpg" | "jpeg") => Some("image/jpeg"),
+        Some("png") => Some("image/png"),
+        Some("gif") => Some("image/gif"),
+        Some("bmp") => Some("image/bmp"),
+        _ => None,
+    };
+
+    let mut res = Picture::from_data(d[len..].to_vec(), kind);
+    res.mime = mime.map(|m| m.to_string());
+    res.description = (!name.is_empty()).then_some(name);
+    Ok(res)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::Strict;
+
+    fn item(key: &str, flags: u32, value: &[u8]) -> Vec<u8> {
+        let mut res = (value.len() as u32).to_le_bytes().to_vec();
+        res.extend(flags.to_le_bytes());
+        res.extend(key.as_bytes());
+        res.push(0);
+        res.extend_from_slice(value);
+        res
+    }
+
+    /// Header or footer of APE tag.
+    fn footer(version: u32, size: usize, cnt: usize, flags: u32) -> Vec<u8> {
+        let mut res = Footer::PREAMBLE.to_vec();
+        res.extend(version.to_le_bytes());
+        res.extend((size as u32).to_le_bytes());
+        res.extend((cnt as u32).to_le_bytes());
+        res.extend(flags.to_le_bytes());
+        res.extend([0; 8]);
+        res
+    }
+
+    /// Create file with audio data followed by APE tag with the given items.
+    fn file(version: u32, header: bool, items: &[Vec<u8>]) -> Vec<u8> {
+        let cnt = items.len();
+        let items = items.concat();
+        let size = items.len() + Footer::SIZE as usize;
+        let has_header = if header { 0x8000_0000 } else { 0 };
+
+        let mut res = b"audio".to_vec();
+        if header {
+            res.extend(footer(version, size, cnt, has_header | 0x2000_0000));
+        }
+        res.extend(items);
+        res.extend(footer(version, size, cnt, has_header));
+        res
+    }
+
+    #[test]
+    fn read_v2_with_header_before_id3v1() {
+        let mut data = file(
+            2000,
+            true,
+            &[
+                item("Title", 1, b"Song"),
+                item("Artist", 0, b"A\0B"),
+                item("Cover Art (Front)", 2, b"c.png\0img"),
+            ],
+        );
+        let end = data.len();
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, 0);
+        data.extend(id3v1);
+
+        let mut r = Cursor::new(&data);
+        let (pos, footer) = Footer::find(&mut r).unwrap().unwrap();
+        assert_eq!(pos, end as u64 - 32);
+        assert_eq!(footer.tag_size(), end as u64 - 5);
+
+        let tag = ApeTag::from_seek(r, &Strict).unwrap();
+        assert_eq!(tag.version, 2000);
+        assert_eq!(tag.items.len(), 3);
+        assert_eq!(tag.items[0].key, "Title");
+        assert!(tag.items[0].read_only);
+        assert!(matches!(
+            &tag.items[1].value,
+            ApeValue::Text(v) if v == &["A", "B"],
+        ));
+        assert!(matches!(
+            &tag.items[2].value,
+            ApeValue::Binary(d) if d == b"c.png\0img",
+        ));
+    }
+
+    #[test]
+    fn read_v1_without_header() {
+        // APEv1 has no flags, so the value is text.
+        let data = file(1000, false, &[item("Title", 2, b"Song")]);
+
+        let mut r = Cursor::new(&data);
+        let (_, footer) = Footer::find(&mut r).unwrap().unwrap();
+        assert!(footer.is_v1());
+        assert_eq!(footer.tag_size(), data.len() as u64 - 5);
+
+        let tag = ApeTag::from_seek(r, &Strict).unwrap();
+        assert_eq!(tag.version, 1000);
+        assert!(!tag.items[0].read_only);
+        assert!(matches!(
+            &tag.items[0].value,
+            ApeValue::Text(v) if v == &["Song"],
+        ));
+    }
+
+    #[test]
+    fn missing_footer() {
+        let data = b"audio data without tag".to_vec();
+        assert!(matches!(
+            ApeTag::from_seek(Cursor::new(data), &Strict),
+            Err(Error::NoTag)
+        ));
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{Read, Seek, SeekFrom};
+
+use crate::Result;
+
+#[derive(Debug)]
+pub struct Footer {
+    pub version: u32,
+    /// Size of the items and footer. Doesn't include header.
+    pub size: u32,
+    pub item_cnt: u32,
+    pub flags: u32,
+}
+
+impl Footer {
+    pub const PREAMBLE: &[u8] = b"APETAGEX";
+    pub const SIZE: u32 = 32;
+    const HAS_HEADER: u32 = 0x8000_0000;
+
+    pub fn from_bytes(d: &[u8; Self::SIZE as usize]) -> Option<Self> {
+        // 0..8: preamble
+        // 8..12: version
+        // 12..16: tag size
+        // 16..20: item count
+        // 20..24: flags
+        // 24..32: reserved
+        if !d.starts_with(Self::PREAMBLE) {
+            return None;
+        }
+
+        Some(Self {
+            version: u32::from_le_bytes(d[8..12].try_into().unwrap()),
+            size: u32::from_le_bytes(d[12..16].try_into().unwrap()),
+            item_cnt: u32::from_le_bytes(d[16..20].try_into().unwrap()),
+            flags: u32::from_le_bytes(d[20..24].try_into().unwrap()),
+        })
+    }
+
+    /// Find the footer of APE tag at the end of the file. It may be either
+    /// at the end of the file or just before ID3v1 tag. Returns the position
+    /// of the footer within the file.
+    pub fn find(r: &mut (impl Read + Seek)) -> Result<Option<(u64, Self)>> {
+        let len = r.seek(SeekFrom::End(0))?;
+
+        let mut end = len;
+        if has_magic(r, len, 128, b"TAG")? {
+            end -= 128;
+            if has_magic(r, len, 256, b"EXT")? {
+                end -= 128;
+            }
+        }
+
+        if end < Self::SIZE as u64 {
+            return Ok(None);
+        }
+
+        let pos = end - Self::SIZE as u64;
+        r.seek(SeekFrom::Start(pos))?;
+        let mut buf = [0; Self::SIZE as usize];
+        r.read_exact(&mut buf)?;
+        Ok(Self::from_bytes(&buf).map(|f| (pos, f)))
+    }
+
+    /// Total size of the tag in file including header.
+    pub fn tag_size(&self) -> u64 {
+        if !self.is_v1() && self.flags & Self::HAS_HEADER != 0 {
+            self.size as u64 + Self::SIZE as u64
+        } else {
+            self.size as u64
+        }
+    }
+
+    /// Version 1000 tags have no header and all values are text.
+    pub fn is_v1(&self) -> bool {
+        self.version < 2000
+    }
+}
+
+/// Check whether there are the given bytes at `offset` from the end of the
+/// stream with length `len`.
+pub fn has_magic(
+    r: &mut (impl Read + Seek),
+    len: u64,
+    offset: u64,
+    magic: &[u8; 3],
+) -> Result<bool> {
+    if len < offset {
+        return Ok(false);
+    }
+
+    r.seek(SeekFrom::Start(len - offset))?;
+    let mut buf = [0; 3];
+    r.read_exact(&mut buf)?;
+    Ok(buf == *magic)
+}
@@ -0,0 +1,74 @@
+mod ape_tag;
+mod footer;
+
+use std::{
+    fs::File,
+    io::{Read, Seek},
+    path::Path,
+};
+
+use crate::{
+    Error, Result, TagRead, TagStore, TagType,
+    file_edit::{self, Edit},
+    trap::Trap,
+};
+
+pub(crate) use self::footer::{Footer, has_magic};
+
+pub use self::ape_tag::*;
+
+// Implementation is based on:
+// - https://wiki.hydrogenaudio.org/index.php?title=APEv2_specification
+// - https://wiki.hydrogenaudio.org/index.php?title=APEv1_specification
+// - https://wiki.hydrogenaudio.org/index.php?title=APE_key
+
+/// Tag reader for APEv1 and APEv2.
+#[derive(Debug)]
+pub struct Ape;
+
+impl<R: Read + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Ape {
+    fn extensions(&self) -> &[&str] {
+        &["ape", "wv", "mpc", "mp+", "mpp", "ofr", "ofs", "tak"]
+    }
+
+    fn store(&self, r: &mut R, store: &mut S, trap: &T) -> Result<()> {
+        from_seek(r, store, trap)
+    }
+}
+
+/// Read APE tag from reader. The tag is searched for at the end of the
+/// stream.
+pub fn from_seek(
+    r: impl Read + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    ApeTag::from_seek(r, trap)?.store(store, trap)
+}
+
+/// Read APE tag from file.
+pub fn from_file(
+    f: impl AsRef<Path>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    ApeTag::from_file(f, trap)?.store(store, trap)
+}
+
+/// Remove APE tag from the end of the file. The tag may be either at the end
+/// or just before ID3v1 tag. Returns the type of the removed tag or `None` if
+/// the file doesn't have APE tag.
+pub fn remove_file(f: impl AsRef<Path>) -> Result<Option<TagType>> {
+    let f = f.as_ref();
+    let Some((pos, footer)) = Footer::find(&mut File::open(f)?)? else {
+        return Ok(None);
+    };
+
+    let end = pos + Footer::SIZE as u64;
+    let Some(start) = end.checked_sub(footer.tag_size()) else {
+        return Err(Error::InvalidLength);
+    };
+
+    file_edit::apply(f, &[Edit::new(start..end, vec![])])?;
+    Ok(Some(TagType::Ape((footer.version / 1000) as u8)))
+}
@@ -4,3 +4,5 @@ pub const CONTENT_DESCRIPTION: u128 =
     0x3326_b275_8e66_cf11_a6d9_00aa_0062_ce6c;
 pub const EXTENDED_CONTENT_DESCRIPTION: u128 =
     0x40a4_d0d2_07e3_d211_97f0_00a0_c95e_a850;
+pub const STREAM_PROPERTIES: u128 = 0x9107_dcb7_b7a9_cf11_8ee6_00c0_0c20_5365;
+pub const AUDIO_MEDIA: u128 = 0x409e_69f8_4d5b_cf11_a8fd_0080_5f5c_442b;
@@ -13,12 +13,15 @@ use std::{
 };
 
 use crate::{
-    Comment, DataType, Error, Picture, PictureKind, Rating, Result, TagRead,
-    TagStore, TagType, bread::Bread, parsers, trap::Trap,
+    AudioProperties, Codec, Comment, CustomField, CustomValue, DataType,
+    Error, Lyrics, LyricsContentType, Picture, PictureKind, Rating, Result,
+    SyncedLyrics, TagRead, TagStore, TagType, TimestampFormat, bread::Bread,
+    musicbrainz::MusicBrainzId, parsers, trap::Trap,
 };
 
 const STR: u16 = 0;
 const BYTES: u16 = 1;
+const BOOL: u16 = 2;
 const INT32: u16 = 3;
 const INT64: u16 = 4;
 const INT16: u16 = 5;
@@ -75,6 +78,8 @@ pub fn from_read(
     let mut count: u32 = r.get_le()?;
     r.seek_by(2)?;
 
+    let mut has_audio = false;
+
     while !store.done() && count > 0 {
         count -= 1;
         let mut header: Object = r.get()?;
@@ -87,6 +92,18 @@ pub fn from_read(
                     store.set_length(l);
                 }
             }
+            guid::STREAM_PROPERTIES
+                if !has_audio
+                    && store.stores_data(DataType::AudioProperties) =>
+            {
+                // Only the first audio stream is used.
+                if let Some(Some(p)) =
+                    r.witht(hsize, trap, read_stream_properties)?
+                {
+                    has_audio = true;
+                    store.set_audio_properties(p);
+                }
+            }
             guid::CONTENT_DESCRIPTION
                 if store.stores_data(DataType::Title)
                     || store.stores_data(DataType::Artists)
@@ -142,6 +159,11 @@ fn read_extended_content_description(
 
         let vsize = vlen as usize;
 
+        let mb = name
+            .strip_prefix("MusicBrainz/")
+            .and_then(MusicBrainzId::from_name)
+            .filter(|id| store.stores_data(id.data_type()));
+
         match name.as_str() {
             "WM/AlbumTitle" if store.stores_data(DataType::Album) => {
                 if let Some(a) =
@@ -186,6 +208,49 @@ fn read_extended_content_description(
                     store.set_album_artist(t);
                 }
             }
+            "WM/Lyrics" if store.stores_data(DataType::Lyrics) => {
+                if let Some(l) =
+                    r.witht(vsize, trap, |d, t| get_string(d, t, typ))?
+                {
+                    store.set_lyrics(vec![Lyrics::from_text(l)]);
+                }
+            }
+            "WM/Lyrics_Synchronised"
+                if store.stores_data(DataType::SyncedLyrics) =>
+            {
+                if let Some(l) =
+                    r.witht(vsize, trap, |d, t| read_synced_lyrics(d, t, typ))?
+                {
+                    store.set_synced_lyrics(vec![l]);
+                }
+            }
+            n if mb.is_some()
+                || (store.stores_data(DataType::CustomFields)
+                    && !is_known_descriptor(n)) =>
+            {
+                let Some(value) =
+                    r.witht(vsize, trap, |d, t| get_custom(d, t, typ))?
+                else {
+                    continue;
+                };
+
+                if let Some(id) = mb {
+                    match &value {
+                        CustomValue::Text(v) if typ == STR => {
+                            id.store(vec![v.clone()], store, trap)?;
+                        }
+                        _ => trap.error(Error::InvalidDataType)?,
+                    }
+                }
+
+                if store.stores_data(DataType::CustomFields) {
+                    store.add_custom_field(CustomField {
+                        namespace: None,
+                        key: name,
+                        value,
+                    });
+                }
+            }
             _ => r.seek_by(vlen as i64)?,
         }
     }
@@ -199,6 +264,23 @@ fn read_extended_content_description(
     Ok(())
 }
 
+/// Check whether the extended content descriptor is stored as known data
+/// type.
+fn is_known_descriptor(name: &str) -> bool {
+    matches!(
+        name,
+        "WM/AlbumTitle"
+            | "WM/Year"
+            | "WM/TrackNumber"
+            | "WM/PartOfSet"
+            | "WM/Genre"
+            | "WM/Picture"
+            | "WM/AlbumArtist"
+            | "WM/Lyrics"
+            | "WM/Lyrics_Synchronised"
+    )
+}
+
 fn read_picture(
     r: &mut Bread<impl BufRead + Seek>,
     store: &mut impl TagStore,
@@ -270,6 +352,63 @@ fn read_picture(
     Ok(())
 }
 
+fn read_synced_lyrics(
+    mut d: &[u8],
+    trap: &impl Trap,
+    typ: u16,
+) -> Result<SyncedLyrics> {
+    if typ != BYTES {
+        return Err(Error::InvalidDataType);
+    }
+
+    // 0: timestamp format
+    // 1: content type
+    // 2..: description, length of lyrics and the synchronized text
+    if d.len() < 2 {
+        return Err(Error::InvalidLength);
+    }
+
+    let Some(timestamp_format) = TimestampFormat::from_id3(d[0]) else {
+        return Err(Error::Unsupported(
+            "Timestamp format in WM/Lyrics_Synchronised.",
+        ));
+    };
+    let content_type = LyricsContentType::from_id3(d[1]);
+
+    d = &d[2..];
+    let (len, desc) = parsers::utf_16_le_nt(d, trap)?;
+    d = &d[len..];
+    if d.len() < 4 {
+        return Err(Error::InvalidLength);
+    }
+    let len = u32::from_le_bytes(d[..4].try_into().unwrap()) as usize;
+    d = &d[4..];
+    if len > d.len() {
+        return Err(Error::InvalidLength);
+    }
+    d = &d[..len];
+
+    let mut lines = vec![];
+    while !d.is_empty() {
+        let (len, text) = parsers::utf_16_le_nt(d, trap)?;
+        d = &d[len..];
+        if d.len() < 4 {
+            return Err(Error::InvalidLength);
+        }
+        let time = u32::from_le_bytes(d[..4].try_into().unwrap());
+        d = &d[4..];
+        lines.push((time, text));
+    }
+
+    Ok(SyncedLyrics {
+        language: None,
+        description: Some(desc),
+        content_type,
+        timestamp_format,
+        lines,
+    })
+}
+
 fn get_num<
     T: FromStr<Err = ParseIntError> + TryFrom<i64, Error = TryFromIntError>,
 >(
@@ -297,6 +436,18 @@ fn get_string(d: &[u8], trap: &impl Trap, typ: u16) -> Result<String> {
     Ok(parsers::utf_16_le_nt(d, trap)?.1)
 }
 
+fn get_custom(d: &[u8], trap: &impl Trap, typ: u16) -> Result<CustomValue> {
+    let v = match typ {
+        STR => parsers::utf_16_le_nt(d, trap)?.1,
+        BOOL => (u32::from_le_bytes(d.try_into()?) != 0).to_string(),
+        INT64 => u64::from_le_bytes(d.try_into()?).to_string(),
+        INT32 => u32::from_le_bytes(d.try_into()?).to_string(),
+        INT16 => u16::from_le_bytes(d.try_into()?).to_string(),
+        _ => return Ok(CustomValue::Binary(d.to_vec())),
+    };
+    Ok(CustomValue::Text(v))
+}
+
 fn read_content_description(
     r: &mut Bread<impl BufRead + Seek>,
     store: &mut impl TagStore,
@@ -357,3 +508,91 @@ fn read_file_properties(d: &[u8], _: &impl Trap) -> Result<Duration> {
         + Duration::from_nanos(dur % 10_000_000)
         - Duration::from_millis(preroll))
 }
+
+/// Read audio properties from stream properties object. Returns [`None`] if
+/// the stream is not audio stream.
+fn read_stream_properties(
+    d: &[u8],
+    _: &impl Trap,
+) -> Result<Option<AudioProperties>> {
+    // 0..16: stream type
+    // 16..32: error correction type
+    // 32..40: time offset
+    // 40..44: type specific data length
+    // 44..48: error correction data length
+    // 48..50: flags
+    // 50..54: reserved
+    // 54..: type specific data (WAVEFORMATEX for audio)
+    if d.len() < 54 {
+        return Err(Error::InvalidLength);
+    }
+
+    if u128::from_be_bytes(d[..16].try_into().unwrap()) != guid::AUDIO_MEDIA {
+        return Ok(None);
+    }
+
+    // 0..2: format tag
+    // 2..4: channels
+    // 4..8: samples per second
+    // 8..12: average bytes per second
+    // 12..14: block align
+    // 14..16: bits per sample
+    let Some(d) = d.get(54..70) else {
+        return Err(Error::InvalidLength);
+    };
+
+    let mut res = AudioProperties::new(Codec::from_wave_format(
+        u16::from_le_bytes(d[0..2].try_into().unwrap()),
+    ));
+    res.channels = Some(u16::from_le_bytes(d[2..4].try_into().unwrap()));
+    res.sample_rate = Some(u32::from_le_bytes(d[4..8].try_into().unwrap()));
+    res.bitrate = Some(
+        u32::from_le_bytes(d[8..12].try_into().unwrap()).saturating_mul(8),
+    );
+    res.bits_per_sample =
+        Some(u16::from_le_bytes(d[14..16].try_into().unwrap()))
+            .filter(|a| *a != 0);
+
+    Ok(Some(res))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::Strict;
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect()
+    }
+
+    #[test]
+    fn synced_lyrics() {
+        // Milliseconds, lyrics.
+        let mut d = vec![2, 1];
+        d.extend(utf16("desc"));
+        let mut lines = utf16("first");
+        lines.extend(1000_u32.to_le_bytes());
+        lines.extend(utf16("second"));
+        lines.extend(2500_u32.to_le_bytes());
+        d.extend((lines.len() as u32).to_le_bytes());
+        d.extend(lines);
+
+        let l = read_synced_lyrics(&d, &Strict, BYTES).unwrap();
+        assert_eq!(l.description.as_deref(), Some("desc"));
+        assert_eq!(l.timestamp_format, TimestampFormat::Milliseconds);
+        assert_eq!(l.content_type, LyricsContentType::Lyrics);
+        assert_eq!(
+            l.lines,
+            [(1000, "first".to_string()), (2500, "second".to_string())]
+        );
+
+        assert!(read_synced_lyrics(&d, &Strict, STR).is_err());
+        // Length of the lines is larger than the data.
+        assert!(
+            read_synced_lyrics(&d[..d.len() - 1], &Strict, BYTES).is_err()
+        );
+    }
+}
@@ -18,6 +18,12 @@ impl<R: Read> Bread<R> {
         Self { read, buf: vec![] }
     }
 
+    /// Get the underlying reader. Bread doesn't buffer any data, so the
+    /// reader is always at the same position as bread.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.read
+    }
+
     pub fn read_exact(&mut self, len: usize) -> Result<&[u8]> {
         self.make_size(len);
         self.read.read_exact(&mut self.buf[..len])?;
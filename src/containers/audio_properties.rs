@@ -0,0 +1,111 @@
+/// Technical properties of the audio stream.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AudioProperties {
+    /// Codec of the audio.
+    pub codec: Option<Codec>,
+    /// Sample rate in Hz.
+    pub sample_rate: Option<u32>,
+    /// Number of channels.
+    pub channels: Option<u16>,
+    /// Number of bits per sample.
+    pub bits_per_sample: Option<u16>,
+    /// Average bitrate in bits per second.
+    pub bitrate: Option<u32>,
+    /// Whether the codec is lossless.
+    pub lossless: Option<bool>,
+    /// Language of the audio as ISO 639-2 code.
+    pub language: Option<String>,
+}
+
+/// Audio codec.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Codec {
+    /// Uncompressed integer PCM.
+    Pcm,
+    /// Uncompressed floating point PCM.
+    FloatPcm,
+    /// G.711 A-law.
+    ALaw,
+    /// G.711 mu-law.
+    MuLaw,
+    /// Free Lossless Audio Codec.
+    Flac,
+    /// Vorbis.
+    Vorbis,
+    /// Opus.
+    Opus,
+    /// Speex.
+    Speex,
+    /// MPEG audio layer I.
+    Mp1,
+    /// MPEG audio layer II.
+    Mp2,
+    /// MPEG audio layer III.
+    Mp3,
+    /// Advanced Audio Coding (other than HE-AAC).
+    Aac,
+    /// High Efficiency AAC (AAC with spectral band replication).
+    HeAac,
+    /// High Efficiency AAC v2 (HE-AAC with parametric stereo).
+    HeAacV2,
+    /// Apple Lossless Audio Codec.
+    Alac,
+    /// Dolby Digital.
+    Ac3,
+    /// Windows Media Audio.
+    Wma,
+    /// Windows Media Audio Professional.
+    WmaPro,
+    /// Windows Media Audio Lossless.
+    WmaLossless,
+    /// Other codec. The string describes the codec identifier used by the
+    /// container.
+    Other(String),
+}
+
+impl AudioProperties {
+    /// Create properties with the given codec. Whether the codec is lossless
+    /// is deduced from the codec.
+    pub fn new(codec: Codec) -> Self {
+        Self {
+            lossless: codec.is_lossless(),
+            codec: Some(codec),
+            ..Default::default()
+        }
+    }
+}
+
+impl Codec {
+    /// Check whether the codec is lossless. Returns [`None`] if it is not
+    /// known.
+    pub fn is_lossless(&self) -> Option<bool> {
+        match self {
+            Self::Pcm
+            | Self::FloatPcm
+            | Self::Flac
+            | Self::Alac
+            | Self::WmaLossless => Some(true),
+            Self::Other(_) => None,
+            _ => Some(false),
+        }
+    }
+
+    /// Get codec from the format tag used by `WAVEFORMATEX` in riff and asf.
+    pub(crate) fn from_wave_format(tag: u16) -> Self {
+        match tag {
+            0x0001 => Self::Pcm,
+            0x0003 => Self::FloatPcm,
+            0x0006 => Self::ALaw,
+            0x0007 => Self::MuLaw,
+            0x0050 => Self::Mp2,
+            0x0055 => Self::Mp3,
+            0x00FF | 0x1610 => Self::Aac,
+            0x0161 => Self::Wma,
+            0x0162 => Self::WmaPro,
+            0x0163 => Self::WmaLossless,
+            0x2000 => Self::Ac3,
+            0xF1AC => Self::Flac,
+            _ => Self::Other(format!("WAVE format 0x{tag:04X}")),
+        }
+    }
+}
@@ -0,0 +1,32 @@
+/// Field that isn't covered by any other data type. For example user defined
+/// text in ID3v2 or vorbis comment with unknown key.
+#[derive(Debug, Clone)]
+pub struct CustomField {
+    /// Namespace of the key. This is the mean of mp4 freeform atom (`----`)
+    /// or the frame id in ID3v2 (`TXXX` or `WXXX`).
+    pub namespace: Option<String>,
+    /// Key of the field. This is the description in ID3v2.
+    pub key: String,
+    /// Value of the field.
+    pub value: CustomValue,
+}
+
+/// Value of custom field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CustomValue {
+    /// Text value.
+    Text(String),
+    /// Binary value.
+    Binary(Vec<u8>),
+}
+
+impl CustomField {
+    /// Create custom field with text value and no namespace.
+    pub fn text(key: String, value: String) -> Self {
+        Self {
+            namespace: None,
+            key,
+            value: CustomValue::Text(value),
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use std::time::Duration;
+
+/// Date and time. Any of the parts may be missing.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DateTime {
+    /// Year.
+    pub year: Option<i32>,
+    /// Month and day of month.
+    pub date: Option<(u8, u8)>,
+    /// Time of day.
+    pub time: Option<Duration>,
+}
+
+impl DateTime {
+    /// Checks whether no part of the date and time is known.
+    pub fn is_empty(&self) -> bool {
+        self.year.is_none() && self.date.is_none() && self.time.is_none()
+    }
+}
@@ -0,0 +1,96 @@
+/// Unsynchronized lyrics of song.
+#[derive(Debug)]
+pub struct Lyrics {
+    /// Language of the lyrics as ISO 639-2 code.
+    pub language: Option<String>,
+    /// Description of the lyrics.
+    pub description: Option<String>,
+    /// The lyrics text.
+    pub text: String,
+}
+
+/// Lyrics or other text synchronized with the audio.
+#[derive(Debug)]
+pub struct SyncedLyrics {
+    /// Language of the lyrics as ISO 639-2 code.
+    pub language: Option<String>,
+    /// Description of the lyrics.
+    pub description: Option<String>,
+    /// Type of the content.
+    pub content_type: LyricsContentType,
+    /// Unit of the timestamps.
+    pub timestamp_format: TimestampFormat,
+    /// Text with the time at which it starts.
+    pub lines: Vec<(u32, String)>,
+}
+
+/// Type of content of synchronized lyrics.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LyricsContentType {
+    /// Other or unknown.
+    #[default]
+    Other,
+    /// Lyrics.
+    Lyrics,
+    /// Transcription of the text.
+    Transcription,
+    /// Names of movements or parts.
+    PartName,
+    /// Events, e.g. "Don Quijote enters the stage".
+    Events,
+    /// Chords.
+    Chord,
+    /// Trivia or pop up information.
+    Trivia,
+    /// URLs to webpages.
+    WebpageUrls,
+    /// URLs to images.
+    ImageUrls,
+}
+
+/// Unit of timestamps.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Number of MPEG frames since the start of the audio.
+    MpegFrames,
+    /// Number of milliseconds since the start of the audio.
+    #[default]
+    Milliseconds,
+}
+
+impl Lyrics {
+    /// Create lyrics with only the text.
+    pub fn from_text(text: String) -> Self {
+        Self {
+            language: None,
+            description: None,
+            text,
+        }
+    }
+}
+
+impl LyricsContentType {
+    pub(crate) fn from_id3(t: u8) -> Self {
+        match t {
+            1 => Self::Lyrics,
+            2 => Self::Transcription,
+            3 => Self::PartName,
+            4 => Self::Events,
+            5 => Self::Chord,
+            6 => Self::Trivia,
+            7 => Self::WebpageUrls,
+            8 => Self::ImageUrls,
+            _ => Self::Other,
+        }
+    }
+}
+
+impl TimestampFormat {
+    pub(crate) fn from_id3(t: u8) -> Option<Self> {
+        match t {
+            1 => Some(Self::MpegFrames),
+            2 => Some(Self::Milliseconds),
+            _ => None,
+        }
+    }
+}
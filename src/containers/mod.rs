@@ -1,4 +1,8 @@
+mod audio_properties;
 mod comment;
+mod custom_field;
+mod date_time;
+mod lyrics;
 mod picture;
 mod picture_kind;
 mod popularimeter;
@@ -6,6 +10,6 @@ mod rating;
 mod tag_type;
 
 pub use self::{
-    comment::*, picture::*, picture_kind::*, popularimeter::*, rating::*,
-    tag_type::*,
+    audio_properties::*, comment::*, custom_field::*, date_time::*, lyrics::*,
+    picture::*, picture_kind::*, popularimeter::*, rating::*, tag_type::*,
 };
@@ -81,6 +81,16 @@ impl PictureKind {
         }
     }
 
+    /// Get the id3 picture type. If multiple kinds are set, the first one is
+    /// used.
+    pub(crate) fn to_id3(self) -> u8 {
+        if self.is_empty() || !self.intersects(Self::all_id3()) {
+            0
+        } else {
+            self.bits().trailing_zeros() as u8
+        }
+    }
+
     pub(crate) fn all_id3() -> Self {
         Self::from_bits_retain(0x1f_ffff)
     }
@@ -13,7 +13,15 @@ pub enum TagType {
     Asf,
     /// Riff (WAVE -> [`crate::riff::chunk::WAVE`])
     Riff(u32),
-    /// Vorbis comment. (usually combined with [`TagType::Flac`])
+    /// Aiff (AIFF -> [`crate::aiff::chunk::AIFF`], AIFF-C ->
+    /// [`crate::aiff::chunk::AIFC`])
+    Aiff(u32),
+    /// APEv1, APEv2
+    Ape(u8),
+    /// Ogg (usually combined with [`TagType::VorbisComment`])
+    Ogg,
+    /// Vorbis comment. (usually combined with [`TagType::Flac`] or
+    /// [`TagType::Ogg`])
     VorbisComment,
     /// Other tag format (by some foregin tag reader)
     Other(&'static str),
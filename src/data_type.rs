@@ -37,6 +37,92 @@ pub enum DataType {
     Copyright,
     /// Ratings by users.
     Ratings,
+    /// Composers of the song.
+    Composers,
+    /// Conductor of the performance.
+    Conductor,
+    /// Artist who remixed or modified the song.
+    Remixer,
+    /// Lyricists of the song.
+    Lyricists,
+    /// Beats per minute.
+    Bpm,
+    /// Initial musical key of the song.
+    InitialKey,
+    /// International Standard Recording Code.
+    Isrc,
+    /// Person or organization that encoded the file.
+    EncodedBy,
+    /// Software and settings used for encoding.
+    EncoderSettings,
+    /// Publisher or label.
+    Publisher,
+    /// Content group of the song (e.g. part of larger work).
+    Grouping,
+    /// Subtitle or description refinement of the title.
+    Subtitle,
+    /// Mood of the song.
+    Mood,
+    /// Languages of the lyrics.
+    Languages,
+    /// Title of the original album.
+    OriginalAlbum,
+    /// Artists of the original song.
+    OriginalArtists,
+    /// Original file name.
+    OriginalFilename,
+    /// Title used for sorting.
+    TitleSort,
+    /// Album used for sorting.
+    AlbumSort,
+    /// Artist used for sorting.
+    ArtistSort,
+    /// Album artist used for sorting.
+    AlbumArtistSort,
+    /// Composer used for sorting.
+    ComposerSort,
+    /// The song is part of compilation.
+    Compilation,
+    /// Time when the file was encoded.
+    EncodingTime,
+    /// Time of the recording.
+    RecordingTime,
+    /// Time of the release.
+    ReleaseTime,
+    /// Time of the original release.
+    OriginalReleaseTime,
+    /// Unsynchronized lyrics.
+    Lyrics,
+    /// Lyrics synchronized with the audio.
+    SyncedLyrics,
+    /// Fields that are not covered by the other data types.
+    CustomFields,
+    /// MusicBrainz recording id.
+    MusicBrainzRecordingId,
+    /// MusicBrainz track id. This identifies the track within release.
+    MusicBrainzTrackId,
+    /// MusicBrainz release id.
+    MusicBrainzReleaseId,
+    /// MusicBrainz release group id.
+    MusicBrainzReleaseGroupId,
+    /// MusicBrainz artist ids.
+    MusicBrainzArtistIds,
+    /// MusicBrainz album artist ids.
+    MusicBrainzAlbumArtistIds,
+    /// MusicBrainz work id.
+    MusicBrainzWorkId,
+    /// MusicBrainz disc id.
+    MusicBrainzDiscId,
+    /// ReplayGain gain of the track.
+    TrackGain,
+    /// ReplayGain peak of the track.
+    TrackPeak,
+    /// ReplayGain gain of the album.
+    AlbumGain,
+    /// ReplayGain peak of the album.
+    AlbumPeak,
+    /// Technical properties of the audio.
+    AudioProperties,
     /// Type of tag.
     TagType,
 }
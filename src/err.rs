@@ -30,6 +30,9 @@ pub enum Error {
     /// Vorbis framing bit is not set.
     #[error("Vorbis framing bit is not set.")]
     InvalidVorbisFramingBit,
+    /// Ogg packet doesn't have the expected format.
+    #[error("Invalid ogg packet.")]
+    InvalidOggPacket,
     /// Invalid data type.
     #[error("Invalid data type.")]
     InvalidDataType,
@@ -42,12 +45,21 @@ pub enum Error {
     /// Missing BOM.
     #[error("Missing BOM.")]
     MissingBom,
+    /// CRC of the data doesn't match.
+    #[error("CRC doesn't match.")]
+    InvalidCrc,
+    /// Data are larger than the configured limit.
+    #[error("Data are too large.")]
+    TooLarge,
     /// Feature required to parse the tag is not supported.
     #[error("Not supported: {0}")]
     Unsupported(&'static str),
     /// Failed to parse number.
     #[error(transparent)]
     ParseInt(#[from] std::num::ParseIntError),
+    /// Failed to parse floating point number.
+    #[error(transparent)]
+    ParseFloat(#[from] std::num::ParseFloatError),
     /// Any IO error.
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -57,4 +69,7 @@ pub enum Error {
     /// Failed to convert integer types.
     #[error(transparent)]
     TryFromInt(#[from] std::num::TryFromIntError),
+    /// Failed to parse UUID.
+    #[error("Invalid UUID: {0}")]
+    Uuid(#[from] uuid::Error),
 }
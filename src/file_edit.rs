@@ -0,0 +1,161 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufReader, BufWriter, Read, Seek, SeekFrom, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+};
+
+use crate::Result;
+
+/// Replacement of range of bytes within file.
+#[derive(Debug)]
+pub struct Edit {
+    /// Range of bytes in the original file that will be replaced.
+    pub range: Range<u64>,
+    /// New data for the range.
+    pub data: Vec<u8>,
+}
+
+impl Edit {
+    pub fn new(range: Range<u64>, data: Vec<u8>) -> Self {
+        Self { range, data }
+    }
+
+    fn keeps_size(&self) -> bool {
+        self.range.end - self.range.start == self.data.len() as u64
+    }
+}
+
+/// Apply the edits to the file. The edits must be sorted and must not
+/// overlap.
+///
+/// If the edits don't change the positions of the other data in the file, the
+/// file is modified in place. Otherwise the new file is first written to
+/// temporary file in the same directory that will than replace the original
+/// file. This way the original file will stay intact if something fails.
+pub fn apply(path: impl AsRef<Path>, edits: &[Edit]) -> Result<()> {
+    let path = path.as_ref();
+    let mut f = OpenOptions::new().read(true).write(true).open(path)?;
+    let len = f.seek(SeekFrom::End(0))?;
+
+    let in_place = match edits.split_last() {
+        None => return Ok(()),
+        Some((last, rest)) => {
+            rest.iter().all(Edit::keeps_size)
+                && (last.keeps_size() || last.range.end == len)
+        }
+    };
+
+    if in_place {
+        for e in edits {
+            f.seek(SeekFrom::Start(e.range.start))?;
+            f.write_all(&e.data)?;
+        }
+        let last = edits.last().unwrap();
+        if !last.keeps_size() {
+            f.set_len(last.range.start + last.data.len() as u64)?;
+        }
+        f.sync_all()?;
+        return Ok(());
+    }
+
+    let tmp = temp_path(path);
+    let res = rewrite(&mut f, &tmp, edits, len).and_then(|_| {
+        fs::set_permissions(&tmp, f.metadata()?.permissions())?;
+        drop(f);
+        fs::rename(&tmp, path)?;
+        Ok(())
+    });
+    if res.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+
+    res
+}
+
+fn rewrite(f: &mut File, tmp: &Path, edits: &[Edit], len: u64) -> Result<()> {
+    let out = OpenOptions::new().write(true).create_new(true).open(tmp)?;
+    let mut out = BufWriter::new(out);
+    f.rewind()?;
+    let mut f = BufReader::new(f);
+
+    let mut pos = 0;
+    for e in edits {
+        copy_exact(&mut f, &mut out, e.range.start - pos)?;
+        out.write_all(&e.data)?;
+        f.seek_relative((e.range.end - e.range.start) as i64)?;
+        pos = e.range.end;
+    }
+    copy_exact(&mut f, &mut out, len - pos)?;
+
+    out.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    Ok(())
+}
+
+fn copy_exact(r: impl Read, w: &mut impl Write, len: u64) -> Result<()> {
+    let cnt = io::copy(&mut r.take(len), w)?;
+    if cnt != len {
+        return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+    }
+    Ok(())
+}
+
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".ratag-tmp");
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use super::*;
+
+    fn temp_file(name: &str, data: &[u8]) -> PathBuf {
+        let path = env::temp_dir()
+            .join(format!("ratag-edit-{}-{name}", process::id()));
+        fs::write(&path, data).unwrap();
+        path
+    }
+
+    #[test]
+    fn edit_in_place() {
+        let path = temp_file("in-place", b"abcdef");
+        let res = apply(&path, &[Edit::new(1..3, b"xy".to_vec())]);
+        let data = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        res.unwrap();
+        assert_eq!(data.unwrap(), b"axydef");
+    }
+
+    #[test]
+    fn edit_with_rewrite() {
+        let path = temp_file("rewrite", b"abcdef");
+        let res = apply(
+            &path,
+            &[Edit::new(1..2, b"xyz".to_vec()), Edit::new(4..4, vec![])],
+        );
+        let data = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        res.unwrap();
+        assert_eq!(data.unwrap(), b"axyzcdef");
+        assert!(!temp_path(&path).exists());
+    }
+
+    #[test]
+    fn failed_rewrite_removes_temp_file() {
+        let path = temp_file("failed", b"abcdef");
+        // The second edit is past the end of the file.
+        let res = apply(
+            &path,
+            &[Edit::new(0..1, b"xy".to_vec()), Edit::new(10..12, vec![])],
+        );
+        let data = fs::read(&path);
+        let tmp_exists = temp_path(&path).exists();
+        let _ = fs::remove_file(&path);
+        assert!(res.is_err());
+        assert_eq!(data.unwrap(), b"abcdef");
+        assert!(!tmp_exists);
+    }
+}
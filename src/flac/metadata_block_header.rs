@@ -13,9 +13,21 @@ pub struct MetadataBlockHeader {
 
 impl MetadataBlockHeader {
     pub const STREAMINFO: u8 = 0;
+    pub const PADDING: u8 = 1;
     pub const VORBISCOMMENT: u8 = 4;
     pub const PICTURE: u8 = 6;
 
+    pub const SIZE: usize = 4;
+    pub const MAX_LENGTH: usize = 0xFF_FFFF;
+
+    pub fn new(block_type: u8, length: u32) -> Self {
+        Self {
+            last: false,
+            block_type,
+            length,
+        }
+    }
+
     pub fn from_bytes(d: &[u8; 4]) -> Self {
         let last = (d[0] & 0x80) == 0x80;
         let block_type = d[0] & 0x7f;
@@ -26,6 +38,12 @@ impl MetadataBlockHeader {
             length,
         }
     }
+
+    pub fn to_bytes(&self) -> [u8; 4] {
+        let mut res = self.length.to_be_bytes();
+        res[0] = self.block_type | if self.last { 0x80 } else { 0 };
+        res
+    }
 }
 
 impl<R: BufRead> Breadable<R> for MetadataBlockHeader {
@@ -1,5 +1,7 @@
 mod metadata_block_header;
-mod streaminfo;
+pub(crate) mod streaminfo;
+mod write_options;
+mod writer;
 
 use encoding::{
     Encoding,
@@ -8,15 +10,18 @@ use encoding::{
 
 use self::{metadata_block_header::*, streaminfo::*};
 
+pub use self::{write_options::*, writer::*};
+
 use std::{
     fs::File,
-    io::{BufRead, BufReader, Seek},
+    io::{BufRead, BufReader, Seek, SeekFrom},
     path::Path,
     time::Duration,
 };
 
 use crate::{
-    DataType, Error, Picture, PictureKind, Result, TagRead, TagStore, TagType,
+    AudioProperties, Codec, DataType, Error, Picture, PictureKind, Result,
+    TagRead, TagSource, TagSourceExt, TagStore, TagType, TagWrite,
     bread::Bread, trap::Trap, vorbis,
 };
 
@@ -36,6 +41,26 @@ impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Flac {
     }
 }
 
+impl<S: TagSource> TagWrite<S> for Flac {
+    fn extensions(&self) -> &[&str] {
+        &["flac"]
+    }
+
+    fn supports_data(&self, typ: DataType) -> bool {
+        !matches!(
+            typ,
+            DataType::Length | DataType::Ratings | DataType::TagType
+        )
+    }
+
+    fn write(&self, path: &Path, src: &S) -> Result<Vec<DataType>> {
+        write_file(path, src, &WriteOptions::default())?;
+        Ok(src.unsupported_data(|t| {
+            <Self as TagWrite<S>>::supports_data(self, t)
+        }))
+    }
+}
+
 /// Read metadata from flac file.
 pub fn from_file(
     f: impl AsRef<Path>,
@@ -68,17 +93,28 @@ pub fn from_read(
 
     store.set_tag_type(TagType::Flac);
 
+    let mut props = None;
+
     let mut next = true;
     while next && !store.done() {
         let header: MetadataBlockHeader = r.get()?;
         next = !header.last;
         match header.block_type {
             MetadataBlockHeader::STREAMINFO
-                if store.stores_data(DataType::Length) =>
+                if store.stores_data(DataType::Length)
+                    || store.stores_data(DataType::AudioProperties) =>
             {
                 let si: Streaminfo = r.get()?;
-                let secs = si.sample_cnt as f64 / si.sample_rate as f64;
-                store.set_length(Duration::from_secs_f64(secs));
+                let mut secs = 0.;
+                if si.sample_rate > 0 {
+                    secs = si.sample_cnt as f64 / si.sample_rate as f64;
+                } else {
+                    trap.error(Error::InvalidLength)?;
+                }
+                if store.stores_data(DataType::Length) && secs > 0. {
+                    store.set_length(Duration::from_secs_f64(secs));
+                }
+                props = Some((si, secs));
             }
             MetadataBlockHeader::VORBISCOMMENT => {
                 vorbis::from_bread(&mut r, store, trap, false)?;
@@ -90,6 +126,23 @@ pub fn from_read(
         }
     }
 
+    if let Some((si, secs)) = props
+        && store.stores_data(DataType::AudioProperties)
+    {
+        let mut p = AudioProperties::new(Codec::Flac);
+        p.sample_rate = Some(si.sample_rate);
+        p.channels = Some(si.channels as u16);
+        p.bits_per_sample = Some(si.bits_per_sample as u16);
+        // Bitrate can be computed only if the whole metadata was read.
+        if !next && secs > 0. {
+            let start = r.seek(SeekFrom::Current(0))?;
+            let end = r.seek(SeekFrom::End(0))?;
+            p.bitrate =
+                Some(((end.saturating_sub(start)) as f64 * 8. / secs) as u32);
+        }
+        store.set_audio_properties(p);
+    }
+
     Ok(())
 }
 
@@ -168,3 +221,71 @@ fn read_picture(
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    #[derive(Default)]
+    struct Store {
+        length: Option<Duration>,
+        props: Option<AudioProperties>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(typ, DataType::Length | DataType::AudioProperties)
+        }
+
+        fn set_length(&mut self, length: Duration) {
+            self.length = Some(length);
+        }
+
+        fn set_audio_properties(&mut self, props: AudioProperties) {
+            self.props = Some(props);
+        }
+    }
+
+    /// Create flac file with only streaminfo and the given amount of audio
+    /// data.
+    fn file(sample_rate: u64, audio_len: usize) -> Vec<u8> {
+        // 2 channels, 16 bits, 2 seconds at 44100 Hz.
+        let d = sample_rate << 44 | 1 << 41 | 15 << 36 | 88200;
+        let mut res = b"fLaC\x80\0\0\x22".to_vec();
+        res.extend([0; 10]);
+        res.extend(d.to_be_bytes());
+        res.extend([0; 16]);
+        res.resize(res.len() + audio_len, 0);
+        res
+    }
+
+    #[test]
+    fn audio_properties() {
+        let mut store = Store::default();
+        from_read(Cursor::new(file(44100, 50000)), &mut store, &Strict)
+            .unwrap();
+        assert_eq!(store.length, Some(Duration::from_secs(2)));
+        let p = store.props.unwrap();
+        assert_eq!(p.codec, Some(Codec::Flac));
+        assert_eq!(p.sample_rate, Some(44100));
+        assert_eq!(p.channels, Some(2));
+        assert_eq!(p.bits_per_sample, Some(16));
+        assert_eq!(p.bitrate, Some(200000));
+        assert_eq!(p.lossless, Some(true));
+    }
+
+    #[test]
+    fn zero_sample_rate() {
+        let d = file(0, 100);
+        let mut store = Store::default();
+        assert!(from_read(Cursor::new(&d), &mut store, &Strict).is_err());
+
+        let mut store = Store::default();
+        from_read(Cursor::new(&d), &mut store, &Skip).unwrap();
+        assert_eq!(store.length, None);
+        assert_eq!(store.props.unwrap().bitrate, None);
+    }
+}
@@ -8,6 +8,8 @@ use crate::{
 pub struct Streaminfo {
     pub sample_cnt: u64,
     pub sample_rate: u32,
+    pub channels: u8,
+    pub bits_per_sample: u8,
 }
 
 impl Streaminfo {
@@ -25,11 +27,15 @@ impl Streaminfo {
         // 23..28: bits per sample
         // 28..64: sample count
         let sample_rate = (d >> 44) as u32;
-        let sample_cnt = d & 0xFFF_FFFF;
+        let channels = ((d >> 41) & 0x7) as u8 + 1;
+        let bits_per_sample = ((d >> 36) & 0x1F) as u8 + 1;
+        let sample_cnt = d & 0xF_FFFF_FFFF;
 
         Self {
             sample_cnt,
             sample_rate,
+            channels,
+            bits_per_sample,
         }
     }
 }
@@ -39,3 +45,28 @@ impl<R: BufRead> Breadable<R> for Streaminfo {
         bread.withc(Self::from_bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Create streaminfo with the given sample rate, channel count, bits
+    /// per sample and sample count.
+    fn streaminfo(rate: u64, ch: u64, bits: u64, cnt: u64) -> [u8; 34] {
+        let mut res = [0; 34];
+        let d = rate << 44 | (ch - 1) << 41 | (bits - 1) << 36 | cnt;
+        res[10..18].copy_from_slice(&d.to_be_bytes());
+        res
+    }
+
+    #[test]
+    fn from_bytes() {
+        // Sample count that doesn't fit into 32 bits.
+        let si =
+            Streaminfo::from_bytes(&streaminfo(96000, 6, 24, 0x9_0000_0001));
+        assert_eq!(si.sample_rate, 96000);
+        assert_eq!(si.channels, 6);
+        assert_eq!(si.bits_per_sample, 24);
+        assert_eq!(si.sample_cnt, 0x9_0000_0001);
+    }
+}
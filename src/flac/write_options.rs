@@ -0,0 +1,14 @@
+/// Options for writing flac metadata.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Size of the padding block that will be added if the file has to be
+    /// rewritten. Padding allows future modifications of the metadata without
+    /// rewriting the whole file.
+    pub padding: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self { padding: 4096 }
+    }
+}
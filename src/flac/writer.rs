@@ -0,0 +1,379 @@
+use std::{fs::File, io::BufReader, path::Path};
+
+use crate::{
+    DataType, Error, Picture, PictureKind, Result, TagSource, TagSourceExt,
+    TagType,
+    bread::Bread,
+    file_edit::{self, Edit},
+    flac::{MetadataBlockHeader, WriteOptions},
+    trap::Strict,
+    vorbis::VorbisTag,
+};
+
+/// Vendor string used when the file doesn't have vorbis comment yet.
+const VENDOR: &str = "ratag";
+
+/// Write the data from the source into the flac file. Existing vorbis comment
+/// is replaced, comments that are not set from [`TagSource`] are kept. Picture
+/// blocks are replaced only if the source provides pictures. All the other
+/// blocks are kept.
+///
+/// If the new metadata fits into the space of the old metadata and padding,
+/// the file is modified in place. Otherwise the whole file is rewritten
+/// through temporary file.
+///
+/// Returns error if the existing vorbis comment cannot be read, so that its
+/// comments are not lost.
+pub fn write_file(
+    f: impl AsRef<Path>,
+    src: &impl TagSource,
+    opts: &WriteOptions,
+) -> Result<()> {
+    let f = f.as_ref();
+    let mut r = Bread::new(BufReader::new(File::open(f)?));
+    if !r.expect(b"fLaC")? {
+        return Err(Error::NoTag);
+    }
+
+    let pictures = src.replaces(DataType::Picture(PictureKind::all()));
+
+    let mut blocks = vec![];
+    let mut tag_pos = None;
+    let mut vorbis = None;
+    let mut end = 4;
+
+    let mut next = true;
+    while next {
+        let header: MetadataBlockHeader = r.get()?;
+        next = !header.last;
+        end += (MetadataBlockHeader::SIZE + header.length as usize) as u64;
+
+        match header.block_type {
+            MetadataBlockHeader::VORBISCOMMENT if vorbis.is_none() => {
+                tag_pos.get_or_insert(blocks.len());
+                let data = r.read_exact_owned(header.length as usize)?;
+                let mut d = Bread::new(data.as_slice());
+                // Comments that cannot be read would be lost.
+                vorbis = Some(VorbisTag::from_bread(&mut d, &Strict, false)?);
+            }
+            MetadataBlockHeader::VORBISCOMMENT => {
+                tag_pos.get_or_insert(blocks.len());
+                r.seek_by(header.length as i64)?;
+            }
+            MetadataBlockHeader::PICTURE if pictures => {
+                tag_pos.get_or_insert(blocks.len());
+                r.seek_by(header.length as i64)?;
+            }
+            MetadataBlockHeader::PADDING => {
+                r.seek_by(header.length as i64)?;
+            }
+            typ => {
+                let data = r.read_exact_owned(header.length as usize)?;
+                blocks.push((typ, data));
+            }
+        }
+    }
+
+    let mut tag = vorbis.unwrap_or_else(|| VorbisTag::new(VENDOR.into()));
+    tag.update(src);
+
+    let mut new_blocks =
+        vec![(MetadataBlockHeader::VORBISCOMMENT, tag.to_bytes(false))];
+    new_blocks.extend(
+        src.pictures()
+            .iter()
+            .map(|p| (MetadataBlockHeader::PICTURE, picture_block(p))),
+    );
+    // Streaminfo must be always the first block.
+    let pos = tag_pos.unwrap_or(blocks.len()).max(1).min(blocks.len());
+    blocks.splice(pos..pos, new_blocks);
+
+    let old_len = end as usize - 4;
+    let len: usize = blocks
+        .iter()
+        .map(|(_, d)| MetadataBlockHeader::SIZE + d.len())
+        .sum();
+
+    let padding = if len == old_len {
+        None
+    } else if let Some(gap) =
+        old_len.checked_sub(len + MetadataBlockHeader::SIZE)
+        && gap <= MetadataBlockHeader::MAX_LENGTH
+    {
+        Some(gap)
+    } else if opts.padding == 0 {
+        None
+    } else {
+        Some(opts.padding.min(MetadataBlockHeader::MAX_LENGTH))
+    };
+
+    if let Some(p) = padding {
+        blocks.push((MetadataBlockHeader::PADDING, vec![0; p]));
+    }
+
+    let mut data = Vec::with_capacity(len);
+    let cnt = blocks.len();
+    for (i, (typ, d)) in blocks.into_iter().enumerate() {
+        if d.len() > MetadataBlockHeader::MAX_LENGTH {
+            return Err(Error::InvalidLength);
+        }
+        let mut header = MetadataBlockHeader::new(typ, d.len() as u32);
+        header.last = i + 1 == cnt;
+        data.extend(header.to_bytes());
+        data.extend(d);
+    }
+
+    file_edit::apply(f, &[Edit::new(4..end, data)])
+}
+
+/// Turn metadata blocks of the given tags into padding. Blocks of
+/// [`TagType::VorbisComment`] are the vorbis comments and blocks of
+/// [`TagType::Flac`] are the pictures. Other tag types are ignored.
+///
+/// Returns the tag types whose blocks were removed. The file is always
+/// modified in place.
+pub fn remove_file(
+    f: impl AsRef<Path>,
+    types: &[TagType],
+) -> Result<Vec<TagType>> {
+    let f = f.as_ref();
+    let mut r = Bread::new(BufReader::new(File::open(f)?));
+    if !r.expect(b"fLaC")? {
+        return Ok(vec![]);
+    }
+
+    let mut edits = vec![];
+    let mut removed = vec![];
+    let mut pos = 4;
+
+    let mut next = true;
+    while next {
+        let header: MetadataBlockHeader = r.get()?;
+        next = !header.last;
+        let typ = match header.block_type {
+            MetadataBlockHeader::VORBISCOMMENT => Some(TagType::VorbisComment),
+            MetadataBlockHeader::PICTURE => Some(TagType::Flac),
+            _ => None,
+        };
+
+        let len = (MetadataBlockHeader::SIZE + header.length as usize) as u64;
+        if let Some(typ) = typ.filter(|a| types.contains(a)) {
+            let padding = MetadataBlockHeader {
+                block_type: MetadataBlockHeader::PADDING,
+                ..header
+            };
+            let mut data = padding.to_bytes().to_vec();
+            data.resize(len as usize, 0);
+            edits.push(Edit::new(pos..pos + len, data));
+            if !removed.contains(&typ) {
+                removed.push(typ);
+            }
+        }
+
+        r.seek_by(header.length as i64)?;
+        pos += len;
+    }
+
+    file_edit::apply(f, &edits)?;
+    Ok(removed)
+}
+
+fn picture_block(p: &Picture) -> Vec<u8> {
+    let mime = if p.is_uri {
+        "-->"
+    } else {
+        p.mime.as_deref().unwrap_or_default()
+    };
+    let desc = p.description.as_deref().unwrap_or_default();
+    let (width, height) = p.size.unwrap_or_default();
+
+    let mut res = vec![];
+    res.extend((p.kind.to_id3() as u32).to_be_bytes());
+    res.extend((mime.len() as u32).to_be_bytes());
+    res.extend_from_slice(mime.as_bytes());
+    res.extend((desc.len() as u32).to_be_bytes());
+    res.extend_from_slice(desc.as_bytes());
+    res.extend((width as u32).to_be_bytes());
+    res.extend((height as u32).to_be_bytes());
+    res.extend(p.color_depth.unwrap_or_default().to_be_bytes());
+    res.extend((p.palette_size.unwrap_or_default() as u32).to_be_bytes());
+    res.extend((p.data.len() as u32).to_be_bytes());
+    res.extend_from_slice(&p.data);
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::{CustomField, PictureKind, TagStore, flac, trap::Skip};
+
+    const AUDIO: &[u8] = b"\xFF\xF8audio frames";
+
+    struct Src(&'static str, Vec<Picture>);
+
+    impl TagSource for Src {
+        fn title(&self) -> Option<&str> {
+            Some(self.0)
+        }
+
+        fn pictures(&self) -> &[Picture] {
+            &self.1
+        }
+    }
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        custom: Vec<String>,
+        pictures: Vec<Vec<u8>>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Title
+                    | DataType::CustomFields
+                    | DataType::Picture(_)
+            )
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push(field.key);
+        }
+
+        fn add_picture(&mut self, picture: Picture) {
+            self.pictures.push(picture.data);
+        }
+    }
+
+    fn block(typ: u8, last: bool, d: &[u8]) -> Vec<u8> {
+        let mut res = MetadataBlockHeader::new(typ, d.len() as u32);
+        res.last = last;
+        let mut res = res.to_bytes().to_vec();
+        res.extend_from_slice(d);
+        res
+    }
+
+    fn picture(data: &[u8]) -> Picture {
+        let mut res =
+            Picture::from_data(data.to_vec(), PictureKind::FRONT_COVER);
+        res.mime = Some("image/png".into());
+        res
+    }
+
+    /// Create flac file with the given comments, pictures and padding.
+    fn file(
+        comments: &[&[u8]],
+        pictures: &[&[u8]],
+        padding: usize,
+    ) -> Vec<u8> {
+        let mut streaminfo = vec![0; 34];
+        // 44100 Hz, 2 channels, 16 bits
+        streaminfo[10..14].copy_from_slice(&[0x0A, 0xC4, 0x42, 0xF0]);
+
+        let mut vorbis = 3u32.to_le_bytes().to_vec();
+        vorbis.extend(b"ven");
+        vorbis.extend((comments.len() as u32).to_le_bytes());
+        for c in comments {
+            vorbis.extend((c.len() as u32).to_le_bytes());
+            vorbis.extend_from_slice(c);
+        }
+
+        let mut res = b"fLaC".to_vec();
+        res.extend(block(MetadataBlockHeader::STREAMINFO, false, &streaminfo));
+        res.extend(block(MetadataBlockHeader::VORBISCOMMENT, false, &vorbis));
+        for p in pictures {
+            res.extend(block(
+                MetadataBlockHeader::PICTURE,
+                false,
+                &picture_block(&picture(p)),
+            ));
+        }
+        res.extend(block(
+            MetadataBlockHeader::PADDING,
+            true,
+            &vec![0; padding],
+        ));
+        res.extend(AUDIO);
+        res
+    }
+
+    /// Write the source to copy of the file and read it back. Returns the
+    /// result of writing, the new file and the read data.
+    fn round_trip(
+        name: &str,
+        data: &[u8],
+        src: &Src,
+    ) -> (Result<()>, Vec<u8>, Store) {
+        let path = env::temp_dir()
+            .join(format!("ratag-flac-{}-{name}.flac", process::id()));
+        fs::write(&path, data).unwrap();
+        let res = write_file(&path, src, &WriteOptions::default());
+        let mut store = Store::default();
+        let read = flac::from_file(&path, &mut store, &Skip);
+        let out = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        read.unwrap();
+        (res, out.unwrap(), store)
+    }
+
+    const LONG: &str = "Title that is much longer than the original title";
+
+    #[test]
+    fn metadata_fits_into_padding() {
+        let data = file(&[b"TITLE=Old", b"KEEPME=yes"], &[], 100);
+        let (res, out, store) = round_trip("fits", &data, &Src(LONG, vec![]));
+        res.unwrap();
+        assert_eq!(out.len(), data.len());
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.custom, ["KEEPME"]);
+    }
+
+    #[test]
+    fn metadata_grows() {
+        let data = file(&[b"TITLE=Old", b"KEEPME=yes"], &[], 0);
+        let (res, out, store) = round_trip("grows", &data, &Src(LONG, vec![]));
+        res.unwrap();
+        assert!(out.len() > data.len());
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.custom, ["KEEPME"]);
+    }
+
+    #[test]
+    fn invalid_comment_is_not_overwritten() {
+        let data = file(&[b"TITLE=Old", b"NO SEPARATOR"], &[], 100);
+        let (res, out, _) = round_trip("invalid", &data, &Src(LONG, vec![]));
+        assert!(res.is_err());
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn pictures_are_kept() {
+        let data = file(&[b"TITLE=Old"], &[b"old picture"], 100);
+        let (res, out, store) =
+            round_trip("keep-pic", &data, &Src(LONG, vec![]));
+        res.unwrap();
+        let block = picture_block(&picture(b"old picture"));
+        assert!(out.windows(block.len()).any(|a| a == block));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.pictures, [b"old picture"]);
+    }
+
+    #[test]
+    fn pictures_are_replaced() {
+        let data = file(&[b"TITLE=Old"], &[b"old picture"], 100);
+        let src = Src(LONG, vec![picture(b"new picture")]);
+        let (res, _, store) = round_trip("new-pic", &data, &src);
+        res.unwrap();
+        assert_eq!(store.pictures, [b"new picture"]);
+    }
+}
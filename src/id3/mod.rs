@@ -1,4 +1,5 @@
 pub(crate) mod genres;
+mod read_options;
 /// Module for reading ID3v1 tags.
 pub mod v1;
 /// Module for reading ID3v2 tags.
@@ -10,11 +11,17 @@ use std::{
     path::Path,
 };
 
-use crate::{Error, Result, TagRead, TagStore, trap::Trap};
+use crate::{
+    DataType, Error, Result, TagRead, TagSource, TagStore, TagWrite, ape,
+    id3::v2::Id3v2, mpeg, trap::Trap,
+};
 
 use self::genres::*;
 
-/// Read data from both ID3v1 and ID3v2. ID3v2 is prioritized.
+pub use self::read_options::*;
+
+/// Read data from ID3v1, ID3v2 and APE. ID3v2 is prioritized. Length and
+/// audio properties are read from the MPEG audio stream. Writes ID3v2.
 #[derive(Debug)]
 pub struct Id3;
 
@@ -28,21 +35,68 @@ impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Id3 {
     }
 }
 
+impl<S: TagSource> TagWrite<S> for Id3 {
+    fn extensions(&self) -> &[&str] {
+        &["mp3", "mpga", "bit"]
+    }
+
+    fn supports_data(&self, typ: DataType) -> bool {
+        TagWrite::<S>::supports_data(&Id3v2, typ)
+    }
+
+    /// Writes ID3v2 tag, see [`Id3v2`].
+    fn write(&self, path: &Path, src: &S) -> Result<Vec<DataType>> {
+        Id3v2.write(path, src)
+    }
+}
+
 /// Read ID3 tags. This will seek to the proper positions within the file.
-/// Data from ID3v2 is prioritized.
+/// APE tag is also read, because it is often used together with ID3 tags.
+/// Data from ID3v2 is prioritized over APE and data from APE is prioritized
+/// over ID3v1.
+///
+/// Length and audio properties are read from the MPEG audio stream after
+/// the tags, see [`mpeg`].
 pub fn from_seek<R: BufRead + Seek>(
     r: &mut R,
     store: &mut impl TagStore,
     trap: &impl Trap,
 ) -> Result<()> {
-    let v1_res = { v1::from_seek(&mut *r, store, trap) };
-    let v2_res = v2::from_seek(&mut *r, store, trap);
-    match (v1_res, v2_res) {
-        (_, Ok(_)) => Ok(()),
-        (Err(Error::NoTag), e) => e,
-        (e, Err(Error::NoTag)) => e,
-        (_, e) => e,
+    from_seek_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read ID3 tags and the MPEG audio stream with the given options. See
+/// [`from_seek`].
+pub fn from_seek_with<R: BufRead + Seek>(
+    r: &mut R,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    opts: &ReadOptions,
+) -> Result<()> {
+    let v1_res = v1::from_seek(&mut *r, store, trap);
+    let ape_res = ape::from_seek(&mut *r, store, trap);
+    let v2_res = v2::from_seek_with(&mut *r, store, trap, &opts.id3v2);
+    let res = merge_res(merge_res(v1_res, ape_res), v2_res);
+
+    if store.done()
+        || !store.stores_data(DataType::Length)
+            && !store.stores_data(DataType::AudioProperties)
+    {
+        return res;
     }
+
+    // Files without ID3v2 tag at the start must start with MPEG frame, so
+    // that streams of other formats are not mistaken for MPEG audio.
+    let mpeg_res = if !mpeg::stream_start(&mut *r).is_ok_and(|a| a > 0) {
+        let opts = mpeg::ReadOptions {
+            max_sync_search: 0,
+            ..opts.mpeg.clone()
+        };
+        mpeg::from_seek_with(&mut *r, store, trap, &opts)
+    } else {
+        mpeg::from_seek_with(&mut *r, store, trap, &opts.mpeg)
+    };
+    merge_res(mpeg_res, res)
 }
 
 /// Read ID3 tags from file. Data from ID3v2 is prioritized.
@@ -53,3 +107,13 @@ pub fn from_file(
 ) -> Result<()> {
     from_seek(&mut BufReader::new(File::open(path)?), store, trap)
 }
+
+/// Merge results of two tag reads. The second result has higher priority.
+fn merge_res(low: Result<()>, high: Result<()>) -> Result<()> {
+    match (low, high) {
+        (_, Ok(_)) => Ok(()),
+        (Err(Error::NoTag), e) => e,
+        (e, Err(Error::NoTag)) => e,
+        (_, e) => e,
+    }
+}
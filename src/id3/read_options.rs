@@ -0,0 +1,10 @@
+use crate::{id3::v2, mpeg};
+
+/// Options for reading ID3 tags together with the MPEG audio stream.
+#[derive(Debug, Clone, Default)]
+pub struct ReadOptions {
+    /// Options for reading ID3v2 tags.
+    pub id3v2: v2::ReadOptions,
+    /// Options for reading the MPEG audio stream.
+    pub mpeg: mpeg::ReadOptions,
+}
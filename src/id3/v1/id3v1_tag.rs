@@ -117,19 +117,33 @@ impl Id3v1Tag {
         Ok(res)
     }
 
+    /// Get the version of the tag based on the data that is present.
+    pub fn tag_type(&self) -> TagType {
+        if self.sub_genre.is_some() {
+            TagType::Id3v1(2)
+        } else if self.track.is_some() {
+            TagType::Id3v1(1)
+        } else {
+            TagType::Id3v1(0)
+        }
+    }
+
+    /// Size of the tag in the file in bytes.
+    pub fn size(&self) -> u64 {
+        if self.sub_genre.is_some() {
+            Self::LEN2 as u64
+        } else {
+            Self::LEN0 as u64
+        }
+    }
+
     /// Store the ID3v1 data into a tag storage.
     pub fn store(
         self,
         store: &mut impl TagStore,
         trap: &impl Trap,
     ) -> Result<()> {
-        if self.sub_genre.is_some() {
-            store.set_tag_type(TagType::Id3v1(2));
-        } else if self.track.is_some() {
-            store.set_tag_type(TagType::Id3v1(1));
-        } else {
-            store.set_tag_type(TagType::Id3v1(0));
-        }
+        store.set_tag_type(self.tag_type());
 
         if !self.title.is_empty() {
             store.set_title(self.title);
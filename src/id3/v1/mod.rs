@@ -1,9 +1,14 @@
 use std::{
-    io::{Read, Seek},
+    fs::File,
+    io::{ErrorKind, Read, Seek, SeekFrom},
     path::Path,
 };
 
-use crate::{Result, TagStore, trap::Trap};
+use crate::{
+    Error, Result, TagStore, TagType,
+    file_edit::{self, Edit},
+    trap::{Skip, Trap},
+};
 
 mod id3v1;
 mod id3v1_tag;
@@ -45,3 +50,25 @@ pub fn from_file(
     Id3v1Tag::from_file(f, trap)?.store(store, trap)?;
     Ok(())
 }
+
+/// Remove ID3v1 tag (including the ID3v1.2 extension) from the end of the
+/// file. Returns the type of the removed tag or `None` if the file doesn't
+/// have ID3v1 tag.
+pub fn remove_file(f: impl AsRef<Path>) -> Result<Option<TagType>> {
+    let f = f.as_ref();
+    let mut r = File::open(f)?;
+    let len = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(len.saturating_sub(256)))?;
+
+    let tag = match Id3v1Tag::from_read(r, &Skip) {
+        Ok(tag) => tag,
+        Err(Error::NoTag) => return Ok(None),
+        Err(Error::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    file_edit::apply(f, &[Edit::new(len - tag.size()..len, vec![])])?;
+    Ok(Some(tag.tag_type()))
+}
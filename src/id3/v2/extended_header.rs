@@ -0,0 +1,255 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek},
+    path::Path,
+};
+
+use crate::{
+    Error, Result,
+    id3::v2::{header::Header, unsync::Unsync},
+    parsers,
+    trap::{Trap, TrapExt},
+};
+
+/// Extended header of ID3v2.3 or ID3v2.4 tag.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtendedHeader {
+    /// The tag is an update of the previous tag. (ID3v2.4)
+    pub update: bool,
+    /// Size of padding. (ID3v2.3)
+    pub padding_size: Option<u32>,
+    /// CRC-32 of the tag data.
+    pub crc: Option<u32>,
+    /// Restrictions of the tag. (ID3v2.4)
+    pub restrictions: Option<Restrictions>,
+}
+
+/// Restrictions of ID3v2.4 tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Restrictions {
+    /// Maximum number of frames in the tag.
+    pub max_frames: u32,
+    /// Maximum total size of the tag in bytes.
+    pub max_size: u32,
+    /// Text is encoded only with ISO-8859-1 or UTF-8.
+    pub latin1_or_utf8: bool,
+    /// Maximum number of characters in text fields.
+    pub max_text_len: Option<u32>,
+    /// Images are only PNG or JPEG.
+    pub png_or_jpeg: bool,
+    /// Restriction of image size.
+    pub image_size: ImageSizeRestriction,
+}
+
+/// Restriction of image size in ID3v2.4 tag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ImageSizeRestriction {
+    /// Images have no size restriction.
+    #[default]
+    None,
+    /// Images are at most 256x256 pixels.
+    Max256,
+    /// Images are at most 64x64 pixels.
+    Max64,
+    /// Images are exactly 64x64 pixels.
+    Exact64,
+}
+
+impl ExtendedHeader {
+    const CRC23: u16 = 0x8000;
+    const UPDATE24: u8 = 0x40;
+    const CRC24: u8 = 0x20;
+    const RESTRICTIONS24: u8 = 0x10;
+
+    /// Read extended header of the ID3v2 tag at the start of the file.
+    /// Returns [`None`] if the tag doesn't have extended header.
+    pub fn from_file(
+        f: impl AsRef<Path>,
+        trap: &impl Trap,
+    ) -> Result<Option<Self>> {
+        Self::from_seek(BufReader::new(File::open(f)?), trap)
+    }
+
+    /// Read extended header of the ID3v2 tag at the start of the stream.
+    /// Returns [`None`] if the tag doesn't have extended header.
+    ///
+    /// # Errors
+    /// - [`Error::NoTag`] if the stream doesn't start with ID3v2 tag.
+    /// - Reading or parsing the header fails.
+    pub fn from_seek(
+        mut r: impl BufRead + Seek,
+        trap: &impl Trap,
+    ) -> Result<Option<Self>> {
+        r.rewind()?;
+        let mut head = [0; Header::SIZE];
+        r.read_exact(&mut head)?;
+        if &head[..3] != b"ID3" {
+            return Err(Error::NoTag);
+        }
+
+        let header = Header::from_bytes(head[3..].try_into().unwrap());
+        if !header.extended_header34() {
+            return Ok(None);
+        }
+
+        match header.major_version {
+            Header::MAJOR_VERSION3 if header.unsynchronization() => {
+                let (mut r, _) = Unsync::new(r, header.size as u64)?;
+                Ok(Self::read(&mut r, header.major_version, trap)?.0)
+            }
+            Header::MAJOR_VERSION3 | Header::MAJOR_VERSION4 => {
+                Ok(Self::read(&mut r, header.major_version, trap)?.0)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Read the extended header of tag with the given major version. Returns
+    /// the header and its total size in the tag. Errors in the content of the
+    /// header are trapped.
+    pub(crate) fn read(
+        r: &mut impl Read,
+        version: u8,
+        trap: &impl Trap,
+    ) -> Result<(Option<Self>, u32)> {
+        let mut size = [0; 4];
+        r.read_exact(&mut size)?;
+
+        let (size, len) = if version == Header::MAJOR_VERSION3 {
+            let size = u32::from_be_bytes(size);
+            (size.saturating_add(4), size)
+        } else {
+            let size = parsers::syncsafe_be_u32(&size);
+            (size, size.saturating_sub(4))
+        };
+
+        // Extended header is small, large size means corrupted tag.
+        if len > 0xFF {
+            return Err(Error::InvalidLength);
+        }
+
+        let mut data = vec![0; len as usize];
+        r.read_exact(&mut data)?;
+
+        let res = if version == Header::MAJOR_VERSION3 {
+            Self::from_bytes23(&data)
+        } else {
+            Self::from_bytes24(&data)
+        };
+
+        Ok((trap.res(res)?, size))
+    }
+
+    /// Parse ID3v2.3 extended header without the size.
+    fn from_bytes23(d: &[u8]) -> Result<Self> {
+        // 0..2: flags
+        // 2..6: padding size
+        // 6..10: CRC (optional)
+        if d.len() < 6 {
+            return Err(Error::InvalidLength);
+        }
+
+        let flags = u16::from_be_bytes(d[..2].try_into().unwrap());
+        let padding_size = u32::from_be_bytes(d[2..6].try_into().unwrap());
+
+        let crc = if flags & Self::CRC23 != 0 {
+            if d.len() < 10 {
+                return Err(Error::InvalidLength);
+            }
+            Some(u32::from_be_bytes(d[6..10].try_into().unwrap()))
+        } else {
+            None
+        };
+
+        Ok(Self {
+            padding_size: Some(padding_size),
+            crc,
+            ..Default::default()
+        })
+    }
+
+    /// Parse ID3v2.4 extended header without the size.
+    fn from_bytes24(d: &[u8]) -> Result<Self> {
+        // 0: number of flag bytes
+        // 1..: flags
+        // then data of the set flags, each prefixed with its length
+        if d.len() < 2 || d.len() < 1 + d[0] as usize {
+            return Err(Error::InvalidLength);
+        }
+
+        let flags = d[1];
+        let mut d = &d[1 + d[0] as usize..];
+        let mut res = Self::default();
+
+        for flag in [Self::UPDATE24, Self::CRC24, Self::RESTRICTIONS24] {
+            if flags & flag == 0 {
+                continue;
+            }
+
+            let Some((&len, rest)) = d.split_first() else {
+                return Err(Error::InvalidLength);
+            };
+            if rest.len() < len as usize {
+                return Err(Error::InvalidLength);
+            }
+            let (data, rest) = rest.split_at(len as usize);
+            d = rest;
+
+            match flag {
+                Self::UPDATE24 => res.update = true,
+                Self::CRC24 => {
+                    if data.len() != 5 {
+                        return Err(Error::InvalidLength);
+                    }
+                    let crc = data
+                        .iter()
+                        .fold(0_u64, |acc, b| acc << 7 | (*b & 0x7f) as u64);
+                    res.crc = Some(crc as u32);
+                }
+                _ => {
+                    let Some(&r) = data.first() else {
+                        return Err(Error::InvalidLength);
+                    };
+                    res.restrictions = Some(Restrictions::from_byte(r));
+                }
+            }
+        }
+
+        Ok(res)
+    }
+}
+
+impl Restrictions {
+    /// Parse the restrictions from the flags byte `%ppqrrstt`.
+    pub fn from_byte(b: u8) -> Self {
+        let (max_frames, max_size) = match b >> 6 {
+            0 => (128, 1024 * 1024),
+            1 => (64, 128 * 1024),
+            2 => (32, 40 * 1024),
+            _ => (32, 4 * 1024),
+        };
+
+        let max_text_len = match (b >> 3) & 0x3 {
+            0 => None,
+            1 => Some(1024),
+            2 => Some(128),
+            _ => Some(30),
+        };
+
+        let image_size = match b & 0x3 {
+            0 => ImageSizeRestriction::None,
+            1 => ImageSizeRestriction::Max256,
+            2 => ImageSizeRestriction::Max64,
+            _ => ImageSizeRestriction::Exact64,
+        };
+
+        Self {
+            max_frames,
+            max_size,
+            latin1_or_utf8: b & 0x20 != 0,
+            max_text_len,
+            png_or_jpeg: b & 0x04 != 0,
+            image_size,
+        }
+    }
+}
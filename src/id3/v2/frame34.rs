@@ -6,6 +6,8 @@ pub const TALB: u32 = u32::from_be_bytes(*b"TALB");
 pub const TCON: u32 = u32::from_be_bytes(*b"TCON");
 /// Date
 pub const TDAT: u32 = u32::from_be_bytes(*b"TDAT");
+/// Recording time.
+pub const TDRC: u32 = u32::from_be_bytes(*b"TDRC");
 /// Release time.
 pub const TDRL: u32 = u32::from_be_bytes(*b"TDRL");
 /// Title
@@ -30,3 +32,11 @@ pub const COMM: u32 = u32::from_be_bytes(*b"COMM");
 pub const TCOP: u32 = u32::from_be_bytes(*b"TCOP");
 /// Popularimeter (rating)
 pub const POPM: u32 = u32::from_be_bytes(*b"POPM");
+/// Offset to the next tag (ID3v2.4)
+pub const SEEK: u32 = u32::from_be_bytes(*b"SEEK");
+/// User defined text
+pub const TXXX: u32 = u32::from_be_bytes(*b"TXXX");
+/// Original release year (ID3v2.3)
+pub const TORY: u32 = u32::from_be_bytes(*b"TORY");
+/// Original release time (ID3v2.4)
+pub const TDOR: u32 = u32::from_be_bytes(*b"TDOR");
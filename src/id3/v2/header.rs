@@ -8,8 +8,8 @@ use crate::{
 
 pub struct Header {
     pub major_version: u8,
-    pub _minor_version: u8,
-    flags: u8,
+    pub minor_version: u8,
+    pub flags: u8,
     pub size: u32,
 }
 
@@ -17,6 +17,10 @@ impl Header {
     const UNSYNCHRONIZATION: u8 = 0x80;
     const COMPRESSION2: u8 = 0x40;
     const EXTENDED_HEADER34: u8 = 0x40;
+    const FOOTER4: u8 = 0x10;
+
+    /// Size of the header including the `ID3` identifier.
+    pub const SIZE: usize = 10;
 
     pub const MAJOR_VERSION2: u8 = 0x02;
     pub const MAJOR_VERSION3: u8 = 0x03;
@@ -25,8 +29,8 @@ impl Header {
     pub fn from_bytes(d: &[u8; 7]) -> Self {
         Self {
             major_version: d[0],
-            _minor_version: d[1],
-            flags: d[3],
+            minor_version: d[1],
+            flags: d[2],
             size: parsers::syncsafe_be_u32(d[3..].try_into().unwrap()),
         }
     }
@@ -39,6 +43,17 @@ impl Header {
         self.get_flag(Self::EXTENDED_HEADER34)
     }
 
+    pub fn footer4(&self) -> bool {
+        self.major_version == Self::MAJOR_VERSION4
+            && self.get_flag(Self::FOOTER4)
+    }
+
+    /// Total size of the tag including header and footer.
+    pub fn tag_size(&self) -> u64 {
+        let footer = if self.footer4() { Self::SIZE } else { 0 };
+        (Self::SIZE + footer) as u64 + self.size as u64
+    }
+
     pub fn compression2(&self) -> bool {
         self.get_flag(Self::COMPRESSION2)
     }
@@ -1,8 +1,17 @@
-use std::io::{BufRead, Seek};
+use std::{
+    fs::File,
+    io::{BufRead, Read, Seek},
+    path::Path,
+};
 
-use crate::{TagRead, TagStore, id3::v2::from_seek, trap::Trap};
+use crate::{
+    DataType, Error, Rating, TagRead, TagSource, TagSourceExt, TagStore,
+    TagWrite,
+    id3::v2::{WriteOptions, from_seek, write_file},
+    trap::Trap,
+};
 
-/// Tag reader for ID3v2.
+/// Tag reader and writer for ID3v2.
 #[derive(Debug)]
 pub struct Id3v2;
 
@@ -15,3 +24,35 @@ impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Id3v2 {
         from_seek(r, store, trap)
     }
 }
+
+impl<S: TagSource> TagWrite<S> for Id3v2 {
+    fn extensions(&self) -> &[&str] {
+        &["mp3", "mpga"]
+    }
+
+    fn supports_data(&self, typ: DataType) -> bool {
+        typ != DataType::TagType
+    }
+
+    /// Writes ID3v2.4 tag. Only files that start with ID3v2 tag or with mpeg
+    /// frame are supported.
+    fn write(&self, path: &Path, src: &S) -> crate::Result<Vec<DataType>> {
+        let mut head = vec![];
+        File::open(path)?.take(3).read_to_end(&mut head)?;
+        let is_mpeg = head.len() >= 2 && head[0] == 0xFF && head[1] >= 0xE0;
+        if !head.starts_with(b"ID3") && !is_mpeg {
+            return Err(Error::NoTag);
+        }
+
+        write_file(path, src, &WriteOptions::default())?;
+
+        let mut res = src.unsupported_data(|t| {
+            <Self as TagWrite<S>>::supports_data(self, t)
+        });
+        // Only popularimeter can be written.
+        if src.ratings().iter().any(|a| matches!(a, Rating::Text(_))) {
+            res.push(DataType::Ratings);
+        }
+        Ok(res)
+    }
+}
@@ -0,0 +1,344 @@
+use crate::{
+    Comment, Error, Lyrics, Picture, PictureKind, Popularimeter, Result,
+    SyncedLyrics,
+    id3::v2::{
+        header::Header, read_comment, read_lyrics, read_popularimeter,
+        read_string_enc_nt, read_string_list, read_synced_lyrics,
+    },
+    parsers,
+    trap::{Trap, TrapExt},
+};
+
+/// Single frame of ID3v2 tag.
+#[derive(Debug)]
+pub struct Id3v2Frame {
+    /// Identifier of the frame. It has 3 characters in ID3v2.2 and 4
+    /// characters in ID3v2.3 and ID3v2.4.
+    pub id: String,
+    /// Flags from the frame header. ID3v2.2 frames have no flags.
+    pub flags: u16,
+    /// Parsed content of the frame.
+    pub content: FrameContent,
+}
+
+/// Content of ID3v2 frame.
+#[derive(Debug)]
+pub enum FrameContent {
+    /// Text information frame (`T***`). Only ID3v2.4 officially supports
+    /// multiple values.
+    Text(Vec<String>),
+    /// User defined text information (`TXXX`).
+    UserText {
+        description: String,
+        values: Vec<String>,
+    },
+    /// URL link frame (`W***`).
+    Url(String),
+    /// User defined URL link (`WXXX`).
+    UserUrl { description: String, url: String },
+    /// Comment (`COMM`).
+    Comment(Comment),
+    /// Unsynchronized lyrics (`USLT`).
+    Lyrics(Lyrics),
+    /// Synchronized lyrics (`SYLT`).
+    SyncedLyrics(SyncedLyrics),
+    /// Attached picture (`APIC`).
+    Picture(Picture),
+    /// Popularimeter (`POPM`).
+    Popularimeter(Popularimeter),
+    /// Relative volume adjustment (`RVA2`).
+    RelativeVolume {
+        identification: String,
+        channels: Vec<ChannelVolume>,
+    },
+    /// Unique file identifier (`UFID`).
+    UniqueFileId { owner: String, id: Vec<u8> },
+    /// Private frame (`PRIV`).
+    Private { owner: String, data: Vec<u8> },
+    /// General encapsulated object (`GEOB`).
+    Object {
+        mime: String,
+        filename: String,
+        description: String,
+        data: Vec<u8>,
+    },
+    /// Frame that is not known, is encrypted or that failed to parse. The
+    /// data are after reversing unsynchronization and decompression.
+    Unknown(Vec<u8>),
+}
+
+/// Volume adjustment of single channel in `RVA2` frame.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelVolume {
+    /// Type of the channel. `1` is the master volume.
+    pub channel: u8,
+    /// Volume adjustment in dB.
+    pub adjustment: f32,
+    /// Peak volume. It is linear where `1.0` is full scale.
+    pub peak: Option<f32>,
+}
+
+impl Id3v2Frame {
+    /// Parse frame from its data. The data must be already resynchronized
+    /// and decompressed. If the content fails to parse, the error is trapped
+    /// and the frame is kept as [`FrameContent::Unknown`].
+    pub(crate) fn parse(
+        id: &[u8],
+        flags: u16,
+        data: Vec<u8>,
+        version: u8,
+        trap: &impl Trap,
+    ) -> Result<Self> {
+        let id = String::from_utf8_lossy(id).into_owned();
+        let content = trap
+            .res(FrameContent::parse(&id, version, &data, trap))?
+            .flatten()
+            .unwrap_or(FrameContent::Unknown(data));
+        Ok(Self { id, flags, content })
+    }
+
+    /// Create frame that will not be parsed.
+    pub(crate) fn unknown(id: &[u8], flags: u16, data: Vec<u8>) -> Self {
+        Self {
+            id: String::from_utf8_lossy(id).into_owned(),
+            flags,
+            content: FrameContent::Unknown(data),
+        }
+    }
+}
+
+impl FrameContent {
+    /// Parse the content of frame with the given id. Returns [`None`] if the
+    /// frame type is not known.
+    fn parse(
+        id: &str,
+        version: u8,
+        d: &[u8],
+        trap: &impl Trap,
+    ) -> Result<Option<Self>> {
+        let res = match id {
+            "TXXX" | "TXX" => {
+                let (enc, d) = split_encoding(d)?;
+                let (l, description) = read_string_enc_nt(enc, d, trap)?;
+                Self::UserText {
+                    description,
+                    values: read_string_list(enc, &d[l..], trap)?,
+                }
+            }
+            "WXXX" | "WXX" => {
+                let (enc, d) = split_encoding(d)?;
+                let (l, description) = read_string_enc_nt(enc, d, trap)?;
+                let (_, url) = parsers::iso_8859_1_mnt(&d[l..], trap)?;
+                Self::UserUrl { description, url }
+            }
+            "COMM" | "COM" => Self::Comment(read_comment(d, trap)?),
+            "USLT" | "ULT" => Self::Lyrics(read_lyrics(d, trap)?),
+            "SYLT" | "SLT" => Self::SyncedLyrics(read_synced_lyrics(d, trap)?),
+            "APIC" | "PIC" => Self::Picture(read_picture(d, version, trap)?),
+            "POPM" | "POP" => {
+                Self::Popularimeter(read_popularimeter(d, trap)?)
+            }
+            "RVA2" => read_relative_volume(d, trap)?,
+            "UFID" | "UFI" => {
+                let (l, owner) = parsers::iso_8859_1_nt(d, trap)?;
+                Self::UniqueFileId {
+                    owner,
+                    id: d[l..].to_vec(),
+                }
+            }
+            "PRIV" => {
+                let (l, owner) = parsers::iso_8859_1_nt(d, trap)?;
+                Self::Private {
+                    owner,
+                    data: d[l..].to_vec(),
+                }
+            }
+            "GEOB" | "GEO" => {
+                let (enc, mut d) = split_encoding(d)?;
+                let (l, mime) = parsers::iso_8859_1_nt(d, trap)?;
+                d = &d[l..];
+                let (l, filename) = read_string_enc_nt(enc, d, trap)?;
+                d = &d[l..];
+                let (l, description) = read_string_enc_nt(enc, d, trap)?;
+                Self::Object {
+                    mime,
+                    filename,
+                    description,
+                    data: d[l..].to_vec(),
+                }
+            }
+            _ if id.starts_with('T') => {
+                let (enc, d) = split_encoding(d)?;
+                Self::Text(read_string_list(enc, d, trap)?)
+            }
+            _ if id.starts_with('W') => {
+                Self::Url(parsers::iso_8859_1_mnt(d, trap)?.1)
+            }
+            _ => return Ok(None),
+        };
+
+        Ok(Some(res))
+    }
+}
+
+/// Split the encoding byte from the frame data.
+fn split_encoding(d: &[u8]) -> Result<(u8, &[u8])> {
+    d.split_first()
+        .map(|(e, d)| (*e, d))
+        .ok_or(Error::InvalidLength)
+}
+
+fn read_picture(d: &[u8], version: u8, trap: &impl Trap) -> Result<Picture> {
+    let (enc, mut d) = split_encoding(d)?;
+
+    // ID3v2.2 has 3 character image format instead of mime type.
+    let mime = if version == Header::MAJOR_VERSION2 {
+        if d.len() < 3 {
+            return Err(Error::InvalidLength);
+        }
+        let res = parsers::iso_8859_1(&d[..3], trap)?;
+        d = &d[3..];
+        res
+    } else {
+        let (l, res) = parsers::iso_8859_1_nt(d, trap)?;
+        d = &d[l..];
+        res
+    };
+    let is_uri = mime == "-->";
+
+    let Some((&kind, d)) = d.split_first() else {
+        return Err(Error::InvalidLength);
+    };
+    let kind = if let Some(k) = PictureKind::from_id3(kind) {
+        k
+    } else {
+        trap.error(Error::InvalidPictureKind)?;
+        PictureKind::default()
+    };
+
+    let (l, description) = read_string_enc_nt(enc, d, trap)?;
+
+    Ok(Picture::from_id3(
+        Some(mime),
+        Some(description),
+        kind,
+        d[l..].to_vec(),
+        is_uri,
+    ))
+}
+
+fn read_relative_volume(d: &[u8], trap: &impl Trap) -> Result<FrameContent> {
+    let (l, identification) = parsers::iso_8859_1_nt(d, trap)?;
+    let mut d = &d[l..];
+
+    let mut channels = vec![];
+    while !d.is_empty() {
+        // 0: channel type
+        // 1..3: volume adjustment
+        // 3: bits representing peak
+        // 4..: peak volume
+        if d.len() < 4 {
+            return Err(Error::InvalidLength);
+        }
+        let adjustment = i16::from_be_bytes([d[1], d[2]]) as f32 / 512.;
+        let bits = d[3] as u32;
+        let len = bits.div_ceil(8) as usize;
+        if d.len() < len + 4 {
+            return Err(Error::InvalidLength);
+        }
+        let peak = (bits != 0).then(|| {
+            let v = d[4..len + 4].iter().fold(0., |v, b| v * 256. + *b as f64);
+            (v / 2f64.powi(bits as i32 - 1)) as f32
+        });
+        channels.push(ChannelVolume {
+            channel: d[0],
+            adjustment,
+            peak,
+        });
+        d = &d[len + 4..];
+    }
+
+    Ok(FrameContent::RelativeVolume {
+        identification,
+        channels,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    fn parse(id: &str, d: &[u8], trap: &impl Trap) -> Result<Id3v2Frame> {
+        Id3v2Frame::parse(
+            id.as_bytes(),
+            0,
+            d.to_vec(),
+            Header::MAJOR_VERSION4,
+            trap,
+        )
+    }
+
+    #[test]
+    fn relative_volume() {
+        // Master volume -6.5 dB with 16 bit peak 0.5, front right +0.25 dB
+        // with 12 bit peak 1.0 and bass without peak.
+        let d = b"track\0\x01\xF3\0\x10\x40\0\x03\0\x80\x0C\x08\0\x07\0\0\0";
+        let frame = parse("RVA2", d, &Strict).unwrap();
+        let FrameContent::RelativeVolume {
+            identification,
+            channels,
+        } = frame.content
+        else {
+            panic!("Expected RVA2, got {:?}", frame.content);
+        };
+        assert_eq!(identification, "track");
+        assert_eq!(
+            channels,
+            [
+                ChannelVolume {
+                    channel: 1,
+                    adjustment: -6.5,
+                    peak: Some(0.5),
+                },
+                ChannelVolume {
+                    channel: 3,
+                    adjustment: 0.25,
+                    peak: Some(1.),
+                },
+                ChannelVolume {
+                    channel: 7,
+                    adjustment: 0.,
+                    peak: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn user_text() {
+        let d = b"\x03desc\0a\0b";
+        let frame = parse("TXXX", d, &Strict).unwrap();
+        let FrameContent::UserText {
+            description,
+            values,
+        } = frame.content
+        else {
+            panic!("Expected TXXX, got {:?}", frame.content);
+        };
+        assert_eq!(description, "desc");
+        assert_eq!(values, ["a", "b"]);
+    }
+
+    #[test]
+    fn invalid_frame_is_unknown() {
+        // Truncated peak.
+        let d = b"\0\x01\0\0\x10\x40";
+        assert!(parse("RVA2", d, &Strict).is_err());
+        let frame = parse("RVA2", d, &Skip).unwrap();
+        assert!(matches!(frame.content, FrameContent::Unknown(v) if v == d));
+
+        let frame = parse("XYZW", b"data", &Strict).unwrap();
+        assert!(matches!(frame.content, FrameContent::Unknown(_)));
+    }
+}
@@ -0,0 +1,743 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Seek},
+    path::Path,
+};
+
+use crate::{
+    CustomField, CustomValue, DataType, DateTime, Error, PictureKind, Rating,
+    Result, TagStore, TagStoreExt, TagType,
+    bread::Bread,
+    id3::v2::{
+        ExtendedHeader, FrameContent, Id3v2Frame, ReadOptions, genres24,
+        header::Header, parse_date23, parse_genres23, parse_length, v2_2,
+        v2_3, v2_4,
+    },
+    musicbrainz::MusicBrainzId,
+    parsers,
+    replay_gain::{ReplayGain, ReplayGainField},
+    trap::{Trap, TrapExt},
+};
+
+/// ID3v2 tag with all of its frames.
+#[derive(Debug)]
+pub struct Id3v2Tag {
+    /// Major version of the tag (2, 3 or 4).
+    pub version: u8,
+    /// Revision of the tag.
+    pub revision: u8,
+    /// Flags from the tag header.
+    pub flags: u8,
+    /// Extended header. (ID3v2.3 and ID3v2.4)
+    pub extended_header: Option<ExtendedHeader>,
+    /// Frames in the order in which they are in the tag.
+    pub frames: Vec<Id3v2Frame>,
+}
+
+impl Id3v2Tag {
+    /// Read ID3v2 tag at the start of the file.
+    pub fn from_file(f: impl AsRef<Path>, trap: &impl Trap) -> Result<Self> {
+        Self::from_file_with(f, trap, &ReadOptions::default())
+    }
+
+    /// Read ID3v2 tag at the start of the file with the given options.
+    pub fn from_file_with(
+        f: impl AsRef<Path>,
+        trap: &impl Trap,
+        opts: &ReadOptions,
+    ) -> Result<Self> {
+        Self::from_read_with(BufReader::new(File::open(f)?), trap, opts)
+    }
+
+    /// Read ID3v2 tag, assuming that the reader is already at the correct
+    /// position.
+    pub fn from_read(
+        r: impl BufRead + Seek,
+        trap: &impl Trap,
+    ) -> Result<Self> {
+        Self::from_read_with(r, trap, &ReadOptions::default())
+    }
+
+    /// Read ID3v2 tag with the given options, assuming that the reader is
+    /// already at the correct position.
+    pub fn from_read_with(
+        r: impl BufRead + Seek,
+        trap: &impl Trap,
+        opts: &ReadOptions,
+    ) -> Result<Self> {
+        Self::read(r, trap, opts, &AllFrames)
+    }
+
+    /// Read the tag. Only frames kept by the filter are read, the others are
+    /// skipped. Reading stops when the filter is done.
+    pub(crate) fn read(
+        r: impl BufRead + Seek,
+        trap: &impl Trap,
+        opts: &ReadOptions,
+        filter: &impl FrameFilter,
+    ) -> Result<Self> {
+        let mut r = Bread::new(r);
+        if !r.expect(b"ID3")? {
+            return Err(Error::NoTag);
+        }
+
+        let header: Header = r.get()?;
+        let mut tag = Self {
+            version: header.major_version,
+            revision: header.minor_version,
+            flags: header.flags,
+            extended_header: None,
+            frames: vec![],
+        };
+
+        match header.major_version {
+            Header::MAJOR_VERSION2 => {
+                v2_2::from_bread(r, trap, &header, &mut tag, filter)?
+            }
+            Header::MAJOR_VERSION3 => {
+                v2_3::from_bread(r, trap, &header, opts, &mut tag, filter)?
+            }
+            Header::MAJOR_VERSION4 => {
+                v2_4::from_bread(r, trap, &header, opts, &mut tag, filter)?
+            }
+            _ => {
+                return Err(Error::Unsupported(
+                    "ID3v2 other version than 2, 3 and 4.",
+                ));
+            }
+        }
+
+        Ok(tag)
+    }
+
+    /// Store the data of the known frames to the tag store.
+    pub fn store(
+        self,
+        store: &mut impl TagStore,
+        trap: &impl Trap,
+    ) -> Result<()> {
+        store.set_tag_type(TagType::Id3v2(self.version));
+
+        let mut comments = vec![];
+        let mut ratings = vec![];
+        let mut lyrics = vec![];
+        let mut synced_lyrics = vec![];
+        let mut dates = Dates::default();
+        let mut replay_gain = ReplayGain::default();
+
+        for frame in self.frames {
+            if store.done() {
+                break;
+            }
+            if !stores_frame(frame.id.as_bytes(), store) {
+                continue;
+            }
+
+            match frame.content {
+                FrameContent::Text(v) => store_text(
+                    self.version,
+                    &frame.id,
+                    v,
+                    store,
+                    &mut dates,
+                    trap,
+                )?,
+                FrameContent::Comment(c) => comments.push(c),
+                FrameContent::Lyrics(l) => lyrics.push(l),
+                FrameContent::SyncedLyrics(l) => synced_lyrics.push(l),
+                FrameContent::Picture(p)
+                    if store.stores_data(DataType::Picture(p.kind)) =>
+                {
+                    store.add_picture(p);
+                }
+                FrameContent::Popularimeter(p) => {
+                    ratings.push(Rating::Popularimeter(p))
+                }
+                FrameContent::UserText {
+                    description,
+                    values,
+                } => {
+                    let mb = description
+                        .strip_prefix("MusicBrainz ")
+                        .and_then(MusicBrainzId::from_name);
+                    if let Some(id) = mb
+                        && store.stores_data(id.data_type())
+                    {
+                        id.store(values.clone(), store, trap)?;
+                    }
+                    if let Some(f) = ReplayGainField::from_key(&description)
+                        && store.stores_data(f.data_type())
+                        && let Some(v) = values.last()
+                    {
+                        replay_gain.set(f, v, trap)?;
+                    }
+                    if !store.stores_data(DataType::CustomFields) {
+                        continue;
+                    }
+                    for v in values {
+                        store.add_custom_field(CustomField {
+                            namespace: Some(frame.id.clone()),
+                            key: description.clone(),
+                            value: CustomValue::Text(v),
+                        });
+                    }
+                }
+                FrameContent::UniqueFileId { owner, id }
+                    if owner == "http://musicbrainz.org" =>
+                {
+                    let id = String::from_utf8_lossy(&id).into_owned();
+                    MusicBrainzId::Recording.store(vec![id], store, trap)?;
+                }
+                FrameContent::RelativeVolume {
+                    identification,
+                    channels,
+                } => {
+                    use ReplayGainField::*;
+                    let (gain, peak) =
+                        match identification.to_ascii_lowercase().as_str() {
+                            "track" => (TrackGain, TrackPeak),
+                            "album" => (AlbumGain, AlbumPeak),
+                            _ => continue,
+                        };
+                    // Only the master volume is used.
+                    if let Some(c) = channels.iter().find(|c| c.channel == 1) {
+                        replay_gain.set_fallback(gain, c.adjustment);
+                        if let Some(p) = c.peak {
+                            replay_gain.set_fallback(peak, p);
+                        }
+                    }
+                }
+                FrameContent::UserUrl { description, url } => {
+                    store.add_custom_field(CustomField {
+                        namespace: Some(frame.id),
+                        key: description,
+                        value: CustomValue::Text(url),
+                    });
+                }
+                _ => {}
+            }
+        }
+
+        if !comments.is_empty() {
+            store.set_comments(comments);
+        }
+
+        if !ratings.is_empty() {
+            store.set_ratings(ratings);
+        }
+
+        if !lyrics.is_empty() {
+            store.set_lyrics(lyrics);
+        }
+
+        if !synced_lyrics.is_empty() {
+            store.set_synced_lyrics(synced_lyrics);
+        }
+
+        dates.store(store);
+        replay_gain.store(store);
+
+        Ok(())
+    }
+}
+
+/// Dates collected from the frames. ID3v2.3 stores the recording time in
+/// `TYER`, `TDAT` and `TIME`.
+#[derive(Default)]
+struct Dates {
+    recording: DateTime,
+    release: DateTime,
+    original: DateTime,
+}
+
+impl Dates {
+    /// Set the dates to the store. Year, date and time are taken from the
+    /// first known of recording time, release time and original release
+    /// time.
+    fn store(self, store: &mut impl TagStore) {
+        let main = [self.recording, self.release, self.original]
+            .into_iter()
+            .find(|a| !a.is_empty());
+        if let Some(dt) = main {
+            store.set_date_time(dt);
+        }
+
+        if !self.recording.is_empty() {
+            store.set_recording_time(self.recording);
+        }
+        if !self.release.is_empty() {
+            store.set_release_time(self.release);
+        }
+        if !self.original.is_empty() {
+            store.set_original_release_time(self.original);
+        }
+    }
+}
+
+/// Set the known parts of `new` to `dt`.
+fn merge(dt: &mut DateTime, new: DateTime) {
+    dt.year = new.year.or(dt.year);
+    dt.date = new.date.or(dt.date);
+    dt.time = new.time.or(dt.time);
+}
+
+/// Check whether data of frame with the given id may be stored.
+/// Decides which frames are read from the tag.
+pub(crate) trait FrameFilter {
+    /// Check whether the frame with the given id should be read.
+    fn keep(&self, id: &[u8]) -> bool;
+
+    /// Check whether no more frames need to be read.
+    fn done(&self) -> bool;
+}
+
+/// Filter that reads all frames.
+struct AllFrames;
+
+impl FrameFilter for AllFrames {
+    fn keep(&self, _id: &[u8]) -> bool {
+        true
+    }
+
+    fn done(&self) -> bool {
+        false
+    }
+}
+
+impl<S: TagStore> FrameFilter for S {
+    fn keep(&self, id: &[u8]) -> bool {
+        stores_frame(id, self)
+    }
+
+    fn done(&self) -> bool {
+        TagStore::done(self)
+    }
+}
+
+pub(crate) fn stores_frame(id: &[u8], store: &impl TagStore) -> bool {
+    let s = |t| store.stores_data(t);
+    let dates = || {
+        s(DataType::Year)
+            || s(DataType::Date)
+            || s(DataType::Time)
+            || s(DataType::RecordingTime)
+            || s(DataType::ReleaseTime)
+            || s(DataType::OriginalReleaseTime)
+    };
+    match id {
+        b"TIT2" | b"TT2" => s(DataType::Title),
+        b"TALB" | b"TAL" => s(DataType::Album),
+        b"TPE1" | b"TP1" => s(DataType::Artists),
+        b"TPE2" | b"TP2" => s(DataType::AlbumArtist),
+        b"TCON" | b"TCO" => s(DataType::Genres),
+        b"TRCK" | b"TRK" => s(DataType::Track) || s(DataType::TrackCount),
+        b"TPOS" | b"TPA" => s(DataType::Disc) || s(DataType::DiscCount),
+        b"TYER" | b"TYE" | b"TDAT" | b"TDA" | b"TIME" | b"TIM" => dates(),
+        b"TDRC" | b"TDRL" | b"TDOR" | b"TORY" | b"TOR" => dates(),
+        b"TLEN" | b"TLE" => s(DataType::Length),
+        b"TCOP" | b"TCR" => s(DataType::Copyright),
+        b"COMM" | b"COM" => s(DataType::Comments),
+        b"USLT" | b"ULT" => s(DataType::Lyrics),
+        b"SYLT" | b"SLT" => s(DataType::SyncedLyrics),
+        b"POPM" | b"POP" => s(DataType::Ratings),
+        b"TXXX" | b"TXX" => {
+            s(DataType::CustomFields)
+                || MusicBrainzId::stores_any(store)
+                || ReplayGain::stores_any(store)
+        }
+        b"RVA2" => ReplayGain::stores_any(store),
+        b"WXXX" | b"WXX" => s(DataType::CustomFields),
+        b"UFID" | b"UFI" => s(DataType::MusicBrainzRecordingId),
+        b"APIC" | b"PIC" => s(DataType::Picture(PictureKind::all_id3())),
+        b"TCOM" | b"TCM" => s(DataType::Composers),
+        b"TPE3" | b"TP3" => s(DataType::Conductor),
+        b"TPE4" | b"TP4" => s(DataType::Remixer),
+        b"TEXT" | b"TXT" => s(DataType::Lyricists),
+        b"TBPM" | b"TBP" => s(DataType::Bpm),
+        b"TKEY" | b"TKE" => s(DataType::InitialKey),
+        b"TSRC" | b"TRC" => s(DataType::Isrc),
+        b"TENC" | b"TEN" => s(DataType::EncodedBy),
+        b"TSSE" | b"TSS" => s(DataType::EncoderSettings),
+        b"TPUB" | b"TPB" => s(DataType::Publisher),
+        b"TIT1" | b"TT1" => s(DataType::Grouping),
+        b"TIT3" | b"TT3" => s(DataType::Subtitle),
+        b"TMOO" => s(DataType::Mood),
+        b"TLAN" | b"TLA" => s(DataType::Languages),
+        b"TOAL" | b"TOT" => s(DataType::OriginalAlbum),
+        b"TOPE" | b"TOA" => s(DataType::OriginalArtists),
+        b"TOFN" | b"TOF" => s(DataType::OriginalFilename),
+        b"TSOT" | b"TST" => s(DataType::TitleSort),
+        b"TSOA" | b"TSA" => s(DataType::AlbumSort),
+        b"TSOP" | b"TSP" => s(DataType::ArtistSort),
+        b"TSO2" | b"TS2" => s(DataType::AlbumArtistSort),
+        b"TSOC" | b"TSC" => s(DataType::ComposerSort),
+        b"TCMP" | b"TCP" => s(DataType::Compilation),
+        b"TDEN" => s(DataType::EncodingTime),
+        _ => false,
+    }
+}
+
+fn store_text(
+    version: u8,
+    id: &str,
+    mut v: Vec<String>,
+    store: &mut impl TagStore,
+    dates: &mut Dates,
+    trap: &impl Trap,
+) -> Result<()> {
+    if v.is_empty() {
+        return Ok(());
+    }
+
+    // Multiple values are supported only in ID3v2.4. Older versions use
+    // custom separators.
+    let v24 = version == Header::MAJOR_VERSION4;
+    let list = |v: Vec<String>| {
+        if v24 {
+            v
+        } else {
+            v.iter()
+                .flat_map(|a| a.split('/'))
+                .map(|a| a.to_string())
+                .collect()
+        }
+    };
+
+    match id {
+        "TIT2" | "TT2" => store.set_title(v.swap_remove(0)),
+        "TALB" | "TAL" => store.set_album(v.swap_remove(0)),
+        "TPE2" | "TP2" => store.set_album_artist(v.swap_remove(0)),
+        "TCOP" | "TCR" => store.set_copyright(v.swap_remove(0)),
+        "TPE1" | "TP1" => store.set_artists(list(v)),
+        "TCON" if v24 => store.set_genres(genres24(v)),
+        "TCON" | "TCO" => {
+            if let Some(g) = trap.res(parse_genres23(&v[0], trap))? {
+                store.set_genres(g);
+            }
+        }
+        "TRCK" | "TRK" => {
+            if let Some((t, c)) = trap.res(parsers::num_of(&v[0], trap))? {
+                store.set_track(t);
+                if let Some(c) = c {
+                    store.set_track_count(c);
+                }
+            }
+        }
+        "TPOS" | "TPA" => {
+            if let Some((d, c)) = trap.res(parsers::num_of(&v[0], trap))? {
+                store.set_disc(d);
+                if let Some(c) = c {
+                    store.set_disc_count(c);
+                }
+            }
+        }
+        "TYER" | "TYE" | "TDRC" => {
+            if let Some(d) = trap.res(parsers::year(&v[0], trap))? {
+                merge(&mut dates.recording, d);
+            }
+        }
+        "TDAT" | "TDA" => {
+            if let Some(d) = trap.res(parse_date23(&v[0], trap))? {
+                merge(&mut dates.recording, d);
+            }
+        }
+        "TIME" | "TIM" => {
+            if let Some(t) = trap.res(parsers::time_only(&v[0]))? {
+                dates.recording.time = Some(t);
+            }
+        }
+        "TDRL" => {
+            if let Some(d) = trap.res(parsers::year(&v[0], trap))? {
+                merge(&mut dates.release, d);
+            }
+        }
+        "TDOR" | "TORY" | "TOR" => {
+            if let Some(d) = trap.res(parsers::year(&v[0], trap))? {
+                merge(&mut dates.original, d);
+            }
+        }
+        "TLEN" | "TLE" => {
+            if let Some(l) = trap.res(parse_length(&v[0]))? {
+                store.set_length(l);
+            }
+        }
+        "TCOM" | "TCM" => store.set_composers(list(v)),
+        "TPE3" | "TP3" => store.set_conductor(v.swap_remove(0)),
+        "TPE4" | "TP4" => store.set_remixer(v.swap_remove(0)),
+        "TEXT" | "TXT" => store.set_lyricists(list(v)),
+        "TBPM" | "TBP" => {
+            if let Some(b) = trap.res(parsers::num(v[0].trim()))? {
+                store.set_bpm(b);
+            }
+        }
+        "TKEY" | "TKE" => store.set_initial_key(v.swap_remove(0)),
+        "TSRC" | "TRC" => store.set_isrc(v.swap_remove(0)),
+        "TENC" | "TEN" => store.set_encoded_by(v.swap_remove(0)),
+        "TSSE" | "TSS" => store.set_encoder_settings(v.swap_remove(0)),
+        "TPUB" | "TPB" => store.set_publisher(v.swap_remove(0)),
+        "TIT1" | "TT1" => store.set_grouping(v.swap_remove(0)),
+        "TIT3" | "TT3" => store.set_subtitle(v.swap_remove(0)),
+        "TMOO" => store.set_mood(v.swap_remove(0)),
+        "TLAN" | "TLA" => store.set_languages(v),
+        "TOAL" | "TOT" => store.set_original_album(v.swap_remove(0)),
+        "TOPE" | "TOA" => store.set_original_artists(list(v)),
+        "TOFN" | "TOF" => store.set_original_filename(v.swap_remove(0)),
+        "TSOT" | "TST" => store.set_title_sort(v.swap_remove(0)),
+        "TSOA" | "TSA" => store.set_album_sort(v.swap_remove(0)),
+        "TSOP" | "TSP" => store.set_artist_sort(v.swap_remove(0)),
+        "TSO2" | "TS2" => store.set_album_artist_sort(v.swap_remove(0)),
+        "TSOC" | "TSC" => store.set_composer_sort(v.swap_remove(0)),
+        "TCMP" | "TCP" => {
+            if let Some(c) = trap.res(parsers::num::<u32>(v[0].trim()))? {
+                store.set_compilation(c != 0);
+            }
+        }
+        "TDEN" => {
+            if let Some(d) = trap.res(parsers::year(&v[0], trap))? {
+                store.set_encoding_time(d);
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::Strict;
+
+    #[derive(Default)]
+    struct Store {
+        composers: Vec<String>,
+        conductor: Option<String>,
+        bpm: Option<u32>,
+        isrc: Option<String>,
+        artist_sort: Option<String>,
+        compilation: Option<bool>,
+        year: Option<i32>,
+        date: Option<(u8, u8)>,
+        recording: Option<DateTime>,
+        release: Option<DateTime>,
+        original: Option<DateTime>,
+        custom: Vec<CustomField>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            use DataType::*;
+            matches!(
+                typ,
+                Composers
+                    | Conductor
+                    | Bpm
+                    | Isrc
+                    | ArtistSort
+                    | Compilation
+                    | Year
+                    | Date
+                    | RecordingTime
+                    | ReleaseTime
+                    | OriginalReleaseTime
+                    | CustomFields
+            )
+        }
+
+        fn set_composers(&mut self, composers: Vec<String>) {
+            self.composers = composers;
+        }
+
+        fn set_conductor(&mut self, conductor: String) {
+            self.conductor = Some(conductor);
+        }
+
+        fn set_bpm(&mut self, bpm: u32) {
+            self.bpm = Some(bpm);
+        }
+
+        fn set_isrc(&mut self, isrc: String) {
+            self.isrc = Some(isrc);
+        }
+
+        fn set_artist_sort(&mut self, artist: String) {
+            self.artist_sort = Some(artist);
+        }
+
+        fn set_compilation(&mut self, compilation: bool) {
+            self.compilation = Some(compilation);
+        }
+
+        fn set_year(&mut self, year: i32) {
+            self.year = Some(year);
+        }
+
+        fn set_date(&mut self, month: u8, day: u8) {
+            self.date = Some((month, day));
+        }
+
+        fn set_recording_time(&mut self, time: DateTime) {
+            self.recording = Some(time);
+        }
+
+        fn set_release_time(&mut self, time: DateTime) {
+            self.release = Some(time);
+        }
+
+        fn set_original_release_time(&mut self, time: DateTime) {
+            self.original = Some(time);
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push(field);
+        }
+    }
+
+    fn tag(version: u8, frames: &[(&str, &[&str])]) -> Id3v2Tag {
+        let frames = frames
+            .iter()
+            .map(|(id, v)| Id3v2Frame {
+                id: id.to_string(),
+                flags: 0,
+                content: FrameContent::Text(
+                    v.iter().map(|a| a.to_string()).collect(),
+                ),
+            })
+            .collect();
+        Id3v2Tag {
+            version,
+            revision: 0,
+            flags: 0,
+            extended_header: None,
+            frames,
+        }
+    }
+
+    fn read(tag: Id3v2Tag) -> Store {
+        let mut store = Store::default();
+        tag.store(&mut store, &Strict).unwrap();
+        store
+    }
+
+    #[test]
+    fn text_frames() {
+        let store = read(tag(
+            3,
+            &[
+                ("TCOM", &["A/B"]),
+                ("TPE3", &["Conductor"]),
+                ("TBPM", &[" 120 "]),
+                ("TSRC", &["USABC0000001"]),
+                ("TSOP", &["Artist, The"]),
+                ("TCMP", &["1"]),
+            ],
+        ));
+        assert_eq!(store.composers, ["A", "B"]);
+        assert_eq!(store.conductor.as_deref(), Some("Conductor"));
+        assert_eq!(store.bpm, Some(120));
+        assert_eq!(store.isrc.as_deref(), Some("USABC0000001"));
+        assert_eq!(store.artist_sort.as_deref(), Some("Artist, The"));
+        assert_eq!(store.compilation, Some(true));
+    }
+
+    #[test]
+    fn text_frames_v2_2() {
+        let store = read(tag(
+            2,
+            &[("TCM", &["A/B"]), ("TBP", &["90"]), ("TCP", &["0"])],
+        ));
+        assert_eq!(store.composers, ["A", "B"]);
+        assert_eq!(store.bpm, Some(90));
+        assert_eq!(store.compilation, Some(false));
+    }
+
+    #[test]
+    fn text_frames_v2_4() {
+        // ID3v2.4 has separate values instead of `/`.
+        let store = read(tag(4, &[("TCOM", &["A/B", "C"])]));
+        assert_eq!(store.composers, ["A/B", "C"]);
+    }
+
+    fn date(year: i32, date: Option<(u8, u8)>) -> DateTime {
+        DateTime {
+            year: Some(year),
+            date,
+            time: None,
+        }
+    }
+
+    #[test]
+    fn recording_time_has_precedence() {
+        let store = read(tag(
+            4,
+            &[
+                ("TDRL", &["2001-02-03"]),
+                ("TDOR", &["1999"]),
+                ("TDRC", &["2000-05-06"]),
+            ],
+        ));
+        assert_eq!(store.year, Some(2000));
+        assert_eq!(store.date, Some((5, 6)));
+        assert_eq!(store.recording, Some(date(2000, Some((5, 6)))));
+        assert_eq!(store.release, Some(date(2001, Some((2, 3)))));
+        assert_eq!(store.original, Some(date(1999, None)));
+    }
+
+    #[test]
+    fn release_time_fallback() {
+        let store =
+            read(tag(4, &[("TDOR", &["1999"]), ("TDRL", &["2001-02-03"])]));
+        assert_eq!(store.year, Some(2001));
+        assert_eq!(store.date, Some((2, 3)));
+        assert_eq!(store.recording, None);
+
+        let store = read(tag(3, &[("TORY", &["1999"])]));
+        assert_eq!(store.year, Some(1999));
+        assert_eq!(store.original, Some(date(1999, None)));
+    }
+
+    #[test]
+    fn recording_time_v2_3() {
+        let store = read(tag(3, &[("TDAT", &["0305"]), ("TYER", &["2000"])]));
+        assert_eq!(store.year, Some(2000));
+        assert_eq!(store.date, Some((5, 3)));
+        assert_eq!(store.recording, Some(date(2000, Some((5, 3)))));
+    }
+
+    #[test]
+    fn user_defined_frames() {
+        let mut tag = tag(4, &[]);
+        tag.frames.push(Id3v2Frame {
+            id: "TXXX".into(),
+            flags: 0,
+            content: FrameContent::UserText {
+                description: "CATALOGNUMBER".into(),
+                values: vec!["CAT-1".into(), "CAT-2".into()],
+            },
+        });
+        tag.frames.push(Id3v2Frame {
+            id: "WXXX".into(),
+            flags: 0,
+            content: FrameContent::UserUrl {
+                description: "Label".into(),
+                url: "http://example.com".into(),
+            },
+        });
+
+        let store = read(tag);
+        let res: Vec<_> = store
+            .custom
+            .iter()
+            .map(|a| (a.namespace.as_deref(), a.key.as_str(), &a.value))
+            .collect();
+        let text = |s: &str| CustomValue::Text(s.into());
+        assert_eq!(
+            res,
+            [
+                (Some("TXXX"), "CATALOGNUMBER", &text("CAT-1")),
+                (Some("TXXX"), "CATALOGNUMBER", &text("CAT-2")),
+                (Some("WXXX"), "Label", &text("http://example.com")),
+            ]
+        );
+    }
+}
@@ -0,0 +1,311 @@
+use std::io::{ErrorKind, Read, Seek, SeekFrom};
+
+use crate::{
+    Result,
+    ape::{Footer, has_magic},
+    id3::v2::{ExtendedHeader, frame34, header::Header, v2_4::FrameHeader},
+    parsers,
+    trap::{Skip, Trap, TrapExt},
+};
+
+/// ID3v2 tag found within the stream.
+struct Location {
+    pos: u64,
+    header: Header,
+    update: bool,
+}
+
+/// Find positions of ID3v2 tags that should be read from the stream. The
+/// tags are returned in the order in which they should be read.
+///
+/// Tags are searched for at the start of the stream, at positions given by
+/// the `SEEK` frames and at the end of the stream (using the `3DI` footer).
+/// The tag at the end may be also just before ID3v1 and APE tags.
+///
+/// Tags that are replaced by later tags are not returned. Only tags that are
+/// marked as an update are merged with the previous tags.
+///
+/// Errors from probing the end of the stream and the `SEEK` targets are
+/// given to the trap and the position is treated as if there was no tag.
+pub fn find_tags(
+    r: &mut (impl Read + Seek),
+    trap: &impl Trap,
+) -> Result<Vec<u64>> {
+    let mut tags: Vec<Location> = vec![];
+    let mut pending = vec![0];
+    pending.extend(find_appended(r, trap)?);
+
+    while let Some(pos) = pending.pop() {
+        if tags.iter().any(|a| a.pos == pos) {
+            continue;
+        }
+        let tag = if pos == 0 {
+            read_tag(r, pos)?
+        } else {
+            trap.res(read_tag(r, pos))?.flatten()
+        };
+        let Some((tag, seek)) = tag else {
+            continue;
+        };
+        if let Some(offset) = seek {
+            pending.push(pos + tag.header.tag_size() + offset);
+        }
+        tags.push(tag);
+    }
+
+    tags.sort_by_key(|a| a.pos);
+
+    let mut res = vec![];
+    for t in tags {
+        if !t.update {
+            res.clear();
+        }
+        res.push(t.pos);
+    }
+
+    Ok(res)
+}
+
+/// Read the tag at the given position. Returns the tag and the offset from
+/// its `SEEK` frame.
+fn read_tag(
+    r: &mut (impl Read + Seek),
+    pos: u64,
+) -> Result<Option<(Location, Option<u64>)>> {
+    let Some(header) = read_header(r, pos, b"ID3")? else {
+        return Ok(None);
+    };
+    let seek = seek_offset(r, pos, &header)?;
+    let update = is_update(r, pos, &header)?;
+    Ok(Some((
+        Location {
+            pos,
+            header,
+            update,
+        },
+        seek,
+    )))
+}
+
+/// Find tags appended at the end of the stream, before ID3v1 tag and before
+/// APE tag.
+fn find_appended(
+    r: &mut (impl Read + Seek),
+    trap: &impl Trap,
+) -> Result<Vec<u64>> {
+    let len = r.seek(SeekFrom::End(0))?;
+    let mut ends = vec![len];
+
+    let mut end = len;
+    if trap.res(has_magic(r, len, 128, b"TAG"))? == Some(true) {
+        end -= 128;
+        if trap.res(has_magic(r, len, 256, b"EXT"))? == Some(true) {
+            end -= 128;
+        }
+        ends.push(end);
+    }
+
+    if let Some(Some((pos, footer))) = trap.res(Footer::find(r))?
+        && let Some(start) =
+            (pos + Footer::SIZE as u64).checked_sub(footer.tag_size())
+    {
+        ends.push(start);
+    }
+
+    let mut res = vec![];
+    for end in ends {
+        let Some(pos) = end.checked_sub(Header::SIZE as u64) else {
+            continue;
+        };
+        if let Some(Some(footer)) = trap.res(read_header(r, pos, b"3DI"))?
+            && let Some(start) = end.checked_sub(footer.tag_size())
+        {
+            res.push(start);
+        }
+    }
+
+    Ok(res)
+}
+
+/// Read ID3v2 header (or footer) with the given identifier at the given
+/// position.
+fn read_header(
+    r: &mut (impl Read + Seek),
+    pos: u64,
+    id: &[u8; 3],
+) -> Result<Option<Header>> {
+    r.seek(SeekFrom::Start(pos))?;
+    let mut buf = [0; Header::SIZE];
+    match r.read_exact(&mut buf) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        res => res?,
+    }
+
+    if &buf[..3] != id {
+        return Ok(None);
+    }
+
+    Ok(Some(Header::from_bytes(buf[3..].try_into().unwrap())))
+}
+
+/// Find the `SEEK` frame in ID3v2.4 tag and get its offset.
+fn seek_offset(
+    r: &mut (impl Read + Seek),
+    pos: u64,
+    header: &Header,
+) -> Result<Option<u64>> {
+    if header.major_version != Header::MAJOR_VERSION4 {
+        return Ok(None);
+    }
+
+    let mut frame_pos = Header::SIZE as u64;
+    if header.extended_header34() {
+        r.seek(SeekFrom::Start(pos + frame_pos))?;
+        let mut size = [0; 4];
+        r.read_exact(&mut size)?;
+        frame_pos += parsers::syncsafe_be_u32(&size) as u64;
+    }
+
+    let end = Header::SIZE as u64 + header.size as u64;
+    while frame_pos + 10 <= end {
+        r.seek(SeekFrom::Start(pos + frame_pos))?;
+        let mut buf = [0; 10];
+        r.read_exact(&mut buf)?;
+        let frame = FrameHeader::from_bytes(&buf);
+        match frame.id {
+            0 => break,
+            frame34::SEEK if frame.size >= 4 => {
+                let mut offset = [0; 4];
+                r.read_exact(&mut offset)?;
+                return Ok(Some(u32::from_be_bytes(offset) as u64));
+            }
+            _ => frame_pos += 10 + frame.size as u64,
+        }
+    }
+
+    Ok(None)
+}
+
+/// Check whether the tag has the flag that it is update of the previous tag.
+fn is_update(
+    r: &mut (impl Read + Seek),
+    pos: u64,
+    header: &Header,
+) -> Result<bool> {
+    if header.major_version != Header::MAJOR_VERSION4
+        || !header.extended_header34()
+    {
+        return Ok(false);
+    }
+
+    r.seek(SeekFrom::Start(pos + Header::SIZE as u64))?;
+    let (ext, _) = ExtendedHeader::read(r, Header::MAJOR_VERSION4, &Skip)?;
+    Ok(ext.is_some_and(|a| a.update))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::Strict;
+
+    /// Create ID3v2.4 tag with the given flags and frames.
+    fn tag(flags: u8, frames: &[u8]) -> Vec<u8> {
+        let size = frames.len() as u32;
+        let mut res = b"ID3\x04\x00".to_vec();
+        res.push(flags);
+        res.extend(
+            [size >> 21, size >> 14, size >> 7, size].map(|a| a as u8 & 0x7F),
+        );
+        res.extend_from_slice(frames);
+        res
+    }
+
+    fn seek(offset: u32) -> Vec<u8> {
+        let mut res = b"SEEK\0\0\0\x04\0\0".to_vec();
+        res.extend(offset.to_be_bytes());
+        res
+    }
+
+    #[test]
+    fn failed_seek_probe_is_skipped() {
+        let mut data = tag(0, &seek(0));
+        // The tag has extended header, but the data ends after the header.
+        data.extend(b"ID3\x04\x00\x40\x00\x00\x00\x40");
+
+        let res = find_tags(&mut Cursor::new(&data), &Skip).unwrap();
+        assert_eq!(res, [0]);
+        assert!(find_tags(&mut Cursor::new(&data), &Strict).is_err());
+    }
+
+    /// Create ID3v2.4 tag with footer.
+    fn tag_with_footer(flags: u8, frames: &[u8]) -> Vec<u8> {
+        let mut res = tag(flags | 0x10, frames);
+        let mut footer = res[..Header::SIZE].to_vec();
+        footer[..3].copy_from_slice(b"3DI");
+        res.extend(footer);
+        res
+    }
+
+    /// Extended header with the update flag.
+    const UPDATE: &[u8] = b"\0\0\0\x07\x01\x40\0";
+
+    const TIT2: &[u8] = b"TIT2\0\0\0\x02\0\0\0a";
+
+    #[test]
+    fn appended_tag() {
+        let mut data = b"audio".to_vec();
+        data.extend(tag_with_footer(0, TIT2));
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [5]);
+
+        // ID3v1 tag after the ID3v2 tag.
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, 0);
+        data.extend(&id3v1);
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [5]);
+
+        // APE tag without header between the ID3v2 and ID3v1 tags.
+        let mut ape = b"APETAGEX".to_vec();
+        for n in [2000, 32, 0, 0] {
+            ape.extend(u32::to_le_bytes(n));
+        }
+        ape.resize(32, 0);
+        data.truncate(data.len() - 128);
+        data.extend(ape);
+        data.extend(id3v1);
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [5]);
+    }
+
+    #[test]
+    fn seek_frame() {
+        let mut data = tag(0, &seek(4));
+        data.extend(b"data");
+        let pos = data.len() as u64;
+        data.extend(tag(0, TIT2));
+
+        // The second tag replaces the first one.
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [pos]);
+
+        // The second tag updates the first one.
+        data.truncate(pos as usize);
+        data.extend(tag(0x40, &[UPDATE, TIT2].concat()));
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [0, pos]);
+    }
+
+    #[test]
+    fn update_of_appended_tag() {
+        let mut data = tag(0, TIT2);
+        data.extend(b"audio");
+        let pos = data.len() as u64;
+        data.extend(tag_with_footer(0x40, &[UPDATE, TIT2].concat()));
+
+        let res = find_tags(&mut Cursor::new(&data), &Strict).unwrap();
+        assert_eq!(res, [0, pos]);
+    }
+}
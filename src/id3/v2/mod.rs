@@ -1,37 +1,75 @@
+mod extended_header;
 mod frame34;
-mod header;
+pub(crate) mod header;
 mod id3v2;
+mod id3v2_frame;
+mod id3v2_tag;
+mod locate;
+mod read_options;
+mod unsync;
 mod v2_2;
 mod v2_3;
 mod v2_4;
+mod write_options;
+mod writer;
 
 use std::{
     fs::File,
-    io::{BufRead, BufReader, Seek},
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
     path::Path,
     time::Duration,
 };
 
+use flate2::bufread::ZlibDecoder;
+
 use crate::{
-    Comment, DataType, Error, Picture, PictureKind, Popularimeter, Rating,
-    Result, TagStore, Trap, TrapExt,
-    bread::Bread,
-    id3::get_genre,
-    parsers::{self, DateTime},
+    Comment, DateTime, Error, Lyrics, LyricsContentType, Popularimeter,
+    Result, SyncedLyrics, TagStore, TimestampFormat, Trap, TrapExt,
+    bread::Bread, id3::get_genre, parsers,
 };
 
-use self::header::*;
-pub use self::id3v2::*;
+pub use self::{
+    extended_header::*, id3v2::*, id3v2_frame::*, id3v2_tag::*,
+    read_options::*, write_options::*, writer::*,
+};
 
 /// Read ID3v2 tag without assuming that the reader is already at the correct
 /// position.
 pub fn from_seek(
+    r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_seek_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read ID3v2 tag with the given options without assuming that the reader is
+/// already at the correct position.
+///
+/// Tags are searched for at the start of the stream, at the end of the stream
+/// (also before ID3v1 and APE tags) and at positions given by `SEEK` frames.
+/// Tags are read in the order in which they are in the stream. Tags that are
+/// not marked as update replace all the previous tags.
+pub fn from_seek_with(
     mut r: impl BufRead + Seek,
     store: &mut impl TagStore,
     trap: &impl Trap,
+    opts: &ReadOptions,
 ) -> Result<()> {
-    r.rewind()?;
-    from_read(r, store, trap)
+    let tags = locate::find_tags(&mut r, trap)?;
+    if tags.is_empty() {
+        return Err(Error::NoTag);
+    }
+
+    for pos in tags {
+        if store.done() {
+            break;
+        }
+        r.seek(SeekFrom::Start(pos))?;
+        from_read_with(&mut r, store, trap, opts)?;
+    }
+
+    Ok(())
 }
 
 /// Read ID3v2 tag from file.
@@ -39,9 +77,19 @@ pub fn from_file(
     f: impl AsRef<Path>,
     store: &mut impl TagStore,
     trap: &impl Trap,
+) -> Result<()> {
+    from_file_with(f, store, trap, &ReadOptions::default())
+}
+
+/// Read ID3v2 tag from file with the given options.
+pub fn from_file_with(
+    f: impl AsRef<Path>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    opts: &ReadOptions,
 ) -> Result<()> {
     let f = BufReader::new(File::open(f)?);
-    from_read(f, store, trap)
+    from_read_with(f, store, trap, opts)
 }
 
 /// Read ID3v2 tag, assuming that the reader is already at the correct
@@ -51,22 +99,67 @@ pub fn from_read(
     store: &mut impl TagStore,
     trap: &impl Trap,
 ) -> Result<()> {
-    let mut r = Bread::new(r);
-    if !r.expect(b"ID3")? {
-        return Err(Error::NoTag);
+    from_read_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read ID3v2 tag with the given options, assuming that the reader is already
+/// at the correct position.
+pub fn from_read_with(
+    r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    opts: &ReadOptions,
+) -> Result<()> {
+    Id3v2Tag::read(r, trap, opts, &*store)?.store(store, trap)
+}
+
+/// Check CRC-32 of the following `len` bytes of the reader. Position of the
+/// reader is not changed.
+fn check_crc(
+    r: &mut Bread<impl BufRead + Seek>,
+    len: u64,
+    crc: u32,
+    trap: &impl Trap,
+) -> Result<()> {
+    let start = r.seek(SeekFrom::Current(0))?;
+    let mut hasher = crc32fast::Hasher::new();
+    let mut data = r.get_mut().take(len);
+    loop {
+        let buf = data.fill_buf()?;
+        if buf.is_empty() {
+            break;
+        }
+        hasher.update(buf);
+        let len = buf.len();
+        data.consume(len);
     }
+    r.seek(SeekFrom::Start(start))?;
 
-    let header: Header = r.get()?;
+    if hasher.finalize() != crc {
+        trap.error(Error::InvalidCrc)?;
+    }
+    Ok(())
+}
 
-    match header.major_version {
-        Header::MAJOR_VERSION2 => v2_2::from_bread(r, store, trap, header),
-        Header::MAJOR_VERSION3 => v2_3::from_bread(r, store, trap, header),
-        Header::MAJOR_VERSION4 => v2_4::from_bread(r, store, trap, header),
-        _ => Err(Error::Unsupported("ID3v2 other version than 2, 3 and 4.")),
+/// Decompress zlib compressed frame data. `size` is the expected size of the
+/// decompressed data.
+fn decompress(
+    r: impl BufRead,
+    size: usize,
+    opts: &ReadOptions,
+) -> Result<Vec<u8>> {
+    if size > opts.max_decompressed_size {
+        return Err(Error::TooLarge);
     }
+
+    let mut res = Vec::with_capacity(size);
+    ZlibDecoder::new(r)
+        .take(size as u64)
+        .read_to_end(&mut res)?;
+    Ok(res)
 }
 
-fn read_popularimeter(d: &[u8], trap: &impl Trap) -> Result<Rating> {
+fn read_popularimeter(d: &[u8], trap: &impl Trap) -> Result<Popularimeter> {
     let (l, email) = parsers::iso_8859_1_nt(d, trap)?;
     if l >= d.len() {
         return Err(Error::InvalidLength);
@@ -77,91 +170,18 @@ fn read_popularimeter(d: &[u8], trap: &impl Trap) -> Result<Rating> {
         play_counter = play_counter << 8 | *b as u64;
     }
 
-    Ok(Rating::Popularimeter(Popularimeter {
+    Ok(Popularimeter {
         email,
         rating,
         play_counter,
-    }))
+    })
 }
 
-fn read_picture34(
-    r: &mut Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
-    trap: &impl Trap,
-    mut length: i64,
-) -> Result<()> {
-    let enc = r.next()?;
-    length -= 1;
-
-    let null = match encoding_null(enc) {
-        Ok(n) => n,
-        Err(e) => {
-            r.seek_by(length)?;
-            return trap.error(e);
-        }
-    };
-
-    if length < 0 {
-        r.seek_by(length)?;
-        return trap.error(Error::InvalidLength);
-    }
-
-    let (res, len) =
-        r.witht_until_chunk(&[0], length as usize, trap, |s, t| {
-            read_string_enc_nonull(0, &s[..s.len() - 1], t)
-        })?;
-    length -= len as i64;
-
-    let Some(mime) = res else {
-        r.seek_by(length)?;
-        return Ok(());
-    };
-
-    let is_uri = mime == "-->";
-
-    let kind = if let Some(p) = PictureKind::from_id3(r.next()?) {
-        p
-    } else {
-        trap.error(Error::InvalidPictureKind)?;
-        PictureKind::default()
-    };
-    length -= 1;
-
-    if !store.stores_data(DataType::Picture(kind)) {
-        r.seek_by(length)?;
-        return Ok(());
-    }
-
-    if length < 0 {
-        r.seek_by(length)?;
-        return trap.error(Error::InvalidLength);
-    }
-
-    let (description, len) =
-        r.witht_until_chunk(null, length as usize, trap, |s, t| {
-            read_string_enc_nonull(enc, &s[..s.len() - null.len()], t)
-        })?;
-    length -= len as i64;
-
-    if length < 0 {
-        r.seek_by(length)?;
-        return trap.error(Error::InvalidLength);
+fn read_comment(mut data: &[u8], trap: &impl Trap) -> Result<Comment> {
+    if data.len() < 4 {
+        return Err(Error::InvalidLength);
     }
 
-    let data = r.read_exact_owned(length as usize)?;
-
-    store.add_picture(Picture::from_id3(
-        Some(mime),
-        description,
-        kind,
-        data,
-        is_uri,
-    ));
-
-    Ok(())
-}
-
-fn read_comment(mut data: &[u8], trap: &impl Trap) -> Result<Comment> {
     let enc = data[0];
     let language = trap.res(parsers::ascii(&data[1..4], trap))?;
 
@@ -176,23 +196,83 @@ fn read_comment(mut data: &[u8], trap: &impl Trap) -> Result<Comment> {
     })
 }
 
-fn read_year(data: &[u8], trap: &impl Trap) -> Result<DateTime> {
-    let year = read_string(data, trap)?;
-    parsers::year(&year, trap)
+fn read_lyrics(mut data: &[u8], trap: &impl Trap) -> Result<Lyrics> {
+    if data.len() < 4 {
+        return Err(Error::InvalidLength);
+    }
+
+    let enc = data[0];
+    let language = trap.res(parsers::ascii(&data[1..4], trap))?;
+
+    data = &data[4..];
+    let (len, desc) = read_string_enc_nt(enc, data, trap)?;
+    let (_, text) = read_string_enc(enc, &data[len..], trap)?;
+
+    Ok(Lyrics {
+        language,
+        description: Some(desc),
+        text,
+    })
 }
 
-fn read_time23(data: &[u8], trap: &impl Trap) -> Result<Duration> {
-    let s = read_string(data, trap)?;
-    parsers::time_only(&s)
+fn read_synced_lyrics(
+    mut data: &[u8],
+    trap: &impl Trap,
+) -> Result<SyncedLyrics> {
+    // 0: encoding
+    // 1..4: language
+    // 4: timestamp format
+    // 5: content type
+    // 6..: description and the synchronized text
+    if data.len() < 6 {
+        return Err(Error::InvalidLength);
+    }
+
+    let enc = data[0];
+    let language = trap.res(parsers::ascii(&data[1..4], trap))?;
+    let Some(timestamp_format) = TimestampFormat::from_id3(data[4]) else {
+        return Err(Error::Unsupported("Timestamp format in SYLT."));
+    };
+    let content_type = LyricsContentType::from_id3(data[5]);
+
+    data = &data[6..];
+    let (len, desc) = read_string_enc_nt(enc, data, trap)?;
+    data = &data[len..];
+
+    let mut lines = vec![];
+    while !data.is_empty() {
+        let (len, text) = read_string_enc_nt(enc, data, trap)?;
+        data = &data[len..];
+        if data.len() < 4 {
+            return Err(Error::InvalidLength);
+        }
+        let time = u32::from_be_bytes(data[..4].try_into().unwrap());
+        data = &data[4..];
+        lines.push((time, text));
+    }
+
+    Ok(SyncedLyrics {
+        language,
+        description: Some(desc),
+        content_type,
+        timestamp_format,
+        lines,
+    })
 }
 
-fn read_date23(data: &[u8], trap: &impl Trap) -> Result<DateTime> {
-    let s = read_string(data, trap)?;
-    parsers::date(&s, trap)
+fn parse_date23(s: &str, trap: &impl Trap) -> Result<DateTime> {
+    // TDAT is in the format DDMM
+    if s.len() == 4 && s.bytes().all(|a| a.is_ascii_digit()) {
+        return Ok(DateTime {
+            year: None,
+            date: Some((parsers::num(&s[2..])?, parsers::num(&s[..2])?)),
+            time: None,
+        });
+    }
+    parsers::date(s, trap)
 }
 
-fn read_length(data: &[u8], trap: &impl Trap) -> Result<Duration> {
-    let s = read_string(data, trap)?;
+fn parse_length(s: &str) -> Result<Duration> {
     let e = match s.parse() {
         Ok(r) => return Ok(Duration::from_millis(r)),
         Err(e) => e,
@@ -205,14 +285,7 @@ fn read_length(data: &[u8], trap: &impl Trap) -> Result<Duration> {
     }
 }
 
-fn read_num_of(data: &[u8], trap: &impl Trap) -> Result<(u32, Option<u32>)> {
-    let s = read_string(data, trap)?;
-    parsers::num_of(&s, trap)
-}
-
-fn read_genres23(data: &[u8], trap: &impl Trap) -> Result<Vec<String>> {
-    let s = read_string(data, trap)?;
-    let mut s = s.as_str();
+fn parse_genres23(mut s: &str, trap: &impl Trap) -> Result<Vec<String>> {
     let mut res = vec![];
 
     loop {
@@ -248,17 +321,37 @@ fn read_genres23(data: &[u8], trap: &impl Trap) -> Result<Vec<String>> {
     Ok(res)
 }
 
-fn read_string_list23(data: &[u8], trap: &impl Trap) -> Result<Vec<String>> {
-    let s = read_string(data, trap)?;
-    Ok(s.split('/').map(|a| a.to_string()).collect())
+/// Replace genre references in ID3v2.4 genres with the genre names.
+fn genres24(mut res: Vec<String>) -> Vec<String> {
+    for g in &mut res {
+        if g == "RX" {
+            *g = "Remix".to_string();
+        } else if g == "CR" {
+            *g = "Cover".to_string();
+        } else if let Ok(gn) = g.parse()
+            && let Some(gs) = get_genre(gn)
+        {
+            *g = gs.to_string()
+        }
+    }
+    res
 }
 
-fn read_string(data: &[u8], trap: &impl Trap) -> Result<String> {
-    if data.is_empty() {
-        return Err(Error::InvalidLength);
+/// Read all the null separated strings with the given encoding.
+fn read_string_list(
+    enc: u8,
+    mut data: &[u8],
+    trap: &impl Trap,
+) -> Result<Vec<String>> {
+    let mut res = vec![];
+
+    while !data.is_empty() {
+        let (l, s) = read_string_enc(enc, data, trap)?;
+        res.push(s);
+        data = &data[l..];
     }
 
-    read_string_enc(data[0], &data[1..], trap).map(|(_, s)| s)
+    Ok(res)
 }
 
 fn read_string_enc_nt(
@@ -289,25 +382,228 @@ fn read_string_enc(
     }
 }
 
-fn read_string_enc_nonull(
-    enc: u8,
-    d: &[u8],
-    trap: &impl Trap,
-) -> Result<String> {
-    match enc {
-        0 => parsers::iso_8859_1(d, trap),
-        1 => parsers::utf_16_bom(d, trap),
-        2 => parsers::utf_16_be(d, trap),
-        3 => parsers::utf_8(d, trap),
-        _ => Err(Error::InvalidEncoding),
-    }
-}
+#[cfg(test)]
+mod tests {
+    use std::io::{Cursor, Write};
 
-fn encoding_null(enc: u8) -> Result<&'static [u8]> {
-    let res: Result<&[_]> = match enc {
-        0 => Ok(&[0]),    // ISO-8859-1
-        1 => Ok(&[0, 0]), // UTF-16
-        _ => Err(Error::Unsupported("Unknown encoding in ID3v2.")),
+    use super::*;
+    use crate::{
+        id3::v2::header::Header,
+        trap::{Skip, Strict},
     };
-    res
+
+    fn syncsafe(n: usize) -> [u8; 4] {
+        [n >> 21, n >> 14, n >> 7, n].map(|a| a as u8 & 0x7F)
+    }
+
+    /// Create tag with the given version, header flags and data.
+    fn tag(version: u8, flags: u8, d: &[u8]) -> Vec<u8> {
+        let mut res = b"ID3".to_vec();
+        res.extend([version, 0, flags]);
+        res.extend(syncsafe(d.len()));
+        res.extend_from_slice(d);
+        res
+    }
+
+    /// Create frame. ID3v2.3 frames have plain size, ID3v2.4 frames have
+    /// syncsafe size.
+    fn frame(version: u8, id: &[u8; 4], flags: u16, d: &[u8]) -> Vec<u8> {
+        let mut res = id.to_vec();
+        if version == Header::MAJOR_VERSION3 {
+            res.extend((d.len() as u32).to_be_bytes());
+        } else {
+            res.extend(syncsafe(d.len()));
+        }
+        res.extend(flags.to_be_bytes());
+        res.extend_from_slice(d);
+        res
+    }
+
+    fn read(d: &[u8], opts: &ReadOptions) -> Result<Id3v2Tag> {
+        Id3v2Tag::from_read_with(Cursor::new(d), &Strict, opts)
+    }
+
+    /// Get the values of text frame with the given id.
+    fn text<'a>(tag: &'a Id3v2Tag, id: &str) -> Option<&'a [String]> {
+        tag.frames
+            .iter()
+            .find(|a| a.id == id)
+            .and_then(|a| match &a.content {
+                FrameContent::Text(v) => Some(v.as_slice()),
+                _ => None,
+            })
+    }
+
+    // Latin-1 text `aÿb`. `0x00` is inserted after `0xFF` by
+    // unsynchronization.
+    const TEXT: &[u8] = b"\0a\xFFb";
+    const TEXT_UNSYNC: &[u8] = b"\0a\xFF\0b";
+
+    #[test]
+    fn tag_level_unsync_v3() {
+        // Frame size is the size after resynchronization.
+        let mut d = frame(3, b"TIT2", 0, TEXT);
+        d.truncate(10);
+        d.extend(TEXT_UNSYNC);
+        // Frame data `00 FF 00` with inserted `00`.
+        d.extend(frame(3, b"TALB", 0, b"\0\xFF\0"));
+        d.push(0);
+        let tag = read(&tag(3, 0x80, &d), &ReadOptions::default()).unwrap();
+        assert_eq!(text(&tag, "TIT2").unwrap(), ["aÿb"]);
+        assert_eq!(text(&tag, "TALB").unwrap(), ["ÿ"]);
+    }
+
+    #[test]
+    fn frame_level_unsync_v4() {
+        // Only the first frame is unsynchronized.
+        let mut d = frame(4, b"TIT2", 0x0002, TEXT_UNSYNC);
+        d.extend(frame(4, b"TALB", 0, b"\0\xFF\0"));
+        let tag = read(&tag(4, 0, &d), &ReadOptions::default()).unwrap();
+        assert_eq!(text(&tag, "TIT2").unwrap(), ["aÿb"]);
+        assert_eq!(text(&tag, "TALB").unwrap(), ["ÿ"]);
+    }
+
+    #[test]
+    fn tag_level_unsync_v4() {
+        // The tag flag applies to all frames with size before
+        // resynchronization.
+        let d = frame(4, b"TIT2", 0, TEXT_UNSYNC);
+        let tag = read(&tag(4, 0x80, &d), &ReadOptions::default()).unwrap();
+        assert_eq!(text(&tag, "TIT2").unwrap(), ["aÿb"]);
+    }
+
+    fn zlib(d: &[u8]) -> Vec<u8> {
+        let mut enc = flate2::write::ZlibEncoder::new(
+            vec![],
+            flate2::Compression::default(),
+        );
+        enc.write_all(d).unwrap();
+        enc.finish().unwrap()
+    }
+
+    /// Create tag with compressed `TIT2` frame and uncompressed `TALB`
+    /// frame.
+    fn compressed_tag(version: u8) -> Vec<u8> {
+        let text = b"\0compressed title";
+        let mut d = vec![];
+        let flags = if version == Header::MAJOR_VERSION3 {
+            d.extend((text.len() as u32).to_be_bytes());
+            0x80
+        } else {
+            d.extend(syncsafe(text.len()));
+            0x0009
+        };
+        d.extend(zlib(text));
+        let mut res = frame(version, b"TIT2", flags, &d);
+        res.extend(frame(version, b"TALB", 0, b"\0album"));
+        tag(version, 0, &res)
+    }
+
+    #[test]
+    fn decompress_frames() {
+        for version in [3, 4] {
+            let d = compressed_tag(version);
+            let tag = read(&d, &ReadOptions::default()).unwrap();
+            assert_eq!(text(&tag, "TIT2").unwrap(), ["compressed title"]);
+            assert_eq!(text(&tag, "TALB").unwrap(), ["album"]);
+        }
+    }
+
+    #[test]
+    fn decompressed_size_limit() {
+        let opts = ReadOptions {
+            max_decompressed_size: 16,
+            ..Default::default()
+        };
+        for version in [3, 4] {
+            let d = compressed_tag(version);
+            let res = read(&d, &opts);
+            assert!(matches!(res, Err(Error::TooLarge)));
+
+            let tag = Id3v2Tag::from_read_with(Cursor::new(&d), &Skip, &opts)
+                .unwrap();
+            assert!(text(&tag, "TIT2").is_none());
+            assert_eq!(text(&tag, "TALB").unwrap(), ["album"]);
+        }
+    }
+
+    /// Create tag with extended header with CRC of the frames and padding.
+    fn crc_tag(version: u8, crc: u32) -> Vec<u8> {
+        let frames = frame(version, b"TIT2", 0, b"\0title");
+        let padding = [0; 6];
+        let mut d = vec![];
+        if version == Header::MAJOR_VERSION3 {
+            d.extend(b"\0\0\0\x0A\x80\0");
+            d.extend((padding.len() as u32).to_be_bytes());
+            d.extend(crc.to_be_bytes());
+        } else {
+            d.extend(b"\0\0\0\x0C\x01\x20\x05");
+            d.extend([28, 21, 14, 7, 0].map(|a| (crc >> a) as u8 & 0x7F));
+        }
+        d.extend(frames);
+        d.extend(padding);
+        tag(version, 0x40, &d)
+    }
+
+    fn crc(d: &[u8]) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+        hasher.update(d);
+        hasher.finalize()
+    }
+
+    #[test]
+    fn verify_crc() {
+        let opts = ReadOptions {
+            verify_crc: true,
+            ..Default::default()
+        };
+        let frames = frame(3, b"TIT2", 0, b"\0title");
+        let frames4 = frame(4, b"TIT2", 0, b"\0title");
+
+        // ID3v2.3 CRC doesn't include padding.
+        let d = crc_tag(3, crc(&frames));
+        let tag = read(&d, &opts).unwrap();
+        assert_eq!(tag.extended_header.unwrap().crc, Some(crc(&frames)));
+
+        // ID3v2.4 CRC includes padding.
+        let d = crc_tag(4, crc(&[frames4.as_slice(), &[0; 6]].concat()));
+        read(&d, &opts).unwrap();
+
+        for d in [crc_tag(3, crc(b"")), crc_tag(4, crc(&frames4))] {
+            assert!(matches!(read(&d, &opts), Err(Error::InvalidCrc)));
+            // CRC is not verified by default.
+            read(&d, &ReadOptions::default()).unwrap();
+            // The tag is read with invalid CRC when the error is skipped.
+            let tag = Id3v2Tag::from_read_with(Cursor::new(&d), &Skip, &opts)
+                .unwrap();
+            assert_eq!(text(&tag, "TIT2").unwrap(), ["title"]);
+        }
+    }
+
+    #[test]
+    fn lyrics() {
+        let d = b"\0engdesc\0line 1\nline 2";
+        let l = read_lyrics(d, &Strict).unwrap();
+        assert_eq!(l.language.as_deref(), Some("eng"));
+        assert_eq!(l.description.as_deref(), Some("desc"));
+        assert_eq!(l.text, "line 1\nline 2");
+    }
+
+    #[test]
+    fn synced_lyrics() {
+        // Latin-1, milliseconds, lyrics.
+        let d = b"\0eng\x02\x01desc\0first\0\0\0\x03\xE8second\0\0\0\x09\xC4";
+        let l = read_synced_lyrics(d, &Strict).unwrap();
+        assert_eq!(l.language.as_deref(), Some("eng"));
+        assert_eq!(l.description.as_deref(), Some("desc"));
+        assert_eq!(l.timestamp_format, TimestampFormat::Milliseconds);
+        assert_eq!(l.content_type, LyricsContentType::Lyrics);
+        assert_eq!(
+            l.lines,
+            [(1000, "first".to_string()), (2500, "second".to_string())]
+        );
+
+        // Missing timestamp.
+        assert!(read_synced_lyrics(&d[..d.len() - 1], &Strict).is_err());
+    }
 }
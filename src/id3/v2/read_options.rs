@@ -0,0 +1,19 @@
+/// Options for reading ID3v2 tags.
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// Maximum size of decompressed frame. Compressed frames that would be
+    /// larger are skipped with [`crate::Error::TooLarge`].
+    pub max_decompressed_size: usize,
+    /// Verify CRC-32 of tags that have it in their extended header. Tags with
+    /// invalid CRC are reported with [`crate::Error::InvalidCrc`].
+    pub verify_crc: bool,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            max_decompressed_size: 16 * 1024 * 1024,
+            verify_crc: false,
+        }
+    }
+}
@@ -0,0 +1,197 @@
+use std::io::{self, BufRead, ErrorKind, Read, Seek, SeekFrom};
+
+use crate::Result;
+
+/// Reader that reverses the unsynchronization scheme of ID3v2. That is, it
+/// removes every `0x00` that follows `0xFF`. Data are decoded on the fly so
+/// that the whole tag doesn't have to be loaded into memory.
+///
+/// Seeking backwards is supported by decoding the data again from the start.
+/// Seeking relative to the end is not supported.
+pub struct Unsync<R> {
+    read: R,
+    /// Position of the data in the underlying reader.
+    start: u64,
+    /// Length of the data in the underlying reader.
+    len: u64,
+    /// Number of bytes that may still be read from the underlying reader.
+    limit: u64,
+    /// The last byte read from the underlying reader.
+    prev: u8,
+    /// Decoded data.
+    buf: Vec<u8>,
+    /// Position within `buf`.
+    pos: usize,
+    /// Position within the decoded data.
+    position: u64,
+}
+
+impl<R: BufRead + Seek> Unsync<R> {
+    /// Create reader that will decode the following `limit` bytes of the
+    /// given reader. Returns the reader and the length of the decoded data.
+    ///
+    /// The length is determined by reading the data ahead and seeking back.
+    pub fn new(mut read: R, limit: u64) -> Result<(Self, u64)> {
+        let start = read.stream_position()?;
+        let mut prev = 0;
+        let mut raw = 0;
+        let mut len = 0;
+        while raw < limit {
+            let buf = read.fill_buf()?;
+            let buf = &buf[..buf.len().min((limit - raw) as usize)];
+            if buf.is_empty() {
+                break;
+            }
+            for &b in buf {
+                if !(prev == 0xFF && b == 0) {
+                    len += 1;
+                }
+                prev = b;
+            }
+            let cnt = buf.len();
+            read.consume(cnt);
+            raw += cnt as u64;
+        }
+        read.seek(SeekFrom::Current(-(raw as i64)))?;
+
+        let res = Self {
+            read,
+            start,
+            len: raw,
+            limit: raw,
+            prev: 0,
+            buf: vec![],
+            pos: 0,
+            position: 0,
+        };
+        Ok((res, len))
+    }
+}
+
+impl<R: BufRead> Read for Unsync<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let buf = self.fill_buf()?;
+        let len = buf.len().min(out.len());
+        out[..len].copy_from_slice(&buf[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead> BufRead for Unsync<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.pos < self.buf.len() {
+            return Ok(&self.buf[self.pos..]);
+        }
+
+        self.buf.clear();
+        self.pos = 0;
+        while self.buf.is_empty() && self.limit > 0 {
+            let d = self.read.fill_buf()?;
+            let d = &d[..d.len().min(self.limit as usize)];
+            if d.is_empty() {
+                break;
+            }
+            for &b in d {
+                if !(self.prev == 0xFF && b == 0) {
+                    self.buf.push(b);
+                }
+                self.prev = b;
+            }
+            let cnt = d.len();
+            self.read.consume(cnt);
+            self.limit -= cnt as u64;
+        }
+
+        Ok(&self.buf)
+    }
+
+    fn consume(&mut self, amt: usize) {
+        let amt = amt.min(self.buf.len() - self.pos);
+        self.pos += amt;
+        self.position += amt as u64;
+    }
+}
+
+impl<R: BufRead + Seek> Seek for Unsync<R> {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let target = match pos {
+            SeekFrom::Start(p) => p,
+            SeekFrom::Current(n) => {
+                self.position.checked_add_signed(n).ok_or(io::Error::new(
+                    ErrorKind::InvalidInput,
+                    "Seek before start.",
+                ))?
+            }
+            SeekFrom::End(_) => {
+                return Err(io::Error::new(
+                    ErrorKind::Unsupported,
+                    "Seek from end in unsynchronized data.",
+                ));
+            }
+        };
+
+        let back = self.position - target.min(self.position);
+        if back <= self.pos as u64 {
+            // Target is within the decoded buffer.
+            self.pos -= back as usize;
+            self.position -= back;
+        } else {
+            // Decode again from the start.
+            self.read.seek(SeekFrom::Start(self.start))?;
+            self.limit = self.len;
+            self.prev = 0;
+            self.buf.clear();
+            self.pos = 0;
+            self.position = 0;
+        }
+
+        let skip = target - self.position;
+        io::copy(&mut self.by_ref().take(skip), &mut io::sink())?;
+        Ok(self.position)
+    }
+}
+
+/// Reverse the unsynchronization scheme.
+pub fn resync(d: &[u8]) -> Vec<u8> {
+    let mut res = Vec::with_capacity(d.len());
+    let mut prev = 0;
+    for &b in d {
+        if !(prev == 0xFF && b == 0) {
+            res.push(b);
+        }
+        prev = b;
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn resync_data() {
+        assert_eq!(resync(b"\xFF\0\xFF\0\0\xE0"), b"\xFF\xFF\0\xE0");
+        assert_eq!(resync(b"\0\xFF"), b"\0\xFF");
+    }
+
+    #[test]
+    fn read_and_seek() {
+        // The last byte is outside the limit.
+        let d = b"a\xFF\0b\xFF\0\0c";
+        let (mut r, len) = Unsync::new(Cursor::new(d), 7).unwrap();
+        assert_eq!(len, 5);
+
+        let mut res = vec![];
+        r.read_to_end(&mut res).unwrap();
+        assert_eq!(res, b"a\xFFb\xFF\0");
+
+        r.seek(SeekFrom::Start(1)).unwrap();
+        let mut buf = [0; 2];
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"\xFFb");
+        assert_eq!(r.stream_position().unwrap(), 3);
+    }
+}
@@ -1,30 +0,0 @@
-/// Title
-pub const TT2: u32 = u32::from_be_bytes(*b"\0TT2");
-/// Artists
-pub const TP1: u32 = u32::from_be_bytes(*b"\0TP1");
-/// Album artist
-pub const TP2: u32 = u32::from_be_bytes(*b"\0TP2");
-/// Genres
-pub const TCO: u32 = u32::from_be_bytes(*b"\0TCO");
-/// Album
-pub const TAL: u32 = u32::from_be_bytes(*b"\0TAL");
-/// Disc
-pub const TPA: u32 = u32::from_be_bytes(*b"\0TPA");
-/// Track number
-pub const TRK: u32 = u32::from_be_bytes(*b"\0TRK");
-/// Year
-pub const TYE: u32 = u32::from_be_bytes(*b"\0TYE");
-/// Date
-pub const TDA: u32 = u32::from_be_bytes(*b"\0TDA");
-/// Time
-pub const TIM: u32 = u32::from_be_bytes(*b"\0TIM");
-/// Length
-pub const TLE: u32 = u32::from_be_bytes(*b"\0TLE");
-/// Comment
-pub const COM: u32 = u32::from_be_bytes(*b"\0COM");
-/// Picture
-pub const PIC: u32 = u32::from_be_bytes(*b"\0PIC");
-/// Copyright
-pub const TCR: u32 = u32::from_be_bytes(*b"\0TCR");
-/// Popularimeter (rating)
-pub const POP: u32 = u32::from_be_bytes(*b"\0POP");
@@ -1,209 +1,76 @@
-mod frame;
 mod frame_header;
-mod picture_header;
 
-use self::{frame_header::*, picture_header::*};
+use self::frame_header::*;
 
 use std::io::{BufRead, Seek};
 
 use crate::{
-    DataType, Error, Picture, PictureKind, Result, TagStore, TagStoreExt,
-    TagType,
+    Error, Result,
     bread::Bread,
     id3::v2::{
-        encoding_null, header::Header, read_comment, read_date23,
-        read_genres23, read_length, read_num_of, read_popularimeter,
-        read_string, read_string_enc_nonull, read_string_list23, read_time23,
-        read_year,
+        FrameFilter, Id3v2Frame, Id3v2Tag, header::Header, unsync::Unsync,
     },
-    parsers,
-    trap::{Trap, TrapExt},
+    trap::Trap,
 };
 
 // Implementation is based on: https://id3.org/id3v2-00.
 
 pub fn from_bread(
     mut r: Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
     trap: &impl Trap,
-    header: Header,
+    header: &Header,
+    tag: &mut Id3v2Tag,
+    filter: &impl FrameFilter,
 ) -> Result<()> {
-    store.set_tag_type(TagType::Id3v2(2));
-
     if header.compression2() {
         return Err(Error::Unsupported("ID3v2.2 compression."));
     }
 
-    let mut pos = 0;
-
-    let mut comments = vec![];
-    let mut ratings = vec![];
-
-    while !store.done() && pos + 6 < header.size {
-        let header: FrameHeader = r.get()?;
-        pos += header.size + 6;
-
-        let hsize = header.size as usize;
-
-        match header.id {
-            0 => break,
-            frame::TT2 if store.stores_data(DataType::Title) => {
-                if let Some(t) = r.witht(hsize, trap, read_string)? {
-                    store.set_title(t);
-                }
-            }
-            frame::TP1 if store.stores_data(DataType::Artists) => {
-                if let Some(a) = r.witht(hsize, trap, read_string_list23)? {
-                    store.set_artists(a);
-                }
-            }
-            frame::TCO if store.stores_data(DataType::Genres) => {
-                if let Some(a) = r.witht(hsize, trap, read_genres23)? {
-                    store.set_genres(a);
-                }
-            }
-            frame::TAL if store.stores_data(DataType::Album) => {
-                if let Some(a) = r.witht(hsize, trap, read_string)? {
-                    store.set_album(a);
-                }
-            }
-            frame::TPA
-                if store.stores_data(DataType::Disc)
-                    || store.stores_data(DataType::DiscCount) =>
-            {
-                if let Some((d, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_disc(d);
-                    if let Some(c) = c {
-                        store.set_disc_count(c);
-                    }
-                }
-            }
-            frame::TRK
-                if store.stores_data(DataType::Track)
-                    || store.stores_data(DataType::TrackCount) =>
-            {
-                if let Some((t, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_track(t);
-                    if let Some(c) = c {
-                        store.set_track_count(c);
-                    }
-                }
-            }
-            frame::TYE if store.stores_data(DataType::Year) => {
-                if let Some(d) = r.witht(hsize, trap, read_year)? {
-                    store.set_date_time(d);
-                }
-            }
-            frame::TDA if store.stores_data(DataType::Date) => {
-                if let Some(d) = r.witht(hsize, trap, read_date23)? {
-                    store.set_date_time(d);
-                }
-            }
-            frame::TIM if store.stores_data(DataType::Time) => {
-                if let Some(t) = r.witht(hsize, trap, read_time23)? {
-                    store.set_time(t);
-                }
-            }
-            frame::TLE if store.stores_data(DataType::Length) => {
-                if let Some(l) = r.witht(hsize, trap, read_length)? {
-                    store.set_length(l);
-                }
-            }
-            frame::COM if store.stores_data(DataType::Comments) => {
-                comments.extend(r.witht(hsize, trap, read_comment)?);
-            }
-            frame::PIC => {
-                read_picture(&mut r, store, trap, header.size as i64)?;
-            }
-            frame::TCR if store.stores_data(DataType::Copyright) => {
-                if let Some(c) = r.witht(hsize, trap, read_string)? {
-                    store.set_copyright(c);
-                }
-            }
-            frame::POP if store.stores_data(DataType::Ratings) => {
-                ratings.extend(r.witht(hsize, trap, read_popularimeter)?);
-            }
-            frame::TP2 if store.stores_data(DataType::AlbumArtist) => {
-                if let Some(a) = r.witht(hsize, trap, read_string)? {
-                    store.set_album_artist(a);
-                }
-            }
-            _ => r.seek_by(header.size as i64)?,
-        }
-    }
-
-    if !comments.is_empty() {
-        store.set_comments(comments);
-    }
-
-    if !ratings.is_empty() {
-        store.set_ratings(ratings);
+    if header.unsynchronization() {
+        let (r, size) = Unsync::new(r.get_mut(), header.size as u64)?;
+        read_frames(Bread::new(r), trap, size as u32, tag, filter)
+    } else {
+        read_frames(r, trap, header.size, tag, filter)
     }
-
-    Ok(())
 }
 
-fn read_picture(
-    r: &mut Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
+/// Read the frames of the tag. `size` is the size of the tag data after
+/// reversing the unsynchronization.
+fn read_frames(
+    mut r: Bread<impl BufRead + Seek>,
     trap: &impl Trap,
-    mut length: i64,
+    size: u32,
+    tag: &mut Id3v2Tag,
+    filter: &impl FrameFilter,
 ) -> Result<()> {
-    let header: PictureHeader = r.get()?;
-    length -= 5;
-
-    let kind = if let Some(t) = PictureKind::from_id3(header.typ) {
-        t
-    } else {
-        trap.error(Error::InvalidPictureKind)?;
-        PictureKind::default()
-    };
-
-    if !store.stores_data(DataType::Picture(kind)) {
-        r.seek_by(length)?;
-        return Ok(());
-    }
+    let mut pos = 0;
 
-    let null = match encoding_null(header.encoding) {
-        Ok(n) => n,
-        Err(e) => {
-            r.seek_by(length)?;
-            return trap.error(e);
+    while !filter.done() && pos + 6 < size {
+        let header: FrameHeader = r.get()?;
+        if header.id == 0 {
+            break;
         }
-    };
 
-    let format = trap.res(parsers::iso_8859_1(&header.format, trap))?;
-    let is_uri = format.as_deref() == Some("-->");
-
-    if length < 0 {
-        r.seek_by(length)?;
-        return trap.error(Error::InvalidLength);
-    }
+        pos += header.size + 6;
+        if pos > size {
+            return trap.error(Error::InvalidLength);
+        }
 
-    let (description, len) =
-        r.witht_until_chunk(null, length as usize, trap, |s, t| {
-            read_string_enc_nonull(
-                header.encoding,
-                &s[..s.len() - null.len()],
-                t,
-            )
-        })?;
-    length -= len as i64;
+        let id = &header.id.to_be_bytes()[1..];
+        if !filter.keep(id) {
+            r.seek_by(header.size as i64)?;
+            continue;
+        }
 
-    if length < 0 {
-        r.seek_by(length)?;
-        return trap.error(Error::InvalidLength);
+        let data = r.read_exact_owned(header.size as usize)?;
+        tag.frames.push(Id3v2Frame::parse(
+            id,
+            0,
+            data,
+            Header::MAJOR_VERSION2,
+            trap,
+        )?);
     }
 
-    let data = r.read_exact_owned(length as usize)?;
-
-    store.add_picture(Picture::from_id3(
-        format,
-        description,
-        kind,
-        data,
-        is_uri,
-    ));
-
     Ok(())
 }
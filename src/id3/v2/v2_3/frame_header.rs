@@ -3,6 +3,7 @@ use std::io::BufRead;
 use crate::{
     Result,
     bread::{Bread, Breadable},
+    id3::v2::v2_4,
 };
 
 #[derive(Debug)]
@@ -13,9 +14,30 @@ pub struct FrameHeader {
 }
 
 impl FrameHeader {
-    const COMPRESSION: u16 = 0x80;
-    const ENCRYPTION: u16 = 0x40;
-    const GROUPING: u16 = 0x20;
+    pub const TAG_ALTER_PRESERVATION: u16 = 0x8000;
+    pub const FILE_ALTER_PRESERVATION: u16 = 0x4000;
+    pub const READ_ONLY: u16 = 0x2000;
+    pub const COMPRESSION: u16 = 0x80;
+    pub const ENCRYPTION: u16 = 0x40;
+    pub const GROUPING: u16 = 0x20;
+
+    /// Convert the frame flags to ID3v2.4. Returns [`None`] if the frame
+    /// should be discarded when the tag is altered or if the frame data
+    /// depends on the flags.
+    pub fn status_to_v4(flags: u16) -> Option<u16> {
+        let format = Self::COMPRESSION | Self::ENCRYPTION | Self::GROUPING;
+        if flags & (format | Self::TAG_ALTER_PRESERVATION) != 0 {
+            return None;
+        }
+        let mut res = 0;
+        if flags & Self::FILE_ALTER_PRESERVATION != 0 {
+            res |= v2_4::FrameHeader::FILE_ALTER_PRESERVATION;
+        }
+        if flags & Self::READ_ONLY != 0 {
+            res |= v2_4::FrameHeader::READ_ONLY;
+        }
+        Some(res)
+    }
 
     pub fn compression(&self) -> bool {
         self.has_flag(Self::COMPRESSION)
@@ -3,14 +3,13 @@ mod frame_header;
 use std::io::{BufRead, Seek};
 
 use crate::{
-    DataType, Error, PictureKind, Result, TagStore, TagStoreExt, TagType,
+    Error, Result,
     bread::Bread,
     id3::v2::{
-        frame34, header::Header, read_comment, read_date23, read_genres23,
-        read_length, read_num_of, read_picture34, read_popularimeter,
-        read_string, read_string_list23, read_time23, read_year,
+        ExtendedHeader, FrameFilter, Id3v2Frame, Id3v2Tag, ReadOptions,
+        check_crc, decompress, header::Header, unsync::Unsync,
     },
-    trap::Trap,
+    trap::{Trap, TrapExt},
 };
 
 pub use self::frame_header::*;
@@ -19,143 +18,118 @@ pub use self::frame_header::*;
 
 pub fn from_bread(
     mut r: Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
     trap: &impl Trap,
-    header: Header,
+    header: &Header,
+    opts: &ReadOptions,
+    tag: &mut Id3v2Tag,
+    filter: &impl FrameFilter,
 ) -> Result<()> {
-    store.set_tag_type(TagType::Id3v2(3));
-
     if header.unsynchronization() {
-        return Err(Error::Unsupported("ID3v2.3 unsynchronization."));
+        let (r, size) = Unsync::new(r.get_mut(), header.size as u64)?;
+        read_frames(
+            Bread::new(r),
+            trap,
+            header,
+            size as u32,
+            opts,
+            tag,
+            filter,
+        )
+    } else {
+        read_frames(r, trap, header, header.size, opts, tag, filter)
     }
+}
 
+/// Read the frames of the tag. `size` is the size of the tag data after
+/// reversing the unsynchronization.
+fn read_frames(
+    mut r: Bread<impl BufRead + Seek>,
+    trap: &impl Trap,
+    header: &Header,
+    size: u32,
+    opts: &ReadOptions,
+    tag: &mut Id3v2Tag,
+    filter: &impl FrameFilter,
+) -> Result<()> {
     let mut pos = 0;
 
     if header.extended_header34() {
-        let len = r.get_be::<u32>()? - 4;
-        r.seek_by(len as i64)?;
-        pos += 4;
+        let (ext, len) =
+            ExtendedHeader::read(r.get_mut(), Header::MAJOR_VERSION3, trap)?;
+        pos += len;
+
+        // CRC is calculated from the frames without padding.
+        if opts.verify_crc
+            && let Some(ext) = &ext
+            && let Some(crc) = ext.crc
+        {
+            let padding = ext.padding_size.unwrap_or_default();
+            let len = size.saturating_sub(pos).saturating_sub(padding);
+            check_crc(&mut r, len as u64, crc, trap)?;
+        }
+
+        tag.extended_header = ext;
     }
 
-    let mut comments = vec![];
-    let mut ratings = vec![];
+    while !filter.done() && pos + 10 < size {
+        let header: FrameHeader = r.get()?;
+        if header.id == 0 {
+            break;
+        }
+
+        pos = pos.saturating_add(header.size + 10);
+        if pos > size {
+            return trap.error(Error::InvalidLength);
+        }
 
-    while !store.done() && pos + 10 < header.size {
-        let mut header: FrameHeader = r.get()?;
-        pos += header.size + 10;
-        if header.compression() || header.encryption() {
-            trap.error(Error::Unsupported(
-                "ID3v2.3 compression and encryption.",
-            ))?;
+        let id = header.id.to_be_bytes();
+        if !filter.keep(&id) {
             r.seek_by(header.size as i64)?;
             continue;
         }
-        if header.grouping() {
-            r.seek_by(1)?;
-            header.size -= 1;
-        }
 
-        let hsize = header.size as usize;
+        let mut data = r.read_exact_owned(header.size as usize)?;
+        if header.encryption() {
+            trap.error(Error::Unsupported("ID3v2.3 encryption."))?;
+            tag.frames
+                .push(Id3v2Frame::unknown(&id, header.flags, data));
+            continue;
+        }
 
-        match header.id {
-            0 => break,
-            frame34::APIC
-                if store.stores_data(DataType::Picture(
-                    PictureKind::all_id3(),
-                )) =>
-            {
-                read_picture34(&mut r, store, trap, header.size as i64)?;
-            }
-            frame34::TALB if store.stores_data(DataType::Album) => {
-                if let Some(r) = r.witht(hsize, trap, read_string)? {
-                    store.set_album(r);
-                }
-            }
-            frame34::TCON if store.stores_data(DataType::Genres) => {
-                if let Some(g) = r.witht(hsize, trap, read_genres23)? {
-                    store.set_genres(g);
-                }
-            }
-            frame34::TDAT if store.stores_data(DataType::Date) => {
-                if let Some(d) = r.witht(hsize, trap, read_date23)? {
-                    store.set_date_time(d);
-                }
-            }
-            frame34::TIT2 if store.stores_data(DataType::Title) => {
-                if let Some(t) = r.witht(hsize, trap, read_string)? {
-                    store.set_title(t);
-                }
-            }
-            frame34::TIME if store.stores_data(DataType::Time) => {
-                if let Some(t) = r.witht(hsize, trap, read_time23)? {
-                    store.set_time(t);
-                }
-            }
-            frame34::TLEN if store.stores_data(DataType::Length) => {
-                if let Some(l) = r.witht(hsize, trap, read_length)? {
-                    store.set_length(l);
-                }
-            }
-            frame34::TPE1 if store.stores_data(DataType::Artists) => {
-                if let Some(a) = r.witht(hsize, trap, read_string_list23)? {
-                    store.set_artists(a);
-                }
-            }
-            frame34::TPOS
-                if store.stores_data(DataType::Disc)
-                    || store.stores_data(DataType::DiscCount) =>
-            {
-                if let Some((d, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_disc(d);
-                    if let Some(c) = c {
-                        store.set_disc_count(c);
-                    }
-                }
-            }
-            frame34::TRCK
-                if store.stores_data(DataType::Track)
-                    || store.stores_data(DataType::TrackCount) =>
-            {
-                if let Some((t, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_track(t);
-                    if let Some(c) = c {
-                        store.set_track_count(c);
-                    }
-                }
-            }
-            frame34::TYER if store.stores_data(DataType::Year) => {
-                if let Some(d) = r.witht(hsize, trap, read_year)? {
-                    store.set_date_time(d);
-                }
-            }
-            frame34::COMM if store.stores_data(DataType::Comments) => {
-                comments.extend(r.witht(hsize, trap, read_comment)?);
-            }
-            frame34::TCOP if store.stores_data(DataType::Copyright) => {
-                if let Some(c) = r.witht(hsize, trap, read_string)? {
-                    store.set_copyright(c);
-                }
-            }
-            frame34::POPM if store.stores_data(DataType::Ratings) => {
-                ratings.extend(r.witht(hsize, trap, read_popularimeter)?);
-            }
-            frame34::TPE2 if store.stores_data(DataType::AlbumArtist) => {
-                if let Some(a) = r.witht(hsize, trap, read_string)? {
-                    store.set_album_artist(a);
-                }
-            }
-            _ => {
-                r.seek_by(header.size as i64)?;
-            }
+        // 0..4: decompressed size (if compressed)
+        // then: group identifier (if grouped)
+        let mut d = data.as_slice();
+        let mut decompressed_size = None;
+        if header.compression() {
+            if d.len() < 4 {
+                trap.error(Error::InvalidLength)?;
+                continue;
+            }
+            decompressed_size =
+                Some(u32::from_be_bytes(d[..4].try_into().unwrap()));
+            d = &d[4..];
+        }
+        if header.grouping() {
+            d = d.get(1..).unwrap_or_default();
         }
-    }
 
-    if !comments.is_empty() {
-        store.set_comments(comments);
-    }
+        if let Some(dsize) = decompressed_size {
+            let Some(dec) = trap.res(decompress(d, dsize as usize, opts))?
+            else {
+                continue;
+            };
+            data = dec;
+        } else if d.len() != data.len() {
+            data = d.to_vec();
+        }
 
-    if !ratings.is_empty() {
-        store.set_ratings(ratings);
+        tag.frames.push(Id3v2Frame::parse(
+            &id,
+            header.flags,
+            data,
+            Header::MAJOR_VERSION3,
+            trap,
+        )?);
     }
 
     Ok(())
@@ -3,6 +3,7 @@ use std::io::BufRead;
 use crate::{
     Result,
     bread::{Bread, Breadable},
+    id3::v2::v2_3,
     parsers,
 };
 
@@ -13,6 +14,9 @@ pub struct FrameHeader {
 }
 
 impl FrameHeader {
+    pub const TAG_ALTER_PRESERVATION: u16 = 0x4000;
+    pub const FILE_ALTER_PRESERVATION: u16 = 0x2000;
+    pub const READ_ONLY: u16 = 0x1000;
     pub const GROUPING: u16 = 0x0040;
     pub const COMPRESSION: u16 = 0x0008;
     pub const ENCRYPTION: u16 = 0x0004;
@@ -27,6 +31,28 @@ impl FrameHeader {
         }
     }
 
+    /// Convert the frame flags to ID3v2.3. Returns [`None`] if the frame
+    /// should be discarded when the tag is altered or if the frame data
+    /// depends on the flags.
+    pub fn status_to_v3(flags: u16) -> Option<u16> {
+        let format = Self::GROUPING
+            | Self::COMPRESSION
+            | Self::ENCRYPTION
+            | Self::UNSYNCHRONIZATION
+            | Self::DATA_LENGTH_INDICATOR;
+        if flags & (format | Self::TAG_ALTER_PRESERVATION) != 0 {
+            return None;
+        }
+        let mut res = 0;
+        if flags & Self::FILE_ALTER_PRESERVATION != 0 {
+            res |= v2_3::FrameHeader::FILE_ALTER_PRESERVATION;
+        }
+        if flags & Self::READ_ONLY != 0 {
+            res |= v2_3::FrameHeader::READ_ONLY;
+        }
+        Some(res)
+    }
+
     pub fn grouping(&self) -> bool {
         self.has_flag(Self::GROUPING)
     }
@@ -1,22 +1,18 @@
 mod frame_header;
 
-use self::frame_header::*;
+pub use self::frame_header::*;
 
 use std::io::{BufRead, Seek};
 
 use crate::{
-    DataType, Error, PictureKind, Result, TagStore, TagStoreExt, TagType,
+    Error, Result,
     bread::Bread,
-    id3::{
-        genres::get_genre,
-        v2::{
-            frame34, header::Header, read_comment, read_length, read_num_of,
-            read_picture34, read_popularimeter, read_string, read_string_enc,
-            read_year,
-        },
+    id3::v2::{
+        ExtendedHeader, FrameFilter, Id3v2Frame, Id3v2Tag, ReadOptions,
+        check_crc, decompress, header::Header, unsync::resync,
     },
     parsers,
-    trap::Trap,
+    trap::{Trap, TrapExt},
 };
 
 // Implementation is based on:
@@ -25,175 +21,102 @@ use crate::{
 
 pub fn from_bread(
     mut r: Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
     trap: &impl Trap,
-    header: Header,
+    header: &Header,
+    opts: &ReadOptions,
+    tag: &mut Id3v2Tag,
+    filter: &impl FrameFilter,
 ) -> Result<()> {
-    store.set_tag_type(TagType::Id3v2(4));
-
     let mut pos = 0;
 
     if header.extended_header34() {
-        let len = r.withc(parsers::syncsafe_be_u32)? - 4;
-        r.seek_by(len as i64)?;
-        pos += len + 4;
-    }
+        let (ext, len) =
+            ExtendedHeader::read(r.get_mut(), Header::MAJOR_VERSION4, trap)?;
+        pos += len;
+
+        // CRC is calculated from all the data after the extended header
+        // including padding.
+        if opts.verify_crc
+            && let Some(crc) = ext.as_ref().and_then(|a| a.crc)
+        {
+            let len = header.size.saturating_sub(pos);
+            check_crc(&mut r, len as u64, crc, trap)?;
+        }
 
-    let mut comments = vec![];
-    let mut ratings = vec![];
+        tag.extended_header = ext;
+    }
 
-    while !store.done() && pos + 10 < header.size {
-        let mut header: FrameHeader = r.get()?;
-        pos += header.size + 10;
+    let unsync = header.unsynchronization();
 
-        if header.compression()
-            || header.encryption()
-            || header.unsynchronization()
-        {
-            trap.error(Error::Unsupported(
-                "ID3v2.4 compression, encryption and unsynchronization.",
-            ))?;
-            r.seek_by(header.size as i64)?;
-            continue;
+    while !filter.done() && pos + 10 < header.size {
+        let fheader: FrameHeader = r.get()?;
+        if fheader.id == 0 {
+            break;
         }
 
-        let mut ads = 0;
-        if header.grouping() {
-            ads += 1;
-            header.size -= 1;
+        pos = pos.saturating_add(fheader.size + 10);
+        if pos > header.size {
+            return trap.error(Error::InvalidLength);
         }
-        if header.data_length_indicator() {
-            ads += 4;
-            header.size -= 4;
+
+        let id = fheader.id.to_be_bytes();
+        if !filter.keep(&id) {
+            r.seek_by(fheader.size as i64)?;
+            continue;
         }
-        r.seek_by(ads)?;
 
-        let hsize = header.size as usize;
+        let mut data = r.read_exact_owned(fheader.size as usize)?;
+        if fheader.encryption() {
+            trap.error(Error::Unsupported("ID3v2.4 encryption."))?;
+            tag.frames
+                .push(Id3v2Frame::unknown(&id, fheader.flags, data));
+            continue;
+        }
 
-        match header.id {
-            0 => break,
-            frame34::TIT2 if store.stores_data(DataType::Title) => {
-                if let Some(t) = r.witht(hsize, trap, read_string)? {
-                    store.set_title(t);
-                }
-            }
-            frame34::TALB if store.stores_data(DataType::Album) => {
-                if let Some(a) = r.witht(hsize, trap, read_string)? {
-                    store.set_album(a);
-                }
-            }
-            frame34::TRCK
-                if store.stores_data(DataType::Track)
-                    || store.stores_data(DataType::TrackCount) =>
-            {
-                if let Some((t, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_track(t);
-                    if let Some(c) = c {
-                        store.set_track_count(c);
-                    }
-                }
-            }
-            frame34::TPOS
-                if store.stores_data(DataType::Disc)
-                    || store.stores_data(DataType::DiscCount) =>
-            {
-                if let Some((d, c)) = r.witht(hsize, trap, read_num_of)? {
-                    store.set_disc(d);
-                    if let Some(c) = c {
-                        store.set_disc_count(c);
-                    }
-                }
-            }
-            frame34::TPE1 if store.stores_data(DataType::Artists) => {
-                if let Some(a) = r.witht(hsize, trap, read_string_list)? {
-                    store.set_artists(a);
-                }
-            }
-            frame34::TLEN if store.stores_data(DataType::Length) => {
-                if let Some(l) = r.witht(hsize, trap, read_length)? {
-                    store.set_length(l);
-                }
-            }
-            frame34::TCON if store.stores_data(DataType::Genres) => {
-                if let Some(g) = r.witht(hsize, trap, read_genres)? {
-                    store.set_genres(g);
-                }
-            }
-            frame34::TDRL
-                if store.stores_data(DataType::Year)
-                    || store.stores_data(DataType::Date)
-                    || store.stores_data(DataType::Time) =>
-            {
-                if let Some(dt) = r.witht(hsize, trap, read_year)? {
-                    store.set_date_time(dt);
-                }
-            }
-            frame34::COMM if store.stores_data(DataType::Comments) => {
-                comments.extend(r.witht(hsize, trap, read_comment)?);
-            }
-            frame34::APIC
-                if store.stores_data(DataType::Picture(
-                    PictureKind::all_id3(),
-                )) =>
-            {
-                read_picture34(&mut r, store, trap, header.size as i64)?;
-            }
-            frame34::TCOP if store.stores_data(DataType::Copyright) => {
-                if let Some(c) = r.witht(hsize, trap, read_string)? {
-                    store.set_copyright(c);
-                }
-            }
-            frame34::POPM if store.stores_data(DataType::Ratings) => {
-                ratings.extend(r.witht(hsize, trap, read_popularimeter)?);
-            }
-            frame34::TPE2 if store.stores_data(DataType::AlbumArtist) => {
-                if let Some(a) = r.witht(hsize, trap, read_string)? {
-                    store.set_album_artist(a);
-                }
-            }
-            _ => {
-                r.seek_by(header.size as i64)?;
+        // The additional data are in the order of the flags:
+        // group identifier, encryption method, data length indicator
+        let mut d = data.as_slice();
+        if fheader.grouping() {
+            d = d.get(1..).unwrap_or_default();
+        }
+        let mut data_length = None;
+        if fheader.data_length_indicator() {
+            if d.len() < 4 {
+                trap.error(Error::InvalidLength)?;
+                continue;
             }
+            data_length =
+                Some(parsers::syncsafe_be_u32(d[..4].try_into().unwrap()));
+            d = &d[4..];
         }
-    }
 
-    if !comments.is_empty() {
-        store.set_comments(comments);
-    }
-
-    if !ratings.is_empty() {
-        store.set_ratings(ratings);
-    }
-
-    Ok(())
-}
-
-fn read_genres(data: &[u8], trap: &impl Trap) -> Result<Vec<String>> {
-    let mut res = read_string_list(data, trap)?;
-    for g in &mut res {
-        if g == "RX" {
-            *g = "Remix".to_string();
-        } else if g == "CR" {
-            *g = "Cover".to_string();
-        } else if let Ok(gn) = g.parse()
-            && let Some(gs) = get_genre(gn)
-        {
-            *g = gs.to_string()
+        if unsync || fheader.unsynchronization() {
+            data = resync(d);
+        } else if d.len() != data.len() {
+            data = d.to_vec();
         }
-    }
-    Ok(res)
-}
 
-fn read_string_list(mut data: &[u8], trap: &impl Trap) -> Result<Vec<String>> {
-    let enc = data[0];
-    data = &data[1..];
-    let mut res = vec![];
+        // Size of the decompressed data is given by the data length
+        // indicator that is required for compressed frames.
+        if fheader.compression() {
+            let res = match data_length {
+                Some(len) => decompress(data.as_slice(), len as usize, opts),
+                None => Err(Error::InvalidLength),
+            };
+            let Some(dec) = trap.res(res)? else {
+                continue;
+            };
+            data = dec;
+        }
 
-    while !data.is_empty() {
-        let (l, s) = read_string_enc(enc, data, trap)?;
-        res.push(s);
-        data = &data[l..];
+        tag.frames.push(Id3v2Frame::parse(
+            &id,
+            fheader.flags,
+            data,
+            Header::MAJOR_VERSION4,
+            trap,
+        )?);
     }
 
-    Ok(res)
+    Ok(())
 }
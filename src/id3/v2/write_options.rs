@@ -0,0 +1,24 @@
+/// Options for writing ID3v2 tag.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Major version of the written tag. Supported values are `3` and `4`.
+    pub version: u8,
+    /// Keep frames from the original tag that are not written by this
+    /// library. Frames that are marked to be discarded when the tag is
+    /// altered are never kept.
+    pub keep_unknown: bool,
+    /// Size of padding that will be added to the tag if the file has to be
+    /// rewritten. Padding allows future modifications of the tag without
+    /// rewriting the whole file.
+    pub padding: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self {
+            version: 4,
+            keep_unknown: true,
+            padding: 1024,
+        }
+    }
+}
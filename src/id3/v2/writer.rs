@@ -0,0 +1,790 @@
+use std::{
+    fs::File,
+    io::{BufReader, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use crate::{
+    DataType, Error, PictureKind, Rating, Result, TagSource, TagSourceExt,
+    TagType,
+    file_edit::{self, Edit},
+    id3::v2::{
+        WriteOptions, frame34, from_read, header::Header, read_string_list,
+        unsync::resync, v2_3, v2_4,
+    },
+    merged_source::{Merged, SourceData},
+    parsers,
+    trap::Skip,
+};
+
+// Implementation is based on:
+// - https://id3.org/id3v2.3.0
+// - https://id3.org/id3v2.4.0-structure
+// - https://id3.org/id3v2.4.0-frames
+
+/// Maximum size of tag that can be represented by the syncsafe integer.
+const MAX_SIZE: usize = 0x0fff_ffff;
+
+/// Data types of the date frames.
+const DATE: &[DataType] = &[DataType::Year, DataType::Date, DataType::Time];
+
+/// Frames that are produced from [`TagSource`] with the data types that they
+/// contain. Frame of the original tag is replaced if the source replaces any
+/// of its data types, otherwise it is kept as it is.
+const KNOWN_FRAMES: &[(u32, &[DataType])] = &[
+    (frame34::APIC, &[DataType::Picture(PictureKind::all())]),
+    (frame34::TALB, &[DataType::Album]),
+    (frame34::TCON, &[DataType::Genres]),
+    (frame34::TDAT, DATE),
+    (frame34::TDRC, DATE),
+    (frame34::TDRL, DATE),
+    (frame34::TIT2, &[DataType::Title]),
+    (frame34::TIME, DATE),
+    (frame34::TLEN, &[DataType::Length]),
+    (frame34::TPE1, &[DataType::Artists]),
+    (frame34::TPE2, &[DataType::AlbumArtist]),
+    (frame34::TPOS, &[DataType::Disc, DataType::DiscCount]),
+    (frame34::TRCK, &[DataType::Track, DataType::TrackCount]),
+    (frame34::TYER, DATE),
+    (frame34::COMM, &[DataType::Comments]),
+    (frame34::TCOP, &[DataType::Copyright]),
+    (frame34::POPM, &[DataType::Ratings]),
+];
+
+/// Frames that exist only in ID3v2.3.
+const V3_ONLY_FRAMES: &[&[u8; 4]] = &[
+    b"EQUA", b"IPLS", b"RVAD", b"TDAT", b"TIME", b"TORY", b"TRDA", b"TSIZ",
+    b"TYER",
+];
+
+/// Frames that exist only in ID3v2.4.
+const V4_ONLY_FRAMES: &[&[u8; 4]] = &[
+    b"ASPI", b"EQU2", b"RVA2", b"SEEK", b"SIGN", b"TDEN", b"TDOR", b"TDRC",
+    b"TDRL", b"TDTG", b"TIPL", b"TMCL", b"TMOO", b"TPRO", b"TSOA", b"TSOP",
+    b"TSOT", b"TSST",
+];
+
+/// Frames other than text frames that start with text encoding.
+const ENCODED_FRAMES: &[&[u8; 4]] = &[
+    b"APIC", b"COMM", b"COMR", b"GEOB", b"OWNE", b"SYLT", b"USER", b"USLT",
+    b"WXXX",
+];
+
+/// Write ID3v2 tag at the start of the given file. Existing ID3v2 tag at the
+/// start of the file is replaced. Frames with data that the source doesn't
+/// provide are kept from the existing tag.
+///
+/// If the new tag fits into the space of the old tag, the file is modified in
+/// place and the remaining space is filled with padding. Otherwise the whole
+/// file is rewritten through temporary file.
+pub fn write_file(
+    f: impl AsRef<Path>,
+    src: &impl TagSource,
+    opts: &WriteOptions,
+) -> Result<()> {
+    let f = f.as_ref();
+    check_version(opts.version)?;
+
+    let old = read_old(BufReader::new(File::open(f)?))?;
+    let mut values = SourceData::default();
+    if old.is_some() {
+        // Values that cannot be read are not written back, but the frames
+        // are kept.
+        _ = from_read(BufReader::new(File::open(f)?), &mut values, &Skip);
+    }
+
+    // If the tag is converted from other version, the known frames are all
+    // written from the old data. Their encodings and dates may not be
+    // representable in the new version.
+    let old_version = old.as_ref().map(|a| a.0.major_version);
+    let write = |types: &[DataType]| {
+        old_version != Some(opts.version)
+            || types.iter().any(|t| src.replaces(*t))
+    };
+
+    let mut frames = Frames::new(opts.version);
+    frames.add_source(&Merged::new(src, &values), write);
+    let mut old_size = 0;
+    if let Some((header, data)) = old {
+        old_size = header.tag_size() as usize;
+        frames.data.extend(keep_frames(&data, &header, opts, write));
+    }
+
+    let len = frames.data.len() + Header::SIZE;
+    let padding = if len <= old_size {
+        old_size - len
+    } else {
+        opts.padding
+    };
+
+    let tag = frames.into_tag(padding)?;
+    file_edit::apply(f, &[Edit::new(0..old_size as u64, tag)])
+}
+
+/// Serialize data from the source into ID3v2 tag. The tag will have padding
+/// given by the options.
+pub fn to_bytes(src: &impl TagSource, opts: &WriteOptions) -> Result<Vec<u8>> {
+    check_version(opts.version)?;
+    let mut frames = Frames::new(opts.version);
+    frames.add_source(src, |_| true);
+    frames.into_tag(opts.padding)
+}
+
+/// Remove ID3v2 tag from the start of the file. Returns the type of the
+/// removed tag or `None` if the file doesn't start with ID3v2 tag.
+pub fn remove_file(f: impl AsRef<Path>) -> Result<Option<TagType>> {
+    let f = f.as_ref();
+    let mut r = File::open(f)?;
+    let mut head = [0; Header::SIZE];
+    match r.read_exact(&mut head) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        res => res?,
+    }
+
+    if &head[..3] != b"ID3" {
+        return Ok(None);
+    }
+
+    let header = Header::from_bytes(head[3..].try_into().unwrap());
+    let len = r.seek(SeekFrom::End(0))?;
+    let size = header.tag_size().min(len);
+    drop(r);
+
+    file_edit::apply(f, &[Edit::new(0..size, vec![])])?;
+    Ok(Some(TagType::Id3v2(header.major_version)))
+}
+
+fn check_version(version: u8) -> Result<()> {
+    if matches!(version, Header::MAJOR_VERSION3 | Header::MAJOR_VERSION4) {
+        Ok(())
+    } else {
+        Err(Error::Unsupported(
+            "Writing ID3v2 other version than 3 and 4.",
+        ))
+    }
+}
+
+/// Read the tag at the start of the stream. Returns the header and the
+/// resynchronized data of the tag or `None` if there is no tag.
+fn read_old(mut r: impl Read) -> Result<Option<(Header, Vec<u8>)>> {
+    let mut head = [0; Header::SIZE];
+    match r.read_exact(&mut head) {
+        Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(None),
+        res => res?,
+    }
+
+    if &head[..3] != b"ID3" {
+        return Ok(None);
+    }
+
+    let header = Header::from_bytes(head[3..].try_into().unwrap());
+    let mut data = vec![0; header.size as usize];
+    r.read_exact(&mut data)?;
+
+    if header.major_version == Header::MAJOR_VERSION3
+        && header.unsynchronization()
+    {
+        data = resync(&data);
+    }
+
+    Ok(Some((header, data)))
+}
+
+/// Get frames from the old tag that should be kept, converted to the version
+/// given by the options. Known frames are kept only if they are not written
+/// from the source.
+fn keep_frames(
+    mut d: &[u8],
+    header: &Header,
+    opts: &WriteOptions,
+    write: impl Fn(&[DataType]) -> bool,
+) -> Vec<u8> {
+    let src = header.major_version;
+    let version = opts.version;
+    // ID3v2.2 frames cannot be converted.
+    if !matches!(src, Header::MAJOR_VERSION3 | Header::MAJOR_VERSION4) {
+        return vec![];
+    }
+
+    if header.extended_header34() && d.len() >= 4 {
+        let len = if src == Header::MAJOR_VERSION3 {
+            u32::from_be_bytes(d[..4].try_into().unwrap()) as usize + 4
+        } else {
+            parsers::syncsafe_be_u32(d[..4].try_into().unwrap()) as usize
+        };
+        d = &d[len.min(d.len())..];
+    }
+
+    let mut res = vec![];
+    while d.len() >= 10 && d[0] != 0 {
+        let id = u32::from_be_bytes(d[..4].try_into().unwrap());
+        let size = if src == Header::MAJOR_VERSION3 {
+            u32::from_be_bytes(d[4..8].try_into().unwrap())
+        } else {
+            parsers::syncsafe_be_u32(d[4..8].try_into().unwrap())
+        } as usize;
+        let flags = u16::from_be_bytes(d[8..10].try_into().unwrap());
+
+        if d.len() - 10 < size {
+            break;
+        }
+        let (frame, rest) = d.split_at(10 + size);
+        d = rest;
+
+        let known = known_types(id);
+        match known {
+            Some(t) if write(t) => continue,
+            None if !opts.keep_unknown => continue,
+            _ => {}
+        }
+
+        if src == version {
+            let tag_alter = if src == Header::MAJOR_VERSION3 {
+                v2_3::FrameHeader::TAG_ALTER_PRESERVATION
+            } else {
+                v2_4::FrameHeader::TAG_ALTER_PRESERVATION
+            };
+            if known.is_some() || flags & tag_alter == 0 {
+                res.extend_from_slice(frame);
+            }
+            continue;
+        }
+
+        // The frame is converted between versions.
+        let flags = if src == Header::MAJOR_VERSION3 {
+            v2_3::FrameHeader::status_to_v4(flags)
+        } else {
+            v2_4::FrameHeader::status_to_v3(flags)
+        };
+        if let Some(flags) = flags
+            && let Some((id, body)) = convert_frame(id, &frame[10..], version)
+        {
+            let mut frames = Frames::new(version);
+            frames.frame_with_flags(id, flags, &body);
+            res.extend(frames.data);
+        }
+    }
+
+    res
+}
+
+/// Get the data types of frame produced from [`TagSource`].
+fn known_types(id: u32) -> Option<&'static [DataType]> {
+    KNOWN_FRAMES.iter().find(|a| a.0 == id).map(|a| a.1)
+}
+
+/// Convert frame from the other version to the given version. Returns
+/// [`None`] if the frame cannot be represented in the given version.
+fn convert_frame(id: u32, body: &[u8], version: u8) -> Option<(u32, Vec<u8>)> {
+    let name = id.to_be_bytes();
+
+    if version == Header::MAJOR_VERSION4 {
+        // Encodings of ID3v2.3 are valid in ID3v2.4.
+        return match id {
+            // TORY contains only year which is valid timestamp of TDOR.
+            frame34::TORY => Some((frame34::TDOR, body.to_vec())),
+            _ if V3_ONLY_FRAMES.contains(&&name) => None,
+            _ => Some((id, body.to_vec())),
+        };
+    }
+
+    if V4_ONLY_FRAMES.contains(&&name) {
+        return None;
+    }
+
+    if name[0] == b'T' {
+        let (&enc, d) = body.split_first()?;
+        return Some((id, text_to_v3(id, enc, d)?));
+    }
+
+    // UTF-16BE and UTF-8 are not supported in ID3v2.3.
+    if ENCODED_FRAMES.contains(&&name)
+        && body.first().is_none_or(|&e| e > Frames::UTF16_BOM)
+    {
+        return None;
+    }
+
+    Some((id, body.to_vec()))
+}
+
+/// Convert body of ID3v2.4 text frame to ID3v2.3. The text is reencoded and
+/// multiple values are joined with `/`.
+fn text_to_v3(id: u32, enc: u8, d: &[u8]) -> Option<Vec<u8>> {
+    let mut values = read_string_list(enc, d, &Skip).ok()?;
+    let desc = if id == frame34::TXXX {
+        Some(values.first()?.clone())
+    } else {
+        None
+    };
+    let value = values.split_off(desc.is_some() as usize).join("/");
+
+    let frames = Frames::new(Header::MAJOR_VERSION3);
+    let enc = frames.encoding(&[desc.as_deref().unwrap_or_default(), &value]);
+    let mut body = vec![enc];
+    if let Some(desc) = desc {
+        push_str_nt(&mut body, enc, &desc);
+    }
+    push_str(&mut body, enc, &value);
+    Some(body)
+}
+
+struct Frames {
+    version: u8,
+    data: Vec<u8>,
+}
+
+impl Frames {
+    const LATIN1: u8 = 0;
+    const UTF16_BOM: u8 = 1;
+    const UTF8: u8 = 3;
+
+    fn new(version: u8) -> Self {
+        Self {
+            version,
+            data: vec![],
+        }
+    }
+
+    /// Add frames from the source. Only the frames with data types for which
+    /// `write` returns `true` are added.
+    fn add_source(
+        &mut self,
+        src: &impl TagSource,
+        write: impl Fn(&[DataType]) -> bool,
+    ) {
+        let w = |id| known_types(id).is_some_and(&write);
+        if w(frame34::TIT2) {
+            self.text(frame34::TIT2, src.title());
+        }
+        if w(frame34::TPE1) {
+            self.texts(frame34::TPE1, src.artists());
+        }
+        if w(frame34::TALB) {
+            self.text(frame34::TALB, src.album());
+        }
+        if w(frame34::TPE2) {
+            self.text(frame34::TPE2, src.album_artist());
+        }
+        if w(frame34::TCON) {
+            self.texts(frame34::TCON, src.genres());
+        }
+        if w(frame34::TRCK) {
+            self.text(
+                frame34::TRCK,
+                src.track().map(|t| num_of(t, src.track_count())).as_deref(),
+            );
+        }
+        if w(frame34::TPOS) {
+            self.text(
+                frame34::TPOS,
+                src.disc().map(|d| num_of(d, src.disc_count())).as_deref(),
+            );
+        }
+        if w(frame34::TDRC) {
+            self.date_time(src);
+        }
+        if w(frame34::TLEN) {
+            self.text(
+                frame34::TLEN,
+                src.length().map(|l| l.as_millis().to_string()).as_deref(),
+            );
+        }
+        if w(frame34::TCOP) {
+            self.text(frame34::TCOP, src.copyright());
+        }
+
+        let comments = if w(frame34::COMM) {
+            src.comments()
+        } else {
+            &[]
+        };
+        for c in comments {
+            let desc = c.desciption.as_deref().unwrap_or_default();
+            let enc = self.encoding(&[desc, &c.value]);
+            let lang = match &c.language {
+                Some(l) if l.len() == 3 && l.is_ascii() => l.as_bytes(),
+                _ => b"XXX",
+            };
+
+            let mut body = vec![enc];
+            body.extend_from_slice(lang);
+            push_str_nt(&mut body, enc, desc);
+            push_str(&mut body, enc, &c.value);
+            self.frame(frame34::COMM, &body);
+        }
+
+        let pictures = if w(frame34::APIC) {
+            src.pictures()
+        } else {
+            &[]
+        };
+        for p in pictures {
+            let desc = p.description.as_deref().unwrap_or_default();
+            let enc = self.encoding(&[desc]);
+            let mime = if p.is_uri {
+                "-->"
+            } else {
+                p.mime.as_deref().unwrap_or("image/")
+            };
+
+            let mut body = vec![enc];
+            push_str_nt(&mut body, Self::LATIN1, mime);
+            body.push(p.kind.to_id3());
+            push_str_nt(&mut body, enc, desc);
+            body.extend_from_slice(&p.data);
+            self.frame(frame34::APIC, &body);
+        }
+
+        let ratings = if w(frame34::POPM) { src.ratings() } else { &[] };
+        for r in ratings {
+            let Rating::Popularimeter(p) = r else {
+                continue;
+            };
+
+            let mut body = vec![];
+            push_str_nt(&mut body, Self::LATIN1, &p.email);
+            body.push(p.rating);
+            let cnt = p.play_counter.to_be_bytes();
+            let skip = (p.play_counter.leading_zeros() / 8).min(4) as usize;
+            body.extend_from_slice(&cnt[skip..]);
+            self.frame(frame34::POPM, &body);
+        }
+    }
+
+    fn date_time(&mut self, src: &impl TagSource) {
+        let Some(year) = src.year() else {
+            return;
+        };
+
+        let date = src.date();
+        let time = src.time().map(|t| t.as_secs());
+
+        if self.version == Header::MAJOR_VERSION3 {
+            self.text(frame34::TYER, Some(&format!("{year:04}")));
+            if let Some((m, d)) = date {
+                self.text(frame34::TDAT, Some(&format!("{d:02}{m:02}")));
+            }
+            if let Some(t) = time {
+                let time = format!("{:02}{:02}", t / 3600, t / 60 % 60);
+                self.text(frame34::TIME, Some(&time));
+            }
+            return;
+        }
+
+        self.text(frame34::TDRC, src.date_time_string().as_deref());
+    }
+
+    fn text(&mut self, id: u32, value: Option<&str>) {
+        if let Some(v) = value {
+            self.texts(id, &[v]);
+        }
+    }
+
+    /// Write text frame with multiple values. ID3v2.4 separates the values
+    /// with null, ID3v2.3 doesn't support multiple values so they are joined
+    /// with `/`.
+    fn texts(&mut self, id: u32, values: &[impl AsRef<str>]) {
+        let values: Vec<_> = values
+            .iter()
+            .map(|a| a.as_ref())
+            .filter(|a| !a.is_empty())
+            .collect();
+        if values.is_empty() {
+            return;
+        }
+
+        let enc = self.encoding(&values);
+        let mut body = vec![enc];
+        if self.version == Header::MAJOR_VERSION3 {
+            push_str(&mut body, enc, &values.join("/"));
+        } else {
+            push_str(&mut body, enc, values[0]);
+            for v in &values[1..] {
+                push_null(&mut body, enc);
+                push_str(&mut body, enc, v);
+            }
+        }
+
+        self.frame(id, &body);
+    }
+
+    /// Choose encoding that can represent all the given strings.
+    fn encoding(&self, s: &[&str]) -> u8 {
+        if self.version == Header::MAJOR_VERSION4 {
+            Self::UTF8
+        } else if s.iter().flat_map(|a| a.chars()).all(|c| c <= '\u{FF}') {
+            Self::LATIN1
+        } else {
+            Self::UTF16_BOM
+        }
+    }
+
+    fn frame(&mut self, id: u32, body: &[u8]) {
+        self.frame_with_flags(id, 0, body);
+    }
+
+    fn frame_with_flags(&mut self, id: u32, flags: u16, body: &[u8]) {
+        self.data.extend(id.to_be_bytes());
+        if self.version == Header::MAJOR_VERSION3 {
+            self.data.extend((body.len() as u32).to_be_bytes());
+        } else {
+            self.data.extend(syncsafe(body.len()));
+        }
+        self.data.extend(flags.to_be_bytes());
+        self.data.extend_from_slice(body);
+    }
+
+    fn into_tag(self, padding: usize) -> Result<Vec<u8>> {
+        let size = self.data.len() + padding;
+        if size > MAX_SIZE {
+            return Err(Error::InvalidLength);
+        }
+
+        let mut res = Vec::with_capacity(size + Header::SIZE);
+        res.extend(b"ID3");
+        res.extend([self.version, 0, 0]);
+        res.extend(syncsafe(size));
+        res.extend(self.data);
+        res.resize(size + Header::SIZE, 0);
+        Ok(res)
+    }
+}
+
+fn num_of(n: u32, cnt: Option<u32>) -> String {
+    match cnt {
+        Some(c) => format!("{n}/{c}"),
+        None => n.to_string(),
+    }
+}
+
+fn syncsafe(n: usize) -> [u8; 4] {
+    [
+        (n >> 21 & 0x7F) as u8,
+        (n >> 14 & 0x7F) as u8,
+        (n >> 7 & 0x7F) as u8,
+        (n & 0x7F) as u8,
+    ]
+}
+
+fn push_str(buf: &mut Vec<u8>, enc: u8, s: &str) {
+    match enc {
+        Frames::LATIN1 => {
+            buf.extend(s.chars().map(|c| u8::try_from(c).unwrap_or(b'?')))
+        }
+        Frames::UTF16_BOM => {
+            buf.extend([0xFF, 0xFE]);
+            buf.extend(s.encode_utf16().flat_map(u16::to_le_bytes));
+        }
+        _ => buf.extend_from_slice(s.as_bytes()),
+    }
+}
+
+fn push_str_nt(buf: &mut Vec<u8>, enc: u8, s: &str) {
+    push_str(buf, enc, s);
+    push_null(buf, enc);
+}
+
+fn push_null(buf: &mut Vec<u8>, enc: u8) {
+    if enc == Frames::UTF16_BOM {
+        buf.extend([0, 0]);
+    } else {
+        buf.push(0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, process};
+
+    use super::*;
+    use crate::{Comment, CustomField, CustomValue, Strict, TagStore, id3};
+
+    const AUDIO: &[u8] = b"\xFF\xFBaudio frames";
+
+    struct Src(&'static str);
+
+    impl TagSource for Src {
+        fn title(&self) -> Option<&str> {
+            Some(self.0)
+        }
+    }
+
+    /// Source that provides only the track number and removes artists.
+    struct TrackSrc;
+
+    impl TagSource for TrackSrc {
+        fn track(&self) -> Option<u32> {
+            Some(5)
+        }
+
+        fn removes(&self, typ: DataType) -> bool {
+            typ == DataType::Artists
+        }
+    }
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        artists: Vec<String>,
+        track: Option<(u32, Option<u32>)>,
+        comments: Vec<String>,
+        artist_sort: Option<String>,
+        custom: Vec<(String, CustomValue)>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Title
+                    | DataType::Artists
+                    | DataType::Track
+                    | DataType::TrackCount
+                    | DataType::Comments
+                    | DataType::ArtistSort
+                    | DataType::CustomFields
+            )
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn set_artists(&mut self, artists: Vec<String>) {
+            self.artists = artists;
+        }
+
+        fn set_track(&mut self, track: u32) {
+            self.track = Some((track, None));
+        }
+
+        fn set_track_count(&mut self, cnt: u32) {
+            if let Some(t) = &mut self.track {
+                t.1 = Some(cnt);
+            }
+        }
+
+        fn set_comments(&mut self, comments: Vec<Comment>) {
+            self.comments = comments.into_iter().map(|a| a.value).collect();
+        }
+
+        fn set_artist_sort(&mut self, artist: String) {
+            self.artist_sort = Some(artist);
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push((field.key, field.value));
+        }
+    }
+
+    /// Create file with ID3v2.4 tag with the given padding.
+    fn file(padding: usize) -> Vec<u8> {
+        let mut frames = Frames::new(Header::MAJOR_VERSION4);
+        frames.text(frame34::TIT2, Some("Old"));
+        frames.text(frame34::TPE1, Some("Artist"));
+        frames.text(frame34::TRCK, Some("3/12"));
+        let mut comm = vec![Frames::UTF8];
+        comm.extend(b"eng");
+        push_str_nt(&mut comm, Frames::UTF8, "");
+        push_str(&mut comm, Frames::UTF8, "Comment");
+        frames.frame(frame34::COMM, &comm);
+        frames.text(u32::from_be_bytes(*b"TSOP"), Some("Sort"));
+        let mut txxx = vec![Frames::UTF8];
+        push_str_nt(&mut txxx, Frames::UTF8, "KEEP");
+        push_str(&mut txxx, Frames::UTF8, "vålue");
+        frames.frame(frame34::TXXX, &txxx);
+
+        let mut res = frames.into_tag(padding).unwrap();
+        res.extend(AUDIO);
+        res
+    }
+
+    /// Write the source to copy of the file and read it back. Returns the new
+    /// file and the read data.
+    fn round_trip(
+        name: &str,
+        data: &[u8],
+        src: &impl TagSource,
+        version: u8,
+    ) -> (Vec<u8>, Store) {
+        let path = env::temp_dir()
+            .join(format!("ratag-id3-{}-{name}.mp3", process::id()));
+        fs::write(&path, data).unwrap();
+        let opts = WriteOptions {
+            version,
+            ..Default::default()
+        };
+        let res = write_file(&path, src, &opts);
+        let mut store = Store::default();
+        let read = id3::v2::from_file(&path, &mut store, &Strict);
+        let out = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        res.unwrap();
+        read.unwrap();
+        (out.unwrap(), store)
+    }
+
+    const LONG: &str = "Title that is much longer than the original title";
+
+    #[test]
+    fn tag_fits_into_padding() {
+        let data = file(100);
+        let (out, store) = round_trip("fits", &data, &Src(LONG), 4);
+        assert_eq!(out.len(), data.len());
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.artist_sort.as_deref(), Some("Sort"));
+        assert_eq!(
+            store.custom,
+            [("KEEP".to_string(), CustomValue::Text("vålue".to_string()))]
+        );
+    }
+
+    #[test]
+    fn tag_grows() {
+        let data = file(0);
+        let (out, store) = round_trip("grows", &data, &Src(LONG), 4);
+        assert!(out.len() > data.len());
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.artist_sort.as_deref(), Some("Sort"));
+    }
+
+    #[test]
+    fn frames_are_converted_to_v3() {
+        let data = file(100);
+        let (out, store) = round_trip("v3", &data, &Src(LONG), 3);
+        assert_eq!(out[3], Header::MAJOR_VERSION3);
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        // TSOP doesn't exist in ID3v2.3.
+        assert_eq!(store.artist_sort, None);
+        // UTF-8 is reencoded.
+        assert_eq!(
+            store.custom,
+            [("KEEP".to_string(), CustomValue::Text("vålue".to_string()))]
+        );
+    }
+
+    #[test]
+    fn not_provided_frames_are_kept() {
+        let data = file(100);
+        let (out, store) = round_trip("keep", &data, &Src(LONG), 4);
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some(LONG));
+        assert_eq!(store.artists, ["Artist"]);
+        assert_eq!(store.track, Some((3, Some(12))));
+        assert_eq!(store.comments, ["Comment"]);
+    }
+
+    #[test]
+    fn frames_are_merged_and_removed() {
+        let data = file(100);
+        let (out, store) = round_trip("merge", &data, &TrackSrc, 3);
+        assert!(out.ends_with(AUDIO));
+        assert_eq!(store.title.as_deref(), Some("Old"));
+        assert!(store.artists.is_empty());
+        assert_eq!(store.track, Some((5, Some(12))));
+        assert_eq!(store.comments, ["Comment"]);
+    }
+}
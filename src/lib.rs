@@ -1,25 +1,39 @@
 #![doc = include_str!("../README.md")]
 // #![warn(missing_docs)]
 
+/// Module for reading tags from aiff files.
+pub mod aiff;
+/// Module for reading APE tags.
+pub mod ape;
 /// Module for reading tags from asf files.
 pub mod asf;
 mod bread;
 mod containers;
 mod data_type;
 mod err;
-/// Module for reading metadata from flac files.
+mod file_edit;
+/// Module for reading and writing metadata of flac files.
 pub mod flac;
-/// Module for reading ID3v1 and ID3v2 tags.
+/// Module for reading ID3v1 and ID3v2 tags and writing ID3v2 tags.
 pub mod id3;
-/// Module for reading tags for mp4 files.
+mod merged_source;
+/// Module for reading and writing tags of mp4 files.
 pub mod mp4;
+/// Module for reading length and audio properties of MPEG audio streams.
+pub mod mpeg;
+mod musicbrainz;
+/// Module for reading tags from ogg files.
+pub mod ogg;
 mod parsers;
+mod replay_gain;
 /// Module for reading tags from riff files.
 pub mod riff;
 /// Tagging containers.
 pub mod tag;
 mod tag_read;
+mod tag_source;
 mod tag_store;
+mod tag_write;
 /// Module for managing how to handle errors.
 pub mod trap;
 /// Module for parsing vorbis comments.
@@ -32,17 +46,26 @@ use std::{
 };
 
 use crate::{
-    asf::Asf, bread::Bread, flac::Flac, id3::Id3, mp4::Mp4, riff::Riff,
-    trap::*,
+    aiff::Aiff, ape::Ape, asf::Asf, bread::Bread, flac::Flac, id3::Id3,
+    mp4::Mp4, ogg::Ogg, riff::Riff, trap::*,
 };
 
+pub use uuid::Uuid;
+
 pub use self::{
-    containers::*, data_type::*, err::*, tag_read::*, tag_store::*,
+    containers::*, data_type::*, err::*, tag_read::*, tag_source::*,
+    tag_store::*, tag_write::*,
 };
 
 macro_rules! all_tags {
     () => {
-        [&Id3, &Flac, &Mp4, &Asf, &Riff]
+        [&Id3, &Flac, &Mp4, &Asf, &Riff, &Aiff, &Ogg, &Ape]
+    };
+}
+
+macro_rules! all_writers {
+    () => {
+        [&Flac, &Mp4, &Id3]
     };
 }
 
@@ -141,9 +164,118 @@ pub fn read_tag_from_file<S: TagStore, T: Trap>(
     read_any_tag_from_file(tags, f, store, trap)
 }
 
+/// Writes the data to file with the first tag format that succeeds.
+///
+/// This prioritizes tag formats based on the extension of the file.
+///
+/// Returns data types that are provided by `src` but couldn't be represented
+/// by the used tag format.
+///
+/// # Errors
+/// - If writing fails for one of the formats.
+/// - [`Error::NoTag`] if the file is not supported by any of the formats.
+pub fn write_any_tag_to_file<
+    'a,
+    S: 'a,
+    I: IntoIterator<Item = &'a dyn TagWrite<S>>,
+>(
+    tags: I,
+    path: impl AsRef<Path>,
+    src: &S,
+) -> Result<Vec<DataType>> {
+    let path = path.as_ref();
+    let ext = path.extension();
+
+    let mut primary = vec![];
+    let mut secondary = vec![];
+
+    for t in tags {
+        if ext.is_some_and(|ext| t.extensions().iter().any(|e| *e == ext)) {
+            primary.push(t);
+        } else {
+            secondary.push(t);
+        }
+    }
+
+    for t in primary.into_iter().chain(secondary) {
+        match t.write(path, src) {
+            Err(Error::NoTag) => continue,
+            res => return res,
+        }
+    }
+    Err(Error::NoTag)
+}
+
+/// Writes the data to file with any of the tag formats supported by this
+/// crate. The format is chosen based on the file extension and content.
+///
+/// Returns data types that are provided by `src` but couldn't be represented
+/// by the used tag format.
+///
+/// # Errors
+/// - Writing tag fails.
+/// - [`Error::NoTag`] if the file is not supported by any of the formats.
+pub fn write_tag_to_file<S: TagSource>(
+    path: impl AsRef<Path>,
+    src: &S,
+) -> Result<Vec<DataType>> {
+    let tags: [&dyn TagWrite<S>; _] = all_writers!();
+    write_any_tag_to_file(tags, path, src)
+}
+
+/// Removes tags of the given types from the file. Versions within the tag
+/// types are ignored, so e.g. [`TagType::Id3v2`] removes ID3v2 tag of any
+/// version.
+///
+/// Supported tag types are:
+/// - [`TagType::Id3v1`]: the trailing ID3v1 tag including the ID3v1.2
+///   extension.
+/// - [`TagType::Id3v2`]: the leading ID3v2 tag.
+/// - [`TagType::Ape`]: APE tag at the end of the file.
+/// - [`TagType::Riff`]: `LIST/INFO` chunks of riff files.
+/// - [`TagType::VorbisComment`]: vorbis comment blocks in flac files.
+/// - [`TagType::Flac`]: picture blocks in flac files.
+///
+/// Other tag types are ignored. Flac blocks are turned into padding, the
+/// other tags are removed from the file.
+///
+/// Returns the types of the tags that were removed.
+///
+/// # Errors
+/// - Reading or modifying the file fails.
+pub fn remove_tags_from_file(
+    path: impl AsRef<Path>,
+    types: &[TagType],
+) -> Result<Vec<TagType>> {
+    let path = path.as_ref();
+    let has = |f: fn(&TagType) -> bool| types.iter().any(f);
+    let mut res = vec![];
+
+    // Tags at the end of the file are removed first so that they can be
+    // removed just by truncating the file.
+    if has(|a| matches!(a, TagType::Id3v1(_))) {
+        res.extend(id3::v1::remove_file(path)?);
+    }
+    if has(|a| matches!(a, TagType::Ape(_))) {
+        res.extend(ape::remove_file(path)?);
+    }
+    if has(|a| matches!(a, TagType::Riff(_))) {
+        res.extend(riff::remove_info(path)?);
+    }
+    if has(|a| matches!(a, TagType::Flac | TagType::VorbisComment)) {
+        res.extend(flac::remove_file(path, types)?);
+    }
+    if has(|a| matches!(a, TagType::Id3v2(_))) {
+        res.extend(id3::v2::remove_file(path)?);
+    }
+
+    Ok(res)
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
+    #[allow(clippy::assertions_on_constants)]
     fn test0() {
         assert!(true);
     }
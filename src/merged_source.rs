@@ -0,0 +1,227 @@
+use std::time::Duration;
+
+use crate::{
+    Comment, DataType, Picture, PictureKind, Rating, TagSource, TagSourceExt,
+    TagStore,
+};
+
+/// Data of the existing tag that can be written back through [`TagSource`].
+#[derive(Debug, Default)]
+pub(crate) struct SourceData {
+    title: Option<String>,
+    album: Option<String>,
+    artists: Vec<String>,
+    album_artist: Option<String>,
+    genres: Vec<String>,
+    track: Option<u32>,
+    track_count: Option<u32>,
+    year: Option<i32>,
+    date: Option<(u8, u8)>,
+    time: Option<Duration>,
+    disc: Option<u32>,
+    disc_count: Option<u32>,
+    length: Option<Duration>,
+    comments: Vec<Comment>,
+    pictures: Vec<Picture>,
+    copyright: Option<String>,
+    ratings: Vec<Rating>,
+}
+
+/// Source that takes the data from `src` if it provides or removes it and
+/// from the existing tag otherwise.
+pub(crate) struct Merged<'a, S> {
+    src: &'a S,
+    old: &'a SourceData,
+}
+
+impl<'a, S: TagSource> Merged<'a, S> {
+    pub fn new(src: &'a S, old: &'a SourceData) -> Self {
+        Self { src, old }
+    }
+
+    fn pick<T>(&self, typ: DataType, old: T, src: T) -> T {
+        if self.src.replaces(typ) { src } else { old }
+    }
+}
+
+impl TagStore for SourceData {
+    fn stores_data(&self, typ: DataType) -> bool {
+        use DataType::*;
+        matches!(
+            typ,
+            Title
+                | Album
+                | Artists
+                | AlbumArtist
+                | Genres
+                | Track
+                | TrackCount
+                | Year
+                | Date
+                | Time
+                | Disc
+                | DiscCount
+                | Length
+                | Comments
+                | Picture(_)
+                | Copyright
+                | Ratings
+        )
+    }
+
+    fn set_title(&mut self, title: String) {
+        self.title = Some(title);
+    }
+
+    fn set_album(&mut self, album: String) {
+        self.album = Some(album);
+    }
+
+    fn set_artists(&mut self, artists: Vec<String>) {
+        self.artists = artists;
+    }
+
+    fn set_album_artist(&mut self, artist: String) {
+        self.album_artist = Some(artist);
+    }
+
+    fn set_genres(&mut self, genres: Vec<String>) {
+        self.genres = genres;
+    }
+
+    fn set_track(&mut self, track: u32) {
+        self.track = Some(track);
+    }
+
+    fn set_track_count(&mut self, cnt: u32) {
+        self.track_count = Some(cnt);
+    }
+
+    fn set_year(&mut self, year: i32) {
+        self.year = Some(year);
+    }
+
+    fn set_date(&mut self, month: u8, day: u8) {
+        self.date = Some((month, day));
+    }
+
+    fn set_time(&mut self, time: Duration) {
+        self.time = Some(time);
+    }
+
+    fn set_disc(&mut self, disc: u32) {
+        self.disc = Some(disc);
+    }
+
+    fn set_disc_count(&mut self, cnt: u32) {
+        self.disc_count = Some(cnt);
+    }
+
+    fn set_length(&mut self, length: Duration) {
+        self.length = Some(length);
+    }
+
+    fn set_comments(&mut self, comments: Vec<Comment>) {
+        self.comments = comments;
+    }
+
+    fn add_picture(&mut self, picture: Picture) {
+        self.pictures.push(picture);
+    }
+
+    fn set_copyright(&mut self, copyright: String) {
+        self.copyright = Some(copyright);
+    }
+
+    fn set_ratings(&mut self, ratings: Vec<Rating>) {
+        self.ratings = ratings;
+    }
+}
+
+impl<S: TagSource> TagSource for Merged<'_, S> {
+    fn title(&self) -> Option<&str> {
+        let src = self.src.title();
+        self.pick(DataType::Title, self.old.title.as_deref(), src)
+    }
+
+    fn album(&self) -> Option<&str> {
+        let src = self.src.album();
+        self.pick(DataType::Album, self.old.album.as_deref(), src)
+    }
+
+    fn artists(&self) -> &[String] {
+        let src = self.src.artists();
+        self.pick(DataType::Artists, self.old.artists.as_slice(), src)
+    }
+
+    fn album_artist(&self) -> Option<&str> {
+        let src = self.src.album_artist();
+        let old = self.old.album_artist.as_deref();
+        self.pick(DataType::AlbumArtist, old, src)
+    }
+
+    fn genres(&self) -> &[String] {
+        let src = self.src.genres();
+        self.pick(DataType::Genres, self.old.genres.as_slice(), src)
+    }
+
+    fn track(&self) -> Option<u32> {
+        self.pick(DataType::Track, self.old.track, self.src.track())
+    }
+
+    fn track_count(&self) -> Option<u32> {
+        let src = self.src.track_count();
+        self.pick(DataType::TrackCount, self.old.track_count, src)
+    }
+
+    fn year(&self) -> Option<i32> {
+        self.pick(DataType::Year, self.old.year, self.src.year())
+    }
+
+    fn date(&self) -> Option<(u8, u8)> {
+        self.pick(DataType::Date, self.old.date, self.src.date())
+    }
+
+    fn time(&self) -> Option<Duration> {
+        self.pick(DataType::Time, self.old.time, self.src.time())
+    }
+
+    fn disc(&self) -> Option<u32> {
+        self.pick(DataType::Disc, self.old.disc, self.src.disc())
+    }
+
+    fn disc_count(&self) -> Option<u32> {
+        let src = self.src.disc_count();
+        self.pick(DataType::DiscCount, self.old.disc_count, src)
+    }
+
+    fn length(&self) -> Option<Duration> {
+        self.pick(DataType::Length, self.old.length, self.src.length())
+    }
+
+    fn comments(&self) -> &[Comment] {
+        let src = self.src.comments();
+        self.pick(DataType::Comments, self.old.comments.as_slice(), src)
+    }
+
+    fn pictures(&self) -> &[Picture] {
+        let src = self.src.pictures();
+        let typ = DataType::Picture(PictureKind::all());
+        self.pick(typ, self.old.pictures.as_slice(), src)
+    }
+
+    fn copyright(&self) -> Option<&str> {
+        let src = self.src.copyright();
+        let old = self.old.copyright.as_deref();
+        self.pick(DataType::Copyright, old, src)
+    }
+
+    fn ratings(&self) -> &[Rating] {
+        let src = self.src.ratings();
+        self.pick(DataType::Ratings, self.old.ratings.as_slice(), src)
+    }
+
+    fn removes(&self, typ: DataType) -> bool {
+        self.src.removes(typ)
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{Error, Result, mp4::boxtype};
+
+/// Box loaded into memory. Only boxes that are needed for writing are parsed
+/// into children, the other boxes are kept as raw data.
+#[derive(Debug)]
+pub struct BoxTree {
+    pub boxtype: u32,
+    /// Data of the box that precede the children. For container boxes this
+    /// is usually empty or the full box header.
+    pub data: Vec<u8>,
+    pub children: Vec<BoxTree>,
+}
+
+impl BoxTree {
+    pub fn new(boxtype: u32, data: Vec<u8>, children: Vec<BoxTree>) -> Self {
+        Self {
+            boxtype,
+            data,
+            children,
+        }
+    }
+
+    /// Parse box with the given type from its content (without header).
+    pub fn parse(boxtype: u32, d: &[u8]) -> Result<Self> {
+        let head_len = match boxtype {
+            boxtype::MOOV
+            | boxtype::TRAK
+            | boxtype::MDIA
+            | boxtype::MINF
+            | boxtype::STBL
+            | boxtype::UDTA
+            | boxtype::ILST => 0,
+            boxtype::META => 4,
+            _ => return Ok(Self::new(boxtype, d.to_vec(), vec![])),
+        };
+
+        if d.len() < head_len {
+            return Err(Error::InvalidLength);
+        }
+
+        let (head, mut d) = d.split_at(head_len);
+        let mut children = vec![];
+        while !d.is_empty() {
+            let (typ, data, rest) = split_box(d)?;
+            children.push(Self::parse(typ, data)?);
+            d = rest;
+        }
+
+        Ok(Self::new(boxtype, head.to_vec(), children))
+    }
+
+    /// Get child with the given type. If there is no such child, it is
+    /// created using `new`.
+    pub fn child_or(
+        &mut self,
+        boxtype: u32,
+        new: impl FnOnce() -> BoxTree,
+    ) -> &mut BoxTree {
+        let idx = match self.children.iter().position(|a| a.boxtype == boxtype)
+        {
+            Some(i) => i,
+            None => {
+                self.children.push(new());
+                self.children.len() - 1
+            }
+        };
+        &mut self.children[idx]
+    }
+
+    /// Call `f` on all boxes in this tree.
+    pub fn for_each_mut(
+        &mut self,
+        f: &mut impl FnMut(&mut BoxTree) -> Result<()>,
+    ) -> Result<()> {
+        f(self)?;
+        for c in &mut self.children {
+            c.for_each_mut(f)?;
+        }
+        Ok(())
+    }
+
+    /// Total size of the box including the header.
+    pub fn size(&self) -> u64 {
+        let len = self.data.len() as u64
+            + self.children.iter().map(|a| a.size()).sum::<u64>();
+        if len + 8 > u32::MAX as u64 {
+            len + 16
+        } else {
+            len + 8
+        }
+    }
+
+    pub fn write_to(&self, out: &mut Vec<u8>) {
+        let size = self.size();
+        if size > u32::MAX as u64 {
+            out.extend(1_u32.to_be_bytes());
+            out.extend(self.boxtype.to_be_bytes());
+            out.extend(size.to_be_bytes());
+        } else {
+            out.extend((size as u32).to_be_bytes());
+            out.extend(self.boxtype.to_be_bytes());
+        }
+
+        out.extend_from_slice(&self.data);
+        for c in &self.children {
+            c.write_to(out);
+        }
+    }
+}
+
+/// Split the first box from the data. Returns the box type, content of the
+/// box and the remaining data.
+fn split_box(d: &[u8]) -> Result<(u32, &[u8], &[u8])> {
+    if d.len() < 8 {
+        return Err(Error::InvalidLength);
+    }
+
+    let size = u32::from_be_bytes(d[..4].try_into().unwrap()) as u64;
+    let typ = u32::from_be_bytes(d[4..8].try_into().unwrap());
+    let (head, size) = match size {
+        0 => (8, d.len() as u64),
+        1 => {
+            if d.len() < 16 {
+                return Err(Error::InvalidLength);
+            }
+            (16, u64::from_be_bytes(d[8..16].try_into().unwrap()))
+        }
+        s => (8, s),
+    };
+
+    if size < head || size > d.len() as u64 {
+        return Err(Error::InvalidLength);
+    }
+
+    let size = size as usize;
+    Ok((typ, &d[head as usize..size], &d[size..]))
+}
@@ -36,3 +36,101 @@ pub const TRKN: u32 = u32::from_be_bytes(*b"trkn");
 pub const UDTA: u32 = u32::from_be_bytes(*b"udta");
 /// Copyright
 pub const CPRT: u32 = u32::from_be_bytes(*b"cprt");
+/// Free space box.
+pub const FREE: u32 = u32::from_be_bytes(*b"free");
+/// Free space box.
+pub const SKIP: u32 = u32::from_be_bytes(*b"skip");
+/// Track box.
+pub const TRAK: u32 = u32::from_be_bytes(*b"trak");
+/// Media box.
+pub const MDIA: u32 = u32::from_be_bytes(*b"mdia");
+/// Media information box.
+pub const MINF: u32 = u32::from_be_bytes(*b"minf");
+/// Sample table box.
+pub const STBL: u32 = u32::from_be_bytes(*b"stbl");
+/// Chunk offset box.
+pub const STCO: u32 = u32::from_be_bytes(*b"stco");
+/// Chunk offset box with 64 bit offsets.
+pub const CO64: u32 = u32::from_be_bytes(*b"co64");
+/// Handler reference box.
+pub const HDLR: u32 = u32::from_be_bytes(*b"hdlr");
+/// Text genre box.
+pub const GEN: u32 = u32::from_be_bytes(*b"\xa9gen");
+/// Lyrics box.
+pub const LYR: u32 = u32::from_be_bytes(*b"\xa9lyr");
+/// Freeform metadata box.
+pub const FREEFORM: u32 = u32::from_be_bytes(*b"----");
+/// Namespace of freeform box.
+pub const MEAN: u32 = u32::from_be_bytes(*b"mean");
+/// Name of freeform box.
+pub const NAME: u32 = u32::from_be_bytes(*b"name");
+/// Media header box.
+pub const MDHD: u32 = u32::from_be_bytes(*b"mdhd");
+/// Sample description box.
+pub const STSD: u32 = u32::from_be_bytes(*b"stsd");
+/// Elementary stream descriptor box.
+pub const ESDS: u32 = u32::from_be_bytes(*b"esds");
+/// MPEG-4 audio sample entry.
+pub const MP4A: u32 = u32::from_be_bytes(*b"mp4a");
+/// Apple lossless sample entry and its configuration box.
+pub const ALAC: u32 = u32::from_be_bytes(*b"alac");
+/// Opus sample entry.
+pub const OPUS: u32 = u32::from_be_bytes(*b"Opus");
+/// Opus specific box.
+pub const DOPS: u32 = u32::from_be_bytes(*b"dOps");
+/// Flac sample entry.
+pub const FLAC: u32 = u32::from_be_bytes(*b"fLaC");
+/// Flac specific box.
+pub const DFLA: u32 = u32::from_be_bytes(*b"dfLa");
+/// Composer box.
+pub const WRT: u32 = u32::from_be_bytes(*b"\xa9wrt");
+/// Grouping box.
+pub const GRP: u32 = u32::from_be_bytes(*b"\xa9grp");
+/// Encoding tool box.
+pub const TOO: u32 = u32::from_be_bytes(*b"\xa9too");
+/// Tempo (BPM) box.
+pub const TMPO: u32 = u32::from_be_bytes(*b"tmpo");
+/// Compilation box.
+pub const CPIL: u32 = u32::from_be_bytes(*b"cpil");
+/// Album sort order box.
+pub const SOAL: u32 = u32::from_be_bytes(*b"soal");
+/// Artist sort order box.
+pub const SOAR: u32 = u32::from_be_bytes(*b"soar");
+/// Album artist sort order box.
+pub const SOAA: u32 = u32::from_be_bytes(*b"soaa");
+/// Title sort order box.
+pub const SONM: u32 = u32::from_be_bytes(*b"sonm");
+/// Composer sort order box.
+pub const SOCO: u32 = u32::from_be_bytes(*b"soco");
+/// Gapless playback box.
+pub const PGAP: u32 = u32::from_be_bytes(*b"pgap");
+/// Content advisory rating box.
+pub const RTNG: u32 = u32::from_be_bytes(*b"rtng");
+/// Media kind box.
+pub const STIK: u32 = u32::from_be_bytes(*b"stik");
+/// Description box.
+pub const DESC: u32 = u32::from_be_bytes(*b"desc");
+/// Long description box.
+pub const LDES: u32 = u32::from_be_bytes(*b"ldes");
+/// Purchase date box.
+pub const PURD: u32 = u32::from_be_bytes(*b"purd");
+/// TV show name box.
+pub const TVSH: u32 = u32::from_be_bytes(*b"tvsh");
+/// TV episode id box.
+pub const TVEN: u32 = u32::from_be_bytes(*b"tven");
+/// TV episode number box.
+pub const TVES: u32 = u32::from_be_bytes(*b"tves");
+/// TV season box.
+pub const TVSN: u32 = u32::from_be_bytes(*b"tvsn");
+/// TV network box.
+pub const TVNN: u32 = u32::from_be_bytes(*b"tvnn");
+/// Podcast flag box.
+pub const PCST: u32 = u32::from_be_bytes(*b"pcst");
+/// Podcast URL box.
+pub const PURL: u32 = u32::from_be_bytes(*b"purl");
+/// Podcast episode GUID box.
+pub const EGID: u32 = u32::from_be_bytes(*b"egid");
+/// Podcast category box.
+pub const CATG: u32 = u32::from_be_bytes(*b"catg");
+/// Podcast keywords box.
+pub const KEYW: u32 = u32::from_be_bytes(*b"keyw");
@@ -11,9 +11,14 @@ pub struct FullBox {
 }
 
 impl FullBox {
+    // Types of data in the `data` box, stored in its flags.
     pub const BINARY: u32 = 0;
     pub const TEXT: u32 = 1;
-    pub const IMAGE: u32 = 0xD;
+    pub const JPEG: u32 = 0xD;
+    pub const PNG: u32 = 0xE;
+    pub const SIGNED: u32 = 0x15;
+    pub const UNSIGNED: u32 = 0x16;
+    pub const BMP: u32 = 0x1B;
 
     pub fn from_bytes(d: &[u8; 4]) -> Self {
         Self {
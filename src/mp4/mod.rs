@@ -1,9 +1,15 @@
+mod box_tree;
 mod boxtype;
 mod full_box;
 mod mp4_box;
 mod opt_u64;
+mod track;
+mod write_options;
+mod writer;
 
-use self::{full_box::*, mp4_box::*, opt_u64::*};
+use self::{full_box::*, mp4_box::*, opt_u64::*, track::*};
+
+pub use self::{write_options::*, writer::*};
 
 use std::{
     fs::File,
@@ -13,11 +19,14 @@ use std::{
 };
 
 use crate::{
-    Comment, DataType, Error, Picture, PictureKind, Result, TagRead, TagStore,
-    TagStoreExt, TagType,
+    Comment, CustomField, CustomValue, DataType, DateTime, Error, Lyrics,
+    Picture, PictureKind, Result, TagRead, TagSource, TagSourceExt, TagStore,
+    TagStoreExt, TagType, TagWrite,
     bread::Bread,
     id3::genres::get_genre,
-    parsers::{self, DateTime},
+    musicbrainz::MusicBrainzId,
+    parsers,
+    replay_gain::{ReplayGain, ReplayGainField},
     trap::{Trap, TrapExt},
 };
 
@@ -38,6 +47,29 @@ impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Mp4 {
     }
 }
 
+impl<S: TagSource> TagWrite<S> for Mp4 {
+    fn extensions(&self) -> &[&str] {
+        &["mp4", "m4a", "m4p", "m4b", "m4r", "m4v"]
+    }
+
+    fn supports_data(&self, typ: DataType) -> bool {
+        !matches!(
+            typ,
+            DataType::Length
+                | DataType::Copyright
+                | DataType::Ratings
+                | DataType::TagType
+        )
+    }
+
+    fn write(&self, path: &Path, src: &S) -> Result<Vec<DataType>> {
+        write_file(path, src, &WriteOptions::default())?;
+        Ok(src.unsupported_data(|t| {
+            <Self as TagWrite<S>>::supports_data(self, t)
+        }))
+    }
+}
+
 /// Read tags from mp4 file given by path.
 pub fn from_path(
     p: impl AsRef<Path>,
@@ -105,6 +137,11 @@ fn read_moov(
     trap: &impl Trap,
     len: OptU64,
 ) -> Result<()> {
+    let read_tracks = store.stores_data(DataType::Length)
+        || store.stores_data(DataType::AudioProperties);
+
+    let mut length = None;
+    let mut sound = None;
     let mut pos = OptU64(Some(0));
     while pos < len && !store.done() {
         let bx: Mp4Box = r.get()?;
@@ -114,7 +151,14 @@ fn read_moov(
                 read_udta(r, store, trap, bx.size_next)?;
             }
             boxtype::MVHD if store.stores_data(DataType::Length) => {
-                read_mvhd(r, store, trap, bx.size_next)?;
+                length = read_mvhd(r, trap, bx.size_next)?;
+            }
+            boxtype::TRAK if read_tracks && sound.is_none() => {
+                let mut track = Track::default();
+                read_trak(r, trap, bx.size_next, bx.boxtype, &mut track)?;
+                if track.is_sound() {
+                    sound = Some(track);
+                }
             }
             _ => {
                 let Some(s) = *bx.size_next else {
@@ -125,9 +169,85 @@ fn read_moov(
         }
     }
 
+    // Length of movie may include other tracks and edits, so the length of
+    // the sound track is preferred.
+    if let Some(t) = sound {
+        length = t.length().or(length);
+        if store.stores_data(DataType::AudioProperties) {
+            store.set_audio_properties(t.properties());
+        }
+    }
+
+    if let Some(l) = length
+        && store.stores_data(DataType::Length)
+    {
+        store.set_length(l);
+    }
+
     Ok(())
 }
 
+/// Read the boxes within track box (`trak`) that describe the audio. `parent`
+/// is the type of the box whose content is read.
+fn read_trak(
+    r: &mut Bread<impl BufRead + Seek>,
+    trap: &impl Trap,
+    len: OptU64,
+    parent: u32,
+    track: &mut Track,
+) -> Result<()> {
+    let mut pos = OptU64(Some(0));
+    while pos < len {
+        let bx: Mp4Box = r.get()?;
+        pos += bx.size_total;
+        match bx.boxtype {
+            boxtype::MDIA | boxtype::MINF | boxtype::STBL => {
+                read_trak(r, trap, bx.size_next, bx.boxtype, track)?;
+            }
+            boxtype::MDHD if parent == boxtype::MDIA => {
+                if let Some(h) = read_box(r, trap, bx.size_next, read_mdhd)? {
+                    track.timescale = h.timescale;
+                    track.duration = h.duration;
+                    track.language = h.language;
+                }
+            }
+            // QuickTime has also data handler in `minf`.
+            boxtype::HDLR if parent == boxtype::MDIA => {
+                if let Some(h) = read_box(r, trap, bx.size_next, read_hdlr)? {
+                    track.handler = h;
+                }
+            }
+            boxtype::STSD if track.is_sound() => {
+                track.entry =
+                    read_box(r, trap, bx.size_next, read_stsd)?.flatten();
+            }
+            _ => {
+                let Some(s) = *bx.size_next else {
+                    break;
+                };
+                r.useek_by(s)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read the whole content of box with the given size.
+fn read_box<T, Tr: Trap>(
+    r: &mut Bread<impl BufRead + Seek>,
+    trap: &Tr,
+    len: OptU64,
+    f: impl FnOnce(&[u8], &Tr) -> Result<T>,
+) -> Result<Option<T>> {
+    let Some(len) = *len else {
+        r.seek(SeekFrom::End(0))?;
+        trap.error(Error::Unsupported("Size to end of file."))?;
+        return Ok(None);
+    };
+    r.witht(len as usize, trap, f)
+}
+
 fn read_udta(
     r: &mut Bread<impl BufRead + Seek>,
     store: &mut impl TagStore,
@@ -168,18 +288,19 @@ fn read_udta(
 
 fn read_mvhd(
     r: &mut Bread<impl BufRead + Seek>,
-    store: &mut impl TagStore,
     trap: &impl Trap,
     len: OptU64,
-) -> Result<()> {
+) -> Result<Option<Duration>> {
     let Some(mut len) = *len else {
         r.seek(SeekFrom::End(0))?;
-        return trap.error(Error::InvalidLength);
+        trap.error(Error::InvalidLength)?;
+        return Ok(None);
     };
 
     if len < 32 {
         r.useek_by(len)?;
-        return trap.error(Error::InvalidLength);
+        trap.error(Error::InvalidLength)?;
+        return Ok(None);
     }
 
     let fb: FullBox = r.get()?;
@@ -201,18 +322,16 @@ fn read_mvhd(
         }
         _ => {
             r.useek_by(len)?;
-            return trap.error(Error::Unsupported(
+            trap.error(Error::Unsupported(
                 "Unsupported movie header version. Max v1 is supported.",
-            ));
+            ))?;
+            return Ok(None);
         }
     };
 
     r.useek_by(len)?;
 
-    let dur = Duration::from_secs_f64(dur as f64 / ts as f64);
-    store.set_length(dur);
-
-    Ok(())
+    Ok(Some(Duration::from_secs_f64(dur as f64 / ts as f64)))
 }
 
 fn read_meta(
@@ -306,7 +425,9 @@ fn read_ilst(
                     Ok(())
                 })?;
             }
-            boxtype::GNRE if store.stores_data(DataType::Genres) => {
+            boxtype::GNRE | boxtype::GEN
+                if store.stores_data(DataType::Genres) =>
+            {
                 read_annotation(r, trap, len, read_genre, |g| {
                     store.set_genres(vec![g]);
                     Ok(())
@@ -327,11 +448,8 @@ fn read_ilst(
                     PictureKind::FRONT_COVER,
                 )) =>
             {
-                read_annotation(r, trap, len, read_image, |d| {
-                    store.add_picture(Picture::from_data(
-                        d,
-                        PictureKind::FRONT_COVER,
-                    ));
+                read_annotations(r, trap, len, read_image, |p: Picture| {
+                    store.add_picture(p);
                     Ok(())
                 })?;
             }
@@ -341,6 +459,121 @@ fn read_ilst(
                     Ok(())
                 })?;
             }
+            boxtype::LYR if store.stores_data(DataType::Lyrics) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_lyrics(vec![Lyrics::from_text(s)]);
+                    Ok(())
+                })?;
+            }
+            boxtype::WRT if store.stores_data(DataType::Composers) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_composers(vec![s]);
+                    Ok(())
+                })?;
+            }
+            boxtype::GRP if store.stores_data(DataType::Grouping) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_grouping(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::TOO if store.stores_data(DataType::EncoderSettings) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_encoder_settings(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::SONM if store.stores_data(DataType::TitleSort) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_title_sort(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::SOAL if store.stores_data(DataType::AlbumSort) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_album_sort(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::SOAR if store.stores_data(DataType::ArtistSort) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_artist_sort(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::SOAA if store.stores_data(DataType::AlbumArtistSort) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_album_artist_sort(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::SOCO if store.stores_data(DataType::ComposerSort) => {
+                read_annotation(r, trap, len, read_string, |s| {
+                    store.set_composer_sort(s);
+                    Ok(())
+                })?;
+            }
+            boxtype::TMPO if store.stores_data(DataType::Bpm) => {
+                read_annotation(r, trap, len, read_int, |n: i64| {
+                    store.set_bpm(n.try_into()?);
+                    Ok(())
+                })?;
+            }
+            boxtype::CPIL if store.stores_data(DataType::Compilation) => {
+                read_annotation(r, trap, len, read_int, |n: i64| {
+                    store.set_compilation(n != 0);
+                    Ok(())
+                })?;
+            }
+            boxtype::PGAP
+            | boxtype::RTNG
+            | boxtype::STIK
+            | boxtype::TVES
+            | boxtype::TVSN
+            | boxtype::PCST
+                if store.stores_data(DataType::CustomFields) =>
+            {
+                let key = String::from_utf8_lossy(&bx.boxtype.to_be_bytes())
+                    .into_owned();
+                read_annotation(r, trap, len, read_int, |n: i64| {
+                    store.add_custom_field(CustomField {
+                        namespace: None,
+                        key,
+                        value: CustomValue::Text(n.to_string()),
+                    });
+                    Ok(())
+                })?;
+            }
+            boxtype::DESC
+            | boxtype::LDES
+            | boxtype::PURD
+            | boxtype::TVSH
+            | boxtype::TVEN
+            | boxtype::TVNN
+            | boxtype::PURL
+            | boxtype::EGID
+            | boxtype::CATG
+            | boxtype::KEYW
+                if store.stores_data(DataType::CustomFields) =>
+            {
+                let key = String::from_utf8_lossy(&bx.boxtype.to_be_bytes())
+                    .into_owned();
+                read_annotation(r, trap, len, read_value, |value| {
+                    store.add_custom_field(CustomField {
+                        namespace: None,
+                        key,
+                        value,
+                    });
+                    Ok(())
+                })?;
+            }
+            boxtype::FREEFORM
+                if store.stores_data(DataType::CustomFields)
+                    || MusicBrainzId::stores_any(store)
+                    || ReplayGain::stores_any(store) =>
+            {
+                read_freeform(r, store, trap, len)?;
+            }
             _ => {
                 let Some(s) = *bx.size_next else {
                     break;
@@ -393,11 +626,158 @@ fn read_annotation<
     Ok(())
 }
 
+/// Read all the data boxes of the annotation.
+fn read_annotations<
+    T,
+    R: BufRead + Seek,
+    Tr: Trap,
+    S: FnMut(T) -> Result<()>,
+>(
+    r: &mut Bread<R>,
+    trap: &Tr,
+    len: OptU64,
+    parse: impl Fn(&mut Bread<R>, &Tr, OptU64, &mut S) -> Result<()>,
+    mut store: S,
+) -> Result<()> {
+    let mut pos = OptU64(Some(0));
+    while pos < len {
+        let bx: Mp4Box = r.get()?;
+        pos += bx.size_total;
+        match bx.boxtype {
+            boxtype::DATA => parse(r, trap, bx.size_next, &mut store)?,
+            _ => {
+                let Some(s) = *bx.size_next else {
+                    break;
+                };
+                r.useek_by(s)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Namespace of freeform boxes used by iTunes.
+const ITUNES_NAMESPACE: &str = "com.apple.iTunes";
+
+/// Read freeform box (`----`) with its namespace (`mean`), name (`name`) and
+/// values (`data`).
+fn read_freeform(
+    r: &mut Bread<impl BufRead + Seek>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    len: OptU64,
+) -> Result<()> {
+    let mut namespace = None;
+    let mut name = None;
+    let mut values = vec![];
+
+    let mut pos = OptU64(Some(0));
+    while pos < len {
+        let bx: Mp4Box = r.get()?;
+        pos += bx.size_total;
+        let Some(mut size) = *bx.size_next else {
+            r.seek(SeekFrom::End(0))?;
+            return trap.error(Error::InvalidLength);
+        };
+
+        match bx.boxtype {
+            boxtype::MEAN | boxtype::NAME => {
+                if size < 4 {
+                    r.useek_by(size)?;
+                    trap.error(Error::InvalidLength)?;
+                    continue;
+                }
+                r.seek_by(4)?;
+                let s = r.witht((size - 4) as usize, trap, |d, t| {
+                    parsers::utf_8(d, t)
+                })?;
+                if bx.boxtype == boxtype::MEAN {
+                    namespace = s;
+                } else {
+                    name = s;
+                }
+            }
+            boxtype::DATA => {
+                if size < 8 {
+                    r.useek_by(size)?;
+                    trap.error(Error::InvalidLength)?;
+                    continue;
+                }
+                let fb: FullBox = r.get()?;
+                r.seek_by(4)?;
+                size -= 8;
+                if fb.flags == FullBox::TEXT {
+                    values.extend(
+                        r.witht(size as usize, trap, |d, t| {
+                            parsers::utf_8(d, t)
+                        })?
+                        .map(CustomValue::Text),
+                    );
+                } else {
+                    values.push(CustomValue::Binary(
+                        r.read_exact_owned(size as usize)?,
+                    ));
+                }
+            }
+            _ => r.useek_by(size)?,
+        }
+    }
+
+    // Freeform box without name cannot be identified.
+    let Some(key) = name else {
+        return Ok(());
+    };
+
+    let itunes = namespace.as_deref() == Some(ITUNES_NAMESPACE);
+
+    let mb = key
+        .strip_prefix("MusicBrainz ")
+        .and_then(MusicBrainzId::from_name)
+        .filter(|_| itunes);
+    if let Some(id) = mb
+        && store.stores_data(id.data_type())
+    {
+        let ids = values
+            .iter()
+            .filter_map(|v| match v {
+                CustomValue::Text(s) => Some(s.clone()),
+                CustomValue::Binary(_) => None,
+            })
+            .collect();
+        id.store(ids, store, trap)?;
+    }
+
+    let rg = ReplayGainField::from_key(&key).filter(|_| itunes);
+    if let Some(f) = rg
+        && store.stores_data(f.data_type())
+        && let Some(CustomValue::Text(v)) = values.last()
+    {
+        let mut replay_gain = ReplayGain::default();
+        replay_gain.set(f, v, trap)?;
+        replay_gain.store(store);
+    }
+
+    if !store.stores_data(DataType::CustomFields) {
+        return Ok(());
+    }
+
+    for value in values {
+        store.add_custom_field(CustomField {
+            namespace: namespace.clone(),
+            key: key.clone(),
+            value,
+        });
+    }
+
+    Ok(())
+}
+
 fn read_image(
     r: &mut Bread<impl BufRead + Seek>,
     trap: &impl Trap,
     len: OptU64,
-    store: impl FnOnce(Vec<u8>) -> Result<()>,
+    store: &mut impl FnMut(Picture) -> Result<()>,
 ) -> Result<()> {
     let Some(mut len) = *len else {
         r.seek(SeekFrom::End(0))?;
@@ -413,13 +793,28 @@ fn read_image(
     r.seek_by(4)?;
     len -= 8;
 
-    if fb.flags != FullBox::IMAGE {
+    if !matches!(
+        fb.flags,
+        FullBox::BINARY | FullBox::JPEG | FullBox::PNG | FullBox::BMP
+    ) {
         r.useek_by(len)?;
         return trap.error(Error::Unsupported("Box type flags for image."));
     }
 
     let data = r.read_exact_owned(len as usize)?;
-    trap.prop(store(data))
+    let mime = match fb.flags {
+        FullBox::JPEG => Some("image/jpeg"),
+        FullBox::PNG => Some("image/png"),
+        FullBox::BMP => Some("image/bmp"),
+        // Implicit type, guess it from the data.
+        _ if data.starts_with(b"\xFF\xD8\xFF") => Some("image/jpeg"),
+        _ if data.starts_with(b"\x89PNG") => Some("image/png"),
+        _ if data.starts_with(b"BM") => Some("image/bmp"),
+        _ => None,
+    };
+    let mut pic = Picture::from_data(data, PictureKind::FRONT_COVER);
+    pic.mime = mime.map(|m| m.to_string());
+    trap.prop(store(pic))
 }
 
 fn read_genre(
@@ -448,11 +843,12 @@ fn read_genre(
                 r.useek_by(len)?;
                 return trap.error(Error::InvalidLength);
             }
+            // The genre number is ID3v1 genre number increased by one.
             let num: u16 = r.get_be()?;
-            if num > u8::MAX as u16 {
+            if num == 0 || num > u8::MAX as u16 + 1 {
                 return trap.error(Error::InvalidGenreRef);
             }
-            let Some(g) = get_genre(num as u8) else {
+            let Some(g) = get_genre((num - 1) as u8) else {
                 return trap.error(Error::InvalidGenreRef);
             };
             trap.prop(store(g.to_string()))
@@ -507,6 +903,101 @@ fn read_num_of(
     res
 }
 
+fn read_int(
+    r: &mut Bread<impl BufRead + Seek>,
+    trap: &impl Trap,
+    len: OptU64,
+    store: impl FnOnce(i64) -> Result<()>,
+) -> Result<()> {
+    let Some(mut len) = *len else {
+        r.seek(SeekFrom::End(0))?;
+        return trap.error(Error::InvalidLength);
+    };
+
+    if len < 9 {
+        r.useek_by(len)?;
+        return trap.error(Error::InvalidLength);
+    }
+
+    let fb: FullBox = r.get()?;
+    r.seek_by(4)?;
+    len -= 8;
+
+    let signed = match fb.flags {
+        FullBox::BINARY | FullBox::SIGNED => true,
+        FullBox::UNSIGNED => false,
+        _ => {
+            r.useek_by(len)?;
+            return trap
+                .error(Error::Unsupported("Box type flags for integer."));
+        }
+    };
+
+    if let Some(n) = r.witht(len as usize, trap, |d, _| be_int(d, signed))? {
+        trap.prop(store(n))
+    } else {
+        Ok(())
+    }
+}
+
+/// Read data of any type. Text is read as string, integers with explicit type
+/// are converted to string and other types are kept as binary data.
+fn read_value(
+    r: &mut Bread<impl BufRead + Seek>,
+    trap: &impl Trap,
+    len: OptU64,
+    store: impl FnOnce(CustomValue) -> Result<()>,
+) -> Result<()> {
+    let Some(mut len) = *len else {
+        r.seek(SeekFrom::End(0))?;
+        return trap.error(Error::InvalidLength);
+    };
+
+    if len < 8 {
+        r.useek_by(len)?;
+        return trap.error(Error::InvalidLength);
+    }
+
+    let fb: FullBox = r.get()?;
+    r.seek_by(4)?;
+    len -= 8;
+
+    let value = match (fb.flags, len) {
+        (FullBox::TEXT, _) => r
+            .witht(len as usize, trap, |d, t| parsers::utf_8(d, t))?
+            .map(CustomValue::Text),
+        (FullBox::SIGNED, 1 | 2 | 4 | 8) => r
+            .witht(len as usize, trap, |d, _| be_int(d, true))?
+            .map(|n| CustomValue::Text(n.to_string())),
+        (FullBox::UNSIGNED, 1 | 2 | 4 | 8) => r
+            .witht(len as usize, trap, |d, _| be_int(d, false))?
+            .map(|n| CustomValue::Text(n.to_string())),
+        _ => Some(CustomValue::Binary(r.read_exact_owned(len as usize)?)),
+    };
+
+    if let Some(v) = value {
+        trap.prop(store(v))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parse big endian integer with 1, 2, 4 or 8 bytes.
+fn be_int(d: &[u8], signed: bool) -> Result<i64> {
+    let n = match (d.len(), signed) {
+        (1, true) => d[0] as i8 as i64,
+        (1, false) => d[0] as i64,
+        (2, true) => i16::from_be_bytes(d.try_into()?) as i64,
+        (2, false) => u16::from_be_bytes(d.try_into()?) as i64,
+        (4, true) => i32::from_be_bytes(d.try_into()?) as i64,
+        (4, false) => u32::from_be_bytes(d.try_into()?) as i64,
+        (8, true) => i64::from_be_bytes(d.try_into()?),
+        (8, false) => u64::from_be_bytes(d.try_into()?).try_into()?,
+        _ => return Err(Error::InvalidLength),
+    };
+    Ok(n)
+}
+
 fn read_day(
     r: &mut Bread<impl BufRead + Seek>,
     trap: &impl Trap,
@@ -551,3 +1042,240 @@ fn read_string(
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    #[derive(Default)]
+    struct Store {
+        composers: Vec<String>,
+        bpm: Option<u32>,
+        compilation: Option<bool>,
+        album_sort: Option<String>,
+        track_gain: Option<f32>,
+        custom: Vec<CustomField>,
+        pictures: Vec<Picture>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Composers
+                    | DataType::Bpm
+                    | DataType::Compilation
+                    | DataType::AlbumSort
+                    | DataType::TrackGain
+                    | DataType::CustomFields
+                    | DataType::Picture(_)
+            )
+        }
+
+        fn set_composers(&mut self, composers: Vec<String>) {
+            self.composers = composers;
+        }
+
+        fn set_bpm(&mut self, bpm: u32) {
+            self.bpm = Some(bpm);
+        }
+
+        fn set_compilation(&mut self, compilation: bool) {
+            self.compilation = Some(compilation);
+        }
+
+        fn set_album_sort(&mut self, album: String) {
+            self.album_sort = Some(album);
+        }
+
+        fn set_track_gain(&mut self, gain: f32) {
+            self.track_gain = Some(gain);
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push(field);
+        }
+
+        fn add_picture(&mut self, picture: Picture) {
+            self.pictures.push(picture);
+        }
+    }
+
+    fn bx(typ: &[u8; 4], d: &[u8]) -> Vec<u8> {
+        let mut res = ((d.len() + 8) as u32).to_be_bytes().to_vec();
+        res.extend(typ);
+        res.extend_from_slice(d);
+        res
+    }
+
+    /// Create `data` box with the given type flags.
+    fn data(flags: u8, d: &[u8]) -> Vec<u8> {
+        let mut res = vec![0, 0, 0, flags, 0, 0, 0, 0];
+        res.extend_from_slice(d);
+        bx(b"data", &res)
+    }
+
+    /// Create mp4 file with the given items in `ilst`.
+    fn file(items: &[Vec<u8>]) -> Vec<u8> {
+        let mut meta = vec![0; 4];
+        meta.extend(bx(b"ilst", &items.concat()));
+        let moov = bx(b"moov", &bx(b"udta", &bx(b"meta", &meta)));
+        let mut res = bx(b"ftyp", b"M4A \0\0\0\0M4A mp42isom");
+        res.extend(moov);
+        res
+    }
+
+    fn read(d: &[u8], trap: &impl Trap) -> Result<Store> {
+        let mut store = Store::default();
+        from_read(Cursor::new(d), &mut store, trap)?;
+        Ok(store)
+    }
+
+    fn freeform(mean: &str, name: &str, data: &[Vec<u8>]) -> Vec<u8> {
+        let mut d = bx(b"mean", &[b"\0\0\0\0", mean.as_bytes()].concat());
+        d.extend(bx(b"name", &[b"\0\0\0\0", name.as_bytes()].concat()));
+        d.extend(data.concat());
+        bx(b"----", &d)
+    }
+
+    #[test]
+    fn itunes_items() {
+        let d = file(&[
+            bx(b"\xa9wrt", &data(1, b"Composer")),
+            bx(b"tmpo", &data(0x15, b"\0\x78")),
+            bx(b"cpil", &data(0x15, b"\x01")),
+            bx(b"soal", &data(1, b"Album, The")),
+            bx(b"pgap", &data(0x15, b"\x01")),
+            bx(b"desc", &data(1, b"Description")),
+        ]);
+        let store = read(&d, &Strict).unwrap();
+        assert_eq!(store.composers, ["Composer"]);
+        assert_eq!(store.bpm, Some(120));
+        assert_eq!(store.compilation, Some(true));
+        assert_eq!(store.album_sort.as_deref(), Some("Album, The"));
+
+        let res: Vec<_> = store
+            .custom
+            .iter()
+            .map(|a| (a.key.as_str(), &a.value))
+            .collect();
+        assert_eq!(
+            res,
+            [
+                ("pgap", &CustomValue::Text("1".into())),
+                ("desc", &CustomValue::Text("Description".into())),
+            ]
+        );
+    }
+
+    #[test]
+    fn freeform_items() {
+        let d = file(&[
+            freeform(
+                ITUNES_NAMESPACE,
+                "CATALOGNUMBER",
+                &[data(1, b"CAT-1"), data(1, b"CAT-2")],
+            ),
+            freeform("org.example", "raw", &[data(0, b"\x01\x02")]),
+            freeform(
+                ITUNES_NAMESPACE,
+                "replaygain_track_gain",
+                &[data(1, b"-6.54 dB")],
+            ),
+        ]);
+        let store = read(&d, &Strict).unwrap();
+        assert_eq!(store.track_gain, Some(-6.54));
+
+        let res: Vec<_> = store
+            .custom
+            .iter()
+            .map(|a| (a.namespace.as_deref(), a.key.as_str(), &a.value))
+            .collect();
+        let text = |s: &str| CustomValue::Text(s.into());
+        assert_eq!(
+            res,
+            [
+                (Some(ITUNES_NAMESPACE), "CATALOGNUMBER", &text("CAT-1")),
+                (Some(ITUNES_NAMESPACE), "CATALOGNUMBER", &text("CAT-2")),
+                (Some("org.example"), "raw", &CustomValue::Binary(vec![1, 2])),
+                (
+                    Some(ITUNES_NAMESPACE),
+                    "replaygain_track_gain",
+                    &text("-6.54 dB")
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_items() {
+        // Invalid integer size and freeform without name.
+        let d = file(&[
+            bx(b"tmpo", &data(0x15, b"\0\0\x78")),
+            bx(b"----", &bx(b"mean", b"\0\0\0\0ns")),
+            bx(b"\xa9wrt", &data(1, b"Composer")),
+        ]);
+        assert!(read(&d, &Strict).is_err());
+
+        let store = read(&d, &Skip).unwrap();
+        assert_eq!(store.bpm, None);
+        assert!(store.custom.is_empty());
+        assert_eq!(store.composers, ["Composer"]);
+    }
+
+    #[test]
+    fn cover_images() {
+        let d = file(&[bx(
+            b"covr",
+            &[
+                data(13, b"jpeg"),
+                data(14, b"png"),
+                data(27, b"bmp"),
+                data(0, b"\x89PNG\r\n"),
+                data(0, b"data"),
+            ]
+            .concat(),
+        )]);
+        let store = read(&d, &Strict).unwrap();
+        let res: Vec<_> = store
+            .pictures
+            .iter()
+            .map(|a| (a.mime.as_deref(), a.data.as_slice()))
+            .collect();
+        assert_eq!(
+            res,
+            [
+                (Some("image/jpeg"), b"jpeg".as_slice()),
+                (Some("image/png"), b"png"),
+                (Some("image/bmp"), b"bmp"),
+                (Some("image/png"), b"\x89PNG\r\n"),
+                (None, b"data"),
+            ]
+        );
+        assert!(
+            store
+                .pictures
+                .iter()
+                .all(|a| a.kind == PictureKind::FRONT_COVER)
+        );
+    }
+
+    #[test]
+    fn invalid_cover_image() {
+        // Text data type.
+        let d = file(&[bx(
+            b"covr",
+            &[data(13, b"first"), data(1, b"text"), data(14, b"last")]
+                .concat(),
+        )]);
+        assert!(read(&d, &Strict).is_err());
+
+        let store = read(&d, &Skip).unwrap();
+        let res: Vec<_> =
+            store.pictures.iter().map(|a| a.data.as_slice()).collect();
+        assert_eq!(res, [b"first".as_slice(), b"last"]);
+    }
+}
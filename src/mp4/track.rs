@@ -0,0 +1,660 @@
+use std::time::Duration;
+
+use crate::{
+    AudioProperties, Codec, Error, Result,
+    flac::streaminfo::Streaminfo,
+    mp4::boxtype,
+    trap::{Trap, TrapExt},
+};
+
+/// Data from track box (`trak`) that describe the audio.
+#[derive(Debug, Default)]
+pub struct Track {
+    /// Handler type from `mdia.hdlr`.
+    pub handler: u32,
+    pub timescale: u32,
+    pub duration: u64,
+    pub language: Option<String>,
+    /// The first sample entry from `stsd`.
+    pub entry: Option<SampleEntry>,
+}
+
+/// Audio sample entry within `stsd`.
+#[derive(Debug)]
+pub struct SampleEntry {
+    pub codec: Codec,
+    pub channels: u16,
+    pub sample_size: u16,
+    pub sample_rate: u32,
+    pub bitrate: Option<u32>,
+}
+
+/// Media header box (`mdhd`).
+pub struct MediaHeader {
+    pub timescale: u32,
+    pub duration: u64,
+    pub language: Option<String>,
+}
+
+impl Track {
+    /// Handler type of sound tracks.
+    pub const SOUND: u32 = u32::from_be_bytes(*b"soun");
+
+    pub fn is_sound(&self) -> bool {
+        self.handler == Self::SOUND
+    }
+
+    /// Length of the track from its media header.
+    pub fn length(&self) -> Option<Duration> {
+        // Duration with all bits set is unknown.
+        let unknown =
+            self.duration == u32::MAX as u64 || self.duration == u64::MAX;
+        (self.timescale != 0 && !unknown).then(|| {
+            Duration::from_secs_f64(
+                self.duration as f64 / self.timescale as f64,
+            )
+        })
+    }
+
+    pub fn properties(&self) -> AudioProperties {
+        let Some(e) = &self.entry else {
+            return AudioProperties {
+                language: self.language.clone(),
+                ..Default::default()
+            };
+        };
+
+        let mut res = AudioProperties::new(e.codec.clone());
+        res.sample_rate = Some(e.sample_rate).filter(|a| *a != 0);
+        res.channels = Some(e.channels).filter(|a| *a != 0);
+        // Sample size of lossy codecs is just a placeholder.
+        if res.lossless == Some(true) {
+            res.bits_per_sample = Some(e.sample_size).filter(|a| *a != 0);
+        }
+        res.bitrate = e.bitrate.filter(|a| *a != 0);
+        res.language = self.language.clone();
+        res
+    }
+}
+
+pub fn read_mdhd(d: &[u8], _: &impl Trap) -> Result<MediaHeader> {
+    // 0: version
+    // 1..4: flags
+    // version 0:
+    //   4..8: creation time
+    //   8..12: modification time
+    //   12..16: timescale
+    //   16..20: duration
+    //   20..22: language
+    // version 1:
+    //   4..12: creation time
+    //   12..20: modification time
+    //   20..24: timescale
+    //   24..32: duration
+    //   32..34: language
+    let (timescale, duration, lang) = match d.first() {
+        Some(0) if d.len() >= 22 => (
+            be_u32(&d[12..]),
+            be_u32(&d[16..]) as u64,
+            u16::from_be_bytes([d[20], d[21]]),
+        ),
+        Some(1) if d.len() >= 34 => (
+            be_u32(&d[20..]),
+            u64::from_be_bytes(d[24..32].try_into().unwrap()),
+            u16::from_be_bytes([d[32], d[33]]),
+        ),
+        Some(0 | 1) | None => return Err(Error::InvalidLength),
+        Some(_) => {
+            return Err(Error::Unsupported(
+                "Unsupported media header version. Max v1 is supported.",
+            ));
+        }
+    };
+
+    // Language is packed ISO-639-2/T code. Each letter has 5 bits and it is
+    // offset by 0x60.
+    let language: String = [10, 5, 0]
+        .iter()
+        .map(|s| (((lang >> s) & 0x1F) as u8 + 0x60) as char)
+        .collect();
+    let language = (lang != 0
+        && language != "und"
+        && language.chars().all(|c| c.is_ascii_lowercase()))
+    .then_some(language);
+
+    Ok(MediaHeader {
+        timescale,
+        duration,
+        language,
+    })
+}
+
+pub fn read_hdlr(d: &[u8], _: &impl Trap) -> Result<u32> {
+    // 0..4: full box
+    // 4..8: pre defined (component type in QuickTime)
+    // 8..12: handler type
+    if d.len() < 12 {
+        return Err(Error::InvalidLength);
+    }
+    Ok(be_u32(&d[8..]))
+}
+
+/// Read the first sample entry from `stsd` of sound track. Returns [`None`]
+/// if there are no entries.
+pub fn read_stsd(d: &[u8], trap: &impl Trap) -> Result<Option<SampleEntry>> {
+    // 0..4: full box
+    // 4..8: entry count
+    // 8..: entries
+    if d.len() < 8 {
+        return Err(Error::InvalidLength);
+    }
+    if be_u32(&d[4..]) == 0 {
+        return Ok(None);
+    }
+    let (format, d, _) = split_box(&d[8..])?;
+
+    // 0..6: reserved
+    // 6..8: data reference index
+    // 8..10: version (QuickTime)
+    // 10..12: revision level
+    // 12..16: vendor
+    // 16..18: channels
+    // 18..20: sample size
+    // 20..22: compression id
+    // 22..24: packet size
+    // 24..28: sample rate (16.16 fixed point)
+    if d.len() < 28 {
+        return Err(Error::InvalidLength);
+    }
+
+    let mut entry = SampleEntry {
+        codec: codec_from_format(format),
+        channels: u16::from_be_bytes([d[16], d[17]]),
+        sample_size: u16::from_be_bytes([d[18], d[19]]),
+        sample_rate: be_u32(&d[24..]) >> 16,
+        bitrate: None,
+    };
+
+    let children = match u16::from_be_bytes([d[8], d[9]]) {
+        // 28..32: samples per packet
+        // 32..36: bytes per packet
+        // 36..40: bytes per frame
+        // 40..44: bytes per sample
+        1 => 44,
+        // 28..32: size of struct
+        // 32..40: sample rate (f64)
+        // 40..44: channels
+        // 44..48: reserved
+        // 48..52: bits per channel
+        // 52..56: flags
+        // 56..60: bytes per packet
+        // 60..64: frames per packet
+        2 if d.len() >= 64 => {
+            let rate = f64::from_be_bytes(d[32..40].try_into().unwrap());
+            entry.sample_rate = rate as u32;
+            entry.channels = be_u32(&d[40..]).try_into().unwrap_or_default();
+            entry.sample_size =
+                be_u32(&d[48..]).try_into().unwrap_or_default();
+            64
+        }
+        _ => 28,
+    };
+
+    let mut d = d.get(children..).unwrap_or_default();
+    while d.len() >= 8 {
+        let (typ, data, rest) = split_box(d)?;
+        d = rest;
+        let res = match typ {
+            boxtype::ESDS if format == boxtype::MP4A => {
+                read_esds(data, &mut entry)
+            }
+            boxtype::ALAC if format == boxtype::ALAC => {
+                read_alac(data, &mut entry)
+            }
+            boxtype::DOPS if format == boxtype::OPUS => {
+                read_dops(data, &mut entry)
+            }
+            boxtype::DFLA if format == boxtype::FLAC => {
+                read_dfla(data, &mut entry)
+            }
+            _ => Ok(()),
+        };
+        trap.res(res)?;
+    }
+
+    Ok(Some(entry))
+}
+
+fn codec_from_format(format: u32) -> Codec {
+    match &format.to_be_bytes() {
+        b"mp4a" => Codec::Aac,
+        b"alac" => Codec::Alac,
+        b"ac-3" => Codec::Ac3,
+        b"Opus" => Codec::Opus,
+        b"fLaC" => Codec::Flac,
+        b".mp3" => Codec::Mp3,
+        b"lpcm" | b"twos" | b"sowt" | b"in24" | b"in32" | b"raw " => {
+            Codec::Pcm
+        }
+        b"fl32" | b"fl64" => Codec::FloatPcm,
+        b"alaw" => Codec::ALaw,
+        b"ulaw" => Codec::MuLaw,
+        f => Codec::Other(String::from_utf8_lossy(f).into_owned()),
+    }
+}
+
+fn read_esds(d: &[u8], entry: &mut SampleEntry) -> Result<()> {
+    // 0..4: full box
+    // 4..: ES descriptor
+    const ES_DESCRIPTOR: u8 = 3;
+    const DECODER_CONFIG: u8 = 4;
+    const DECODER_SPECIFIC: u8 = 5;
+
+    let (tag, mut d, _) = split_descriptor(d.get(4..).unwrap_or_default())?;
+    if tag != ES_DESCRIPTOR || d.len() < 3 {
+        return Err(Error::InvalidLength);
+    }
+
+    // 0..2: ES id
+    // 2: flags
+    //   0x80: stream dependence (2 bytes)
+    //   0x40: URL (length and URL)
+    //   0x20: OCR stream (2 bytes)
+    let flags = d[2];
+    d = &d[3..];
+    if flags & 0x80 != 0 {
+        d = d.get(2..).ok_or(Error::InvalidLength)?;
+    }
+    if flags & 0x40 != 0 {
+        let len = *d.first().ok_or(Error::InvalidLength)? as usize;
+        d = d.get(len + 1..).ok_or(Error::InvalidLength)?;
+    }
+    if flags & 0x20 != 0 {
+        d = d.get(2..).ok_or(Error::InvalidLength)?;
+    }
+
+    // 0: object type indication
+    // 1: stream type
+    // 2..5: buffer size
+    // 5..9: maximum bitrate
+    // 9..13: average bitrate
+    // 13..: decoder specific info
+    let (tag, d, _) = split_descriptor(d)?;
+    if tag != DECODER_CONFIG || d.len() < 13 {
+        return Err(Error::InvalidLength);
+    }
+    entry.bitrate = Some(be_u32(&d[9..]));
+    entry.codec = match d[0] {
+        0x40 => Codec::Aac,
+        0x66..=0x68 => Codec::Aac,
+        0x69 | 0x6B => Codec::Mp3,
+        0xA5 => Codec::Ac3,
+        0xAD => Codec::Opus,
+        t => Codec::Other(format!("MP4 object type 0x{t:02X}")),
+    };
+
+    if d[0] == 0x40
+        && let Ok((DECODER_SPECIFIC, d, _)) = split_descriptor(&d[13..])
+    {
+        read_audio_specific_config(d, entry)?;
+    }
+
+    Ok(())
+}
+
+/// Audio object type of spectral band replication (HE-AAC).
+const SBR: u32 = 5;
+/// Audio object type of parametric stereo (HE-AACv2).
+const PS: u32 = 29;
+
+/// Read MPEG-4 `AudioSpecificConfig`.
+fn read_audio_specific_config(
+    d: &[u8],
+    entry: &mut SampleEntry,
+) -> Result<()> {
+    let mut bits = Bits { d, pos: 0 };
+    let mut aot = read_object_type(&mut bits)?;
+    let mut rate = read_sample_rate(&mut bits)?;
+    let channels = bits.read(4)?;
+    // Explicitly signaled SBR is followed by the output sample rate.
+    if aot == SBR || aot == PS {
+        rate = read_sample_rate(&mut bits)?;
+    } else if matches!(aot, 1..=4)
+        && channels != 0
+        && let Ok(Some((ext, r))) = read_sync_extension(&mut bits)
+    {
+        aot = ext;
+        rate = r;
+    }
+
+    entry.sample_rate = rate;
+    entry.channels = match channels {
+        0 => entry.channels,
+        // Parametric stereo has mono core.
+        1 if aot == PS => 2,
+        7 => 8,
+        c => c as u16,
+    };
+    entry.codec = match aot {
+        SBR => Codec::HeAac,
+        PS => Codec::HeAacV2,
+        32 => Codec::Mp1,
+        33 => Codec::Mp2,
+        34 => Codec::Mp3,
+        1..=4 | 6 | 7 | 17 | 19..=23 | 42 => Codec::Aac,
+        t => Codec::Other(format!("MPEG-4 audio object type {t}")),
+    };
+
+    Ok(())
+}
+
+/// Read backward compatible signaling of SBR and PS after `GASpecificConfig`
+/// of AAC. Returns the extension object type and the output sample rate.
+fn read_sync_extension(bits: &mut Bits) -> Result<Option<(u32, u32)>> {
+    // GASpecificConfig without program config element:
+    // 1: frame length flag
+    // 1: depends on core coder (followed by 14 bits of core coder delay)
+    // 1: extension flag (followed by 1 bit of extension flag 3)
+    bits.read(1)?;
+    if bits.read(1)? == 1 {
+        bits.read(14)?;
+    }
+    if bits.read(1)? == 1 {
+        bits.read(1)?;
+    }
+
+    // 11: sync extension type 0x2B7
+    // 5: extension audio object type
+    // 1: SBR present flag
+    // 4: extension sample rate
+    if bits.remaining() < 16
+        || bits.read(11)? != 0x2B7
+        || read_object_type(bits)? != SBR
+        || bits.read(1)? == 0
+    {
+        return Ok(None);
+    }
+    let rate = read_sample_rate(bits)?;
+
+    // 11: sync extension type 0x548
+    // 1: PS present flag
+    let ps = bits.remaining() >= 12
+        && bits.read(11)? == 0x548
+        && bits.read(1)? == 1;
+
+    Ok(Some((if ps { PS } else { SBR }, rate)))
+}
+
+fn read_object_type(bits: &mut Bits) -> Result<u32> {
+    match bits.read(5)? {
+        31 => Ok(32 + bits.read(6)?),
+        t => Ok(t),
+    }
+}
+
+fn read_sample_rate(bits: &mut Bits) -> Result<u32> {
+    const SAMPLE_RATES: [u32; 13] = [
+        96000, 88200, 64000, 48000, 44100, 32000, 24000, 22050, 16000, 12000,
+        11025, 8000, 7350,
+    ];
+
+    match bits.read(4)? {
+        15 => bits.read(24),
+        i => SAMPLE_RATES
+            .get(i as usize)
+            .copied()
+            .ok_or(Error::InvalidLength),
+    }
+}
+
+fn read_alac(d: &[u8], entry: &mut SampleEntry) -> Result<()> {
+    // 0..4: full box
+    // 4..8: frame length
+    // 8: compatible version
+    // 9: bit depth
+    // 10: pb
+    // 11: mb
+    // 12: kb
+    // 13: channels
+    // 14..16: max run
+    // 16..20: max frame bytes
+    // 20..24: average bitrate
+    // 24..28: sample rate
+    if d.len() < 28 {
+        return Err(Error::InvalidLength);
+    }
+    entry.sample_size = d[9] as u16;
+    entry.channels = d[13] as u16;
+    entry.bitrate = Some(be_u32(&d[20..]));
+    entry.sample_rate = be_u32(&d[24..]);
+    Ok(())
+}
+
+fn read_dops(d: &[u8], entry: &mut SampleEntry) -> Result<()> {
+    // 0: version
+    // 1: channels
+    // 2..4: pre-skip
+    // 4..8: input sample rate
+    if d.len() < 8 {
+        return Err(Error::InvalidLength);
+    }
+    entry.channels = d[1] as u16;
+    // Opus is always decoded at 48 kHz.
+    entry.sample_rate = 48000;
+    Ok(())
+}
+
+fn read_dfla(d: &[u8], entry: &mut SampleEntry) -> Result<()> {
+    // 0..4: full box
+    // 4..8: metadata block header of STREAMINFO
+    // 8..42: STREAMINFO
+    let Some(d) = d.get(8..42) else {
+        return Err(Error::InvalidLength);
+    };
+    let si = Streaminfo::from_bytes(d.try_into().unwrap());
+    entry.sample_rate = si.sample_rate;
+    entry.channels = si.channels as u16;
+    entry.sample_size = si.bits_per_sample as u16;
+    Ok(())
+}
+
+/// Split the first box in the data. Returns its type, content and the
+/// remaining data.
+fn split_box(d: &[u8]) -> Result<(u32, &[u8], &[u8])> {
+    if d.len() < 8 {
+        return Err(Error::InvalidLength);
+    }
+    let size = match be_u32(d) {
+        0 => d.len(),
+        s => s as usize,
+    };
+    if size < 8 || size > d.len() {
+        return Err(Error::InvalidLength);
+    }
+    Ok((be_u32(&d[4..]), &d[8..size], &d[size..]))
+}
+
+/// Split the first MPEG-4 descriptor. Returns its tag, content and the
+/// remaining data.
+fn split_descriptor(d: &[u8]) -> Result<(u8, &[u8], &[u8])> {
+    let (&tag, mut d) = d.split_first().ok_or(Error::InvalidLength)?;
+    // Size has up to 4 bytes with 7 bits each. The highest bit is set if
+    // there are more bytes.
+    let mut size = 0;
+    for _ in 0..4 {
+        let (&b, rest) = d.split_first().ok_or(Error::InvalidLength)?;
+        d = rest;
+        size = (size << 7) | (b & 0x7F) as usize;
+        if b & 0x80 == 0 {
+            break;
+        }
+    }
+    if size > d.len() {
+        return Err(Error::InvalidLength);
+    }
+    Ok((tag, &d[..size], &d[size..]))
+}
+
+fn be_u32(d: &[u8]) -> u32 {
+    u32::from_be_bytes(d[..4].try_into().unwrap())
+}
+
+/// Reader of big endian bit fields.
+struct Bits<'a> {
+    d: &'a [u8],
+    pos: usize,
+}
+
+impl Bits<'_> {
+    /// Number of bits that haven't been read yet.
+    fn remaining(&self) -> usize {
+        (self.d.len() * 8).saturating_sub(self.pos)
+    }
+
+    fn read(&mut self, cnt: usize) -> Result<u32> {
+        let mut res = 0;
+        for _ in 0..cnt {
+            let b = self.d.get(self.pos / 8).ok_or(Error::InvalidLength)?;
+            res = (res << 1) | ((b >> (7 - self.pos % 8)) & 1) as u32;
+            self.pos += 1;
+        }
+        Ok(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    /// Convert string of binary digits to bytes. Spaces are ignored and the
+    /// last byte is padded with zeros.
+    fn bits(s: &str) -> Vec<u8> {
+        let s: Vec<_> = s.bytes().filter(|a| *a != b' ').collect();
+        s.chunks(8)
+            .map(|c| {
+                c.iter()
+                    .chain([b'0'; 8].iter())
+                    .take(8)
+                    .fold(0, |acc, b| acc << 1 | (b - b'0'))
+            })
+            .collect()
+    }
+
+    fn descriptor(tag: u8, d: &[u8]) -> Vec<u8> {
+        let mut res = vec![tag, d.len() as u8];
+        res.extend_from_slice(d);
+        res
+    }
+
+    fn mp4_box(typ: &[u8; 4], d: &[u8]) -> Vec<u8> {
+        let mut res = (d.len() as u32 + 8).to_be_bytes().to_vec();
+        res.extend(typ);
+        res.extend_from_slice(d);
+        res
+    }
+
+    /// Create `esds` with AAC decoder config with average bitrate 128 kbps
+    /// and the given `AudioSpecificConfig`.
+    fn esds(asc: &[u8]) -> Vec<u8> {
+        let mut config = vec![0x40, 0x15, 0, 0, 0];
+        config.extend(160000_u32.to_be_bytes());
+        config.extend(128000_u32.to_be_bytes());
+        config.extend(descriptor(5, asc));
+        let mut es = vec![0, 1, 0];
+        es.extend(descriptor(4, &config));
+        let mut res = vec![0; 4];
+        res.extend(descriptor(3, &es));
+        res
+    }
+
+    /// Create `stsd` with `mp4a` entry with 2 channels, 16 bits and 44100
+    /// Hz and the given `AudioSpecificConfig`.
+    fn stsd(asc: &[u8]) -> Vec<u8> {
+        let mut entry = vec![0; 16];
+        entry.extend(b"\0\x02\0\x10\0\0\0\0\xAC\x44\0\0");
+        entry.extend(mp4_box(b"esds", &esds(asc)));
+        let mut res = b"\0\0\0\0\0\0\0\x01".to_vec();
+        res.extend(mp4_box(b"mp4a", &entry));
+        res
+    }
+
+    fn entry(asc: &str) -> SampleEntry {
+        read_stsd(&stsd(&bits(asc)), &Strict).unwrap().unwrap()
+    }
+
+    #[test]
+    fn aac_lc() {
+        // AAC LC, 44100 Hz, 2 channels
+        let e = entry("00010 0100 0010 000");
+        assert_eq!(e.codec, Codec::Aac);
+        assert_eq!(e.sample_rate, 44100);
+        assert_eq!(e.channels, 2);
+        assert_eq!(e.bitrate, Some(128000));
+    }
+
+    #[test]
+    fn explicit_he_aac() {
+        // SBR, 24000 Hz, 2 channels, 48000 Hz output, AAC LC
+        let e = entry("00101 0110 0010 0011 00010");
+        assert_eq!(e.codec, Codec::HeAac);
+        assert_eq!(e.sample_rate, 48000);
+        assert_eq!(e.channels, 2);
+
+        // PS, 24000 Hz, 1 channel, 48000 Hz output, AAC LC
+        let e = entry("11101 0110 0001 0011 00010");
+        assert_eq!(e.codec, Codec::HeAacV2);
+        assert_eq!(e.sample_rate, 48000);
+        assert_eq!(e.channels, 2);
+    }
+
+    #[test]
+    fn implicit_he_aac() {
+        // AAC LC, 24000 Hz, 2 channels, GASpecificConfig, sync extension
+        // with SBR and 48000 Hz output
+        let asc = "00010 0110 0010 000 01010110111 00101 1 0011";
+        let e = entry(asc);
+        assert_eq!(e.codec, Codec::HeAac);
+        assert_eq!(e.sample_rate, 48000);
+
+        // With parametric stereo and mono core.
+        let asc = "00010 0110 0001 000 01010110111 00101 1 0011 10101001000 1";
+        let e = entry(asc);
+        assert_eq!(e.codec, Codec::HeAacV2);
+        assert_eq!(e.sample_rate, 48000);
+        assert_eq!(e.channels, 2);
+    }
+
+    #[test]
+    fn invalid_audio_specific_config() {
+        // Invalid sample rate index.
+        let d = stsd(&bits("00010 1101 0010 000"));
+        assert!(read_stsd(&d, &Strict).is_err());
+
+        // The values from the sample entry are kept.
+        let e = read_stsd(&d, &Skip).unwrap().unwrap();
+        assert_eq!(e.codec, Codec::Aac);
+        assert_eq!(e.sample_rate, 44100);
+        assert_eq!(e.channels, 2);
+        assert_eq!(e.bitrate, Some(128000));
+    }
+
+    #[test]
+    fn media_header() {
+        // Version 0, timescale 44100, duration 88200, language `eng`.
+        let mut d = vec![0; 12];
+        d.extend(44100_u32.to_be_bytes());
+        d.extend(88200_u32.to_be_bytes());
+        d.extend(b"\x15\xC7\0\0");
+        let h = read_mdhd(&d, &Strict).unwrap();
+        assert_eq!(h.timescale, 44100);
+        assert_eq!(h.duration, 88200);
+        assert_eq!(h.language.as_deref(), Some("eng"));
+
+        // Undetermined language.
+        d[20..22].copy_from_slice(b"\x55\xC4");
+        assert_eq!(read_mdhd(&d, &Strict).unwrap().language, None);
+
+        assert!(read_mdhd(&d[..21], &Strict).is_err());
+    }
+}
@@ -0,0 +1,14 @@
+/// Options for writing mp4 metadata.
+#[derive(Debug, Clone)]
+pub struct WriteOptions {
+    /// Size of `free` box that will be added after `moov` if it doesn't fit
+    /// to its original place. Free space allows future modifications of the
+    /// metadata without rewriting the whole file.
+    pub padding: usize,
+}
+
+impl Default for WriteOptions {
+    fn default() -> Self {
+        Self { padding: 1024 }
+    }
+}
@@ -0,0 +1,540 @@
+use std::{
+    fs::File,
+    io::{BufReader, SeekFrom},
+    path::Path,
+};
+
+use crate::{
+    DataType, Error, Picture, PictureKind, Result, TagSource, TagSourceExt,
+    bread::Bread,
+    file_edit::{self, Edit},
+    merged_source::{Merged, SourceData},
+    mp4::{
+        WriteOptions, box_tree::BoxTree, boxtype, from_seek,
+        full_box::FullBox, mp4_box::Mp4Box,
+    },
+    trap::Skip,
+};
+
+/// Items in `ilst` that are written from [`TagSource`] with the data types
+/// that they contain. Item is replaced only if the source replaces any of its
+/// data types.
+const SOURCE_ITEMS: &[(u32, &[DataType])] = &[
+    (boxtype::NAM, &[DataType::Title]),
+    (boxtype::ART, &[DataType::Artists]),
+    (boxtype::ALB, &[DataType::Album]),
+    (boxtype::AART, &[DataType::AlbumArtist]),
+    (boxtype::TRK, &[DataType::Track, DataType::TrackCount]),
+    (boxtype::TRKN, &[DataType::Track, DataType::TrackCount]),
+    (boxtype::DISK, &[DataType::Disc, DataType::DiscCount]),
+    (boxtype::GNRE, &[DataType::Genres]),
+    (boxtype::GEN, &[DataType::Genres]),
+    (
+        boxtype::DAY,
+        &[DataType::Year, DataType::Date, DataType::Time],
+    ),
+    (boxtype::CMT, &[DataType::Comments]),
+    (boxtype::COVR, &[DataType::Picture(PictureKind::all())]),
+];
+
+/// Write the data from the source to `moov.udta.meta.ilst` of the mp4 file.
+/// Items in `ilst` with data that the source doesn't provide or remove are
+/// kept.
+///
+/// If the new `moov` fits into the space of the old `moov` and the
+/// neighbouring `free` boxes, the file is modified in place. Otherwise the
+/// file is rewritten through temporary file and the chunk offsets in `stco`
+/// and `co64` are updated.
+pub fn write_file(
+    f: impl AsRef<Path>,
+    src: &impl TagSource,
+    opts: &WriteOptions,
+) -> Result<()> {
+    let f = f.as_ref();
+    let mut r = Bread::new(BufReader::new(File::open(f)?));
+    let len = r.seek(SeekFrom::End(0))?;
+    r.seek(SeekFrom::Start(0))?;
+
+    // (position, type, size) of the top level boxes.
+    let mut boxes = vec![];
+    let mut pos = 0;
+    while pos + 8 <= len {
+        r.seek(SeekFrom::Start(pos))?;
+        let bx: Mp4Box = r.get()?;
+        let size = bx.size_total.unwrap_or(len - pos);
+        if size < 8 {
+            return Err(Error::InvalidLength);
+        }
+        boxes.push((pos, bx.boxtype, size));
+        pos += size;
+    }
+
+    if boxes.first().map(|a| a.1) != Some(boxtype::FTYP) {
+        return Err(Error::NoTag);
+    }
+
+    let Some(moov_idx) = boxes.iter().position(|a| a.1 == boxtype::MOOV)
+    else {
+        return Err(Error::NoTag);
+    };
+
+    // Size of the box is already resolved also if it extends to the end of
+    // the file.
+    let (moov_pos, _, moov_size) = boxes[moov_idx];
+    r.seek(SeekFrom::Start(moov_pos))?;
+    r.get::<Mp4Box>()?;
+    let header = r.seek(SeekFrom::Current(0))? - moov_pos;
+    let data = r.read_exact_owned((moov_size - header) as usize)?;
+    let mut moov = BoxTree::parse(boxtype::MOOV, &data)?;
+
+    // Values that cannot be read are not written back, but the items are
+    // kept.
+    let mut old = SourceData::default();
+    _ = from_seek(r.get_mut(), &mut old, &Skip);
+    update_ilst(&mut moov, src, &old);
+
+    let is_free =
+        |i: usize| matches!(boxes[i].1, boxtype::FREE | boxtype::SKIP);
+    let mut first = moov_idx;
+    while first > 0 && is_free(first - 1) {
+        first -= 1;
+    }
+    let mut last = moov_idx;
+    while last + 1 < boxes.len() && is_free(last + 1) {
+        last += 1;
+    }
+
+    let start = boxes[first].0;
+    let end = boxes[last].0 + boxes[last].2;
+    let space = end - start;
+
+    let size = moov.size();
+    let free = if size == space {
+        0
+    } else if size + 8 <= space {
+        space - size
+    } else {
+        // The free box has at least 8 bytes, so if the gap after smaller moov
+        // is too small for it, the moov is moved as if it didn't fit.
+        let free = if opts.padding == 0 && size > space {
+            0
+        } else {
+            opts.padding.max(8) as u64
+        };
+        // All data after `moov` will be moved.
+        patch_offsets(&mut moov, end, size + free - space)?;
+        free
+    };
+
+    let mut data = Vec::with_capacity((size + free) as usize);
+    moov.write_to(&mut data);
+    if free != 0 {
+        data.extend((free as u32).to_be_bytes());
+        data.extend(boxtype::FREE.to_be_bytes());
+        data.resize(data.len() + free as usize - 8, 0);
+    }
+
+    file_edit::apply(f, &[Edit::new(start..end, data)])
+}
+
+fn update_ilst(moov: &mut BoxTree, src: &impl TagSource, old: &SourceData) {
+    let udta = moov.child_or(boxtype::UDTA, || {
+        BoxTree::new(boxtype::UDTA, vec![], vec![])
+    });
+    let meta = udta.child_or(boxtype::META, || {
+        let mut hdlr = vec![0; 8];
+        hdlr.extend(b"mdirappl");
+        hdlr.resize(hdlr.len() + 9, 0);
+        BoxTree::new(
+            boxtype::META,
+            vec![0; 4],
+            vec![BoxTree::new(boxtype::HDLR, hdlr, vec![])],
+        )
+    });
+    // Free space within meta is not needed, free box after moov is used
+    // instead.
+    meta.children
+        .retain(|a| !matches!(a.boxtype, boxtype::FREE | boxtype::SKIP));
+    let ilst = meta.child_or(boxtype::ILST, || {
+        BoxTree::new(boxtype::ILST, vec![], vec![])
+    });
+
+    let write = |typ| {
+        SOURCE_ITEMS
+            .iter()
+            .filter(|a| a.0 == typ)
+            .any(|a| a.1.iter().any(|t| src.replaces(*t)))
+    };
+    ilst.children.retain(|a| !write(a.boxtype));
+    let items = &mut ilst.children;
+    let src = Merged::new(src, old);
+
+    let mut text = |typ: u32, s: Option<&str>| {
+        if write(typ)
+            && let Some(s) = s.filter(|a| !a.is_empty())
+        {
+            items.push(item(typ, vec![data(FullBox::TEXT, s.as_bytes())]));
+        }
+    };
+
+    text(boxtype::NAM, src.title());
+    text(boxtype::ART, Some(&src.artists().join(", ")));
+    text(boxtype::ALB, src.album());
+    text(boxtype::AART, src.album_artist());
+    text(boxtype::GEN, Some(&src.genres().join(", ")));
+    text(boxtype::DAY, src.date_time_string().as_deref());
+    let comments: Vec<_> =
+        src.comments().iter().map(|a| a.value.as_str()).collect();
+    text(boxtype::CMT, Some(&comments.join("\n")));
+
+    if write(boxtype::TRKN)
+        && let Some(t) = src.track()
+    {
+        let mut d = vec![0; 2];
+        d.extend(num16(t));
+        d.extend(num16(src.track_count().unwrap_or_default()));
+        d.extend([0; 2]);
+        items.push(item(boxtype::TRKN, vec![data(FullBox::BINARY, &d)]));
+    }
+
+    if write(boxtype::DISK)
+        && let Some(n) = src.disc()
+    {
+        let mut d = vec![0; 2];
+        d.extend(num16(n));
+        d.extend(num16(src.disc_count().unwrap_or_default()));
+        items.push(item(boxtype::DISK, vec![data(FullBox::BINARY, &d)]));
+    }
+
+    let covers: Vec<_> = src
+        .pictures()
+        .iter()
+        .filter(|a| !a.is_uri)
+        .map(|p| data(image_type(p), &p.data))
+        .collect();
+    if write(boxtype::COVR) && !covers.is_empty() {
+        items.push(item(boxtype::COVR, covers));
+    }
+}
+
+fn item(typ: u32, data: Vec<BoxTree>) -> BoxTree {
+    BoxTree::new(typ, vec![], data)
+}
+
+fn data(typ: u32, d: &[u8]) -> BoxTree {
+    let mut res = typ.to_be_bytes().to_vec();
+    // locale
+    res.extend([0; 4]);
+    res.extend_from_slice(d);
+    BoxTree::new(boxtype::DATA, res, vec![])
+}
+
+fn num16(n: u32) -> [u8; 2] {
+    (n.min(u16::MAX as u32) as u16).to_be_bytes()
+}
+
+fn image_type(p: &Picture) -> u32 {
+    match p.mime.as_deref() {
+        Some("image/jpeg" | "image/jpg") => FullBox::JPEG,
+        Some("image/png") => FullBox::PNG,
+        Some("image/bmp") => FullBox::BMP,
+        _ if p.data.starts_with(b"\x89PNG") => FullBox::PNG,
+        _ if p.data.starts_with(b"BM") => FullBox::BMP,
+        _ => FullBox::JPEG,
+    }
+}
+
+/// Move all chunk offsets that point at or after `from` by `delta`.
+fn patch_offsets(moov: &mut BoxTree, from: u64, delta: u64) -> Result<()> {
+    moov.for_each_mut(&mut |b| {
+        let width = match b.boxtype {
+            boxtype::STCO => 4,
+            boxtype::CO64 => 8,
+            _ => return Ok(()),
+        };
+
+        // 0..4: full box
+        // 4..8: entry count
+        if b.data.len() < 8 {
+            return Err(Error::InvalidLength);
+        }
+        let cnt = u32::from_be_bytes(b.data[4..8].try_into().unwrap());
+        let entries = &mut b.data[8..];
+        if entries.len() / width < cnt as usize {
+            return Err(Error::InvalidLength);
+        }
+
+        for e in entries.chunks_exact_mut(width).take(cnt as usize) {
+            if width == 4 {
+                let o = u32::from_be_bytes(e.try_into().unwrap()) as u64;
+                if o >= from {
+                    let o = u32::try_from(o + delta).map_err(|_| {
+                        Error::Unsupported("Chunk offset doesn't fit to stco.")
+                    })?;
+                    e.copy_from_slice(&o.to_be_bytes());
+                }
+            } else {
+                let o = u64::from_be_bytes(e.try_into().unwrap());
+                if o >= from {
+                    e.copy_from_slice(&(o + delta).to_be_bytes());
+                }
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs, io::Cursor, process};
+
+    use super::*;
+    use crate::{DataType, TagStore, mp4, trap::Strict};
+
+    /// Data of the chunks in `mdat`. The first chunk is referenced from
+    /// `stco` and the second from `co64`.
+    const CHUNKS: [&[u8]; 2] = [b"first chunk data", b"second chunk"];
+
+    struct Src(&'static str);
+
+    impl TagSource for Src {
+        fn title(&self) -> Option<&str> {
+            Some(self.0)
+        }
+    }
+
+    /// Source that provides only the track number and removes title.
+    struct TrackSrc(u32, Option<u32>);
+
+    impl TagSource for TrackSrc {
+        fn track(&self) -> Option<u32> {
+            Some(self.0)
+        }
+
+        fn track_count(&self) -> Option<u32> {
+            self.1
+        }
+
+        fn removes(&self, typ: DataType) -> bool {
+            typ == DataType::Title
+        }
+    }
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        track: Option<u32>,
+        track_count: Option<u32>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Title | DataType::Track | DataType::TrackCount
+            )
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn set_track(&mut self, track: u32) {
+            self.track = Some(track);
+        }
+
+        fn set_track_count(&mut self, cnt: u32) {
+            self.track_count = Some(cnt);
+        }
+    }
+
+    fn bx(typ: &[u8; 4], d: &[u8]) -> Vec<u8> {
+        let mut res = ((d.len() + 8) as u32).to_be_bytes().to_vec();
+        res.extend(typ);
+        res.extend_from_slice(d);
+        res
+    }
+
+    fn trak(offsets: Vec<u8>) -> Vec<u8> {
+        bx(b"trak", &bx(b"mdia", &bx(b"minf", &bx(b"stbl", &offsets))))
+    }
+
+    fn moov(offsets: [u64; 2], title: &str) -> Vec<u8> {
+        let mut stco = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        stco.extend((offsets[0] as u32).to_be_bytes());
+        let mut co64 = vec![0, 0, 0, 0, 0, 0, 0, 1];
+        co64.extend(offsets[1].to_be_bytes());
+
+        let mut hdlr = vec![0; 8];
+        hdlr.extend(b"mdirappl");
+        hdlr.resize(hdlr.len() + 9, 0);
+        let mut nam = vec![0, 0, 0, 1, 0, 0, 0, 0];
+        nam.extend(title.as_bytes());
+        let ilst = bx(b"ilst", &bx(b"\xa9nam", &bx(b"data", &nam)));
+        let mut meta = vec![0; 4];
+        meta.extend(bx(b"hdlr", &hdlr));
+        meta.extend(ilst);
+
+        let mut res = trak(bx(b"stco", &stco));
+        res.extend(trak(bx(b"co64", &co64)));
+        res.extend(bx(b"udta", &bx(b"meta", &meta)));
+        bx(b"moov", &res)
+    }
+
+    /// Create mp4 file with `moov` before or after `mdat`. If `size0` is
+    /// set, `moov` is the last box and its size is 0.
+    fn file(title: &str, moov_first: bool, size0: bool) -> Vec<u8> {
+        let mut res = bx(b"ftyp", b"M4A \0\0\0\0M4A mp42isom");
+        // Size of moov doesn't depend on the offsets.
+        let moov_len = moov([0, 0], title).len();
+        let mdat_pos = res.len() + if moov_first { moov_len } else { 0 } + 8;
+        let offsets = [mdat_pos as u64, (mdat_pos + CHUNKS[0].len()) as u64];
+
+        let moov = moov(offsets, title);
+        let mdat = bx(b"mdat", &CHUNKS.concat());
+        let moov_pos;
+        if moov_first {
+            moov_pos = res.len();
+            res.extend(moov);
+            res.extend(mdat);
+        } else {
+            res.extend(mdat);
+            moov_pos = res.len();
+            res.extend(moov);
+        }
+
+        if size0 {
+            res[moov_pos..moov_pos + 4].fill(0);
+        }
+        res
+    }
+
+    /// Get the chunk offsets from `stco` and `co64` of the file.
+    fn chunk_offsets(d: &[u8]) -> Vec<u64> {
+        let mut pos = 0;
+        let mut moov = None;
+        while pos < d.len() {
+            let size = u32::from_be_bytes(d[pos..pos + 4].try_into().unwrap());
+            let size = size as usize;
+            if &d[pos + 4..pos + 8] == b"moov" {
+                let data = &d[pos + 8..pos + size];
+                moov = Some(BoxTree::parse(boxtype::MOOV, data).unwrap());
+            }
+            pos += size;
+        }
+
+        let mut res = vec![];
+        moov.unwrap()
+            .for_each_mut(&mut |b| {
+                match b.boxtype {
+                    boxtype::STCO => res.push(u32::from_be_bytes(
+                        b.data[8..12].try_into().unwrap(),
+                    ) as u64),
+                    boxtype::CO64 => res.push(u64::from_be_bytes(
+                        b.data[8..16].try_into().unwrap(),
+                    )),
+                    _ => {}
+                }
+                Ok(())
+            })
+            .unwrap();
+        res
+    }
+
+    /// Write the title to copy of the file. Check that the title is read
+    /// back and that the chunk offsets point to the original chunk data.
+    /// Returns the new file.
+    fn round_trip(
+        name: &str,
+        data: &[u8],
+        title: &'static str,
+        padding: usize,
+    ) -> Vec<u8> {
+        let path = env::temp_dir()
+            .join(format!("ratag-mp4-{}-{name}.m4a", process::id()));
+        fs::write(&path, data).unwrap();
+        let res = write_file(&path, &Src(title), &WriteOptions { padding });
+        let out = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        res.unwrap();
+        let out = out.unwrap();
+
+        let mut store = Store::default();
+        mp4::from_seek(Cursor::new(&out), &mut store, &Strict).unwrap();
+        assert_eq!(store.title.as_deref(), Some(title));
+
+        let offsets = chunk_offsets(&out);
+        assert_eq!(offsets.len(), CHUNKS.len());
+        for (o, c) in offsets.into_iter().zip(CHUNKS) {
+            assert_eq!(&out[o as usize..o as usize + c.len()], c);
+        }
+
+        out
+    }
+
+    const LONG: &str = "Title that is much longer than the original title";
+
+    #[test]
+    fn moov_grows_before_mdat() {
+        let data = file("T", true, false);
+        let out = round_trip("grow-before", &data, LONG, 1024);
+        assert!(out.len() > data.len() + 1024);
+    }
+
+    #[test]
+    fn moov_grows_after_mdat() {
+        let data = file("T", false, false);
+        let out = round_trip("grow-after", &data, LONG, 1024);
+        assert!(out.len() > data.len() + 1024);
+    }
+
+    #[test]
+    fn moov_shrinks_before_mdat() {
+        let data = file(LONG, true, false);
+        let out = round_trip("shrink-before", &data, "T", 1024);
+        assert_eq!(out.len(), data.len());
+    }
+
+    #[test]
+    fn moov_shrinks_after_mdat() {
+        let data = file(LONG, false, false);
+        let out = round_trip("shrink-after", &data, "T", 1024);
+        assert_eq!(out.len(), data.len());
+    }
+
+    #[test]
+    fn moov_shrinks_less_than_free_box() {
+        // The gap of 3 bytes is too small for free box.
+        let data = file("Tabc", true, false);
+        let out = round_trip("shrink-gap", &data, "T", 0);
+        assert_eq!(out.len(), data.len() - 3 + 8);
+    }
+
+    #[test]
+    fn moov_with_size_zero() {
+        let data = file("T", false, true);
+        round_trip("size0", &data, LONG, 0);
+    }
+
+    #[test]
+    fn items_are_merged_and_removed() {
+        let path = env::temp_dir()
+            .join(format!("ratag-mp4-{}-merge.m4a", process::id()));
+        fs::write(&path, file("T", true, false)).unwrap();
+        let opts = WriteOptions { padding: 0 };
+        let first = write_file(&path, &TrackSrc(3, Some(12)), &opts);
+        let second = write_file(&path, &TrackSrc(5, None), &opts);
+        let out = fs::read(&path);
+        let _ = fs::remove_file(&path);
+        first.unwrap();
+        second.unwrap();
+
+        let mut store = Store::default();
+        mp4::from_seek(Cursor::new(out.unwrap()), &mut store, &Strict)
+            .unwrap();
+        assert_eq!(store.title, None);
+        assert_eq!(store.track, Some(5));
+        assert_eq!(store.track_count, Some(12));
+    }
+}
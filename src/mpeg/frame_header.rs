@@ -0,0 +1,233 @@
+/// Version of MPEG audio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Version {
+    /// MPEG-1
+    Mpeg1,
+    /// MPEG-2
+    Mpeg2,
+    /// MPEG-2.5 (unofficial extension of MPEG-2 for low sample rates)
+    Mpeg25,
+}
+
+/// Layer of MPEG audio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layer {
+    /// Layer I
+    Layer1,
+    /// Layer II
+    Layer2,
+    /// Layer III
+    Layer3,
+}
+
+/// Channel mode of MPEG audio.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChannelMode {
+    /// Stereo
+    Stereo,
+    /// Joint stereo
+    JointStereo,
+    /// Two independent mono channels.
+    DualChannel,
+    /// Single channel.
+    Mono,
+}
+
+/// Header of single MPEG audio frame.
+#[derive(Debug, Copy, Clone)]
+pub struct FrameHeader {
+    pub version: Version,
+    pub layer: Layer,
+    /// Bitrate in bits per second.
+    pub bitrate: u32,
+    pub sample_rate: u32,
+    pub padding: bool,
+    pub channel_mode: ChannelMode,
+}
+
+// Bitrates in kbps. The first index is free format and the last index is
+// invalid, so they are not included.
+const BITRATES_V1_L1: [u16; 14] = [
+    32, 64, 96, 128, 160, 192, 224, 256, 288, 320, 352, 384, 416, 448,
+];
+const BITRATES_V1_L2: [u16; 14] = [
+    32, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320, 384,
+];
+const BITRATES_V1_L3: [u16; 14] = [
+    32, 40, 48, 56, 64, 80, 96, 112, 128, 160, 192, 224, 256, 320,
+];
+const BITRATES_V2_L1: [u16; 14] = [
+    32, 48, 56, 64, 80, 96, 112, 128, 144, 160, 176, 192, 224, 256,
+];
+const BITRATES_V2_L23: [u16; 14] =
+    [8, 16, 24, 32, 40, 48, 56, 64, 80, 96, 112, 128, 144, 160];
+
+impl FrameHeader {
+    pub const SIZE: usize = 4;
+
+    /// Parse frame header. Returns [`None`] if the data are not valid frame
+    /// header. Free format frames are not supported because their size
+    /// cannot be determined from the header.
+    pub fn from_bytes(d: &[u8; Self::SIZE]) -> Option<Self> {
+        // 11 bits: sync (all set)
+        // 2 bits: version
+        // 2 bits: layer
+        // 1 bit: protection (not set if there is CRC)
+        // 4 bits: bitrate index
+        // 2 bits: sample rate index
+        // 1 bit: padding
+        // 1 bit: private
+        // 2 bits: channel mode
+        // 2 bits: mode extension
+        // 1 bit: copyright
+        // 1 bit: original
+        // 2 bits: emphasis
+        if d[0] != 0xFF || d[1] & 0xE0 != 0xE0 {
+            return None;
+        }
+
+        let version = match (d[1] >> 3) & 3 {
+            0 => Version::Mpeg25,
+            2 => Version::Mpeg2,
+            3 => Version::Mpeg1,
+            _ => return None,
+        };
+
+        let layer = match (d[1] >> 1) & 3 {
+            1 => Layer::Layer3,
+            2 => Layer::Layer2,
+            3 => Layer::Layer1,
+            _ => return None,
+        };
+
+        let bitrates = match (version, layer) {
+            (Version::Mpeg1, Layer::Layer1) => &BITRATES_V1_L1,
+            (Version::Mpeg1, Layer::Layer2) => &BITRATES_V1_L2,
+            (Version::Mpeg1, Layer::Layer3) => &BITRATES_V1_L3,
+            (_, Layer::Layer1) => &BITRATES_V2_L1,
+            _ => &BITRATES_V2_L23,
+        };
+        let idx = (d[2] >> 4) as usize;
+        if idx == 0 || idx > bitrates.len() {
+            return None;
+        }
+        let bitrate = bitrates[idx - 1] as u32 * 1000;
+
+        let sample_rate = match (d[2] >> 2) & 3 {
+            0 => 44100,
+            1 => 48000,
+            2 => 32000,
+            _ => return None,
+        };
+        let sample_rate = match version {
+            Version::Mpeg1 => sample_rate,
+            Version::Mpeg2 => sample_rate / 2,
+            Version::Mpeg25 => sample_rate / 4,
+        };
+
+        let channel_mode = match d[3] >> 6 {
+            0 => ChannelMode::Stereo,
+            1 => ChannelMode::JointStereo,
+            2 => ChannelMode::DualChannel,
+            _ => ChannelMode::Mono,
+        };
+
+        Some(Self {
+            version,
+            layer,
+            bitrate,
+            sample_rate,
+            padding: d[2] & 2 != 0,
+            channel_mode,
+        })
+    }
+
+    /// Number of samples (per channel) in the frame.
+    pub fn samples(&self) -> u32 {
+        match (self.layer, self.version) {
+            (Layer::Layer1, _) => 384,
+            (Layer::Layer2, _) | (Layer::Layer3, Version::Mpeg1) => 1152,
+            (Layer::Layer3, _) => 576,
+        }
+    }
+
+    /// Size of the whole frame including the header.
+    pub fn frame_size(&self) -> usize {
+        let slots = self.samples() / 8 * self.bitrate / self.sample_rate;
+        match self.layer {
+            Layer::Layer1 => (slots / 4 + self.padding as u32) as usize * 4,
+            _ => (slots + self.padding as u32) as usize,
+        }
+    }
+
+    /// Size of the side information in layer III frame. Xing header is just
+    /// after the side information.
+    pub fn side_info_size(&self) -> usize {
+        match (self.version, self.channel_mode) {
+            (Version::Mpeg1, ChannelMode::Mono) => 17,
+            (Version::Mpeg1, _) => 32,
+            (_, ChannelMode::Mono) => 9,
+            _ => 17,
+        }
+    }
+
+    /// Check whether the other frame may be part of the same stream.
+    pub fn matches(&self, other: &Self) -> bool {
+        self.version == other.version
+            && self.layer == other.layer
+            && self.sample_rate == other.sample_rate
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mpeg1_layer3() {
+        // 128 kbps, 44100 Hz, joint stereo
+        let h = FrameHeader::from_bytes(b"\xFF\xFB\x90\x40").unwrap();
+        assert_eq!(h.version, Version::Mpeg1);
+        assert_eq!(h.layer, Layer::Layer3);
+        assert_eq!(h.bitrate, 128000);
+        assert_eq!(h.sample_rate, 44100);
+        assert_eq!(h.channel_mode, ChannelMode::JointStereo);
+        assert_eq!(h.samples(), 1152);
+        assert_eq!(h.frame_size(), 417);
+        assert_eq!(h.side_info_size(), 32);
+
+        // With padding.
+        let h = FrameHeader::from_bytes(b"\xFF\xFB\x92\x40").unwrap();
+        assert_eq!(h.frame_size(), 418);
+    }
+
+    #[test]
+    fn other_versions_and_layers() {
+        // MPEG-2 layer III, 64 kbps, 22050 Hz, mono
+        let h = FrameHeader::from_bytes(b"\xFF\xF3\x80\xC0").unwrap();
+        assert_eq!(h.version, Version::Mpeg2);
+        assert_eq!(h.sample_rate, 22050);
+        assert_eq!(h.samples(), 576);
+        assert_eq!(h.frame_size(), 208);
+        assert_eq!(h.side_info_size(), 9);
+
+        // MPEG-1 layer I, 384 kbps, 48000 Hz, with padding
+        let h = FrameHeader::from_bytes(b"\xFF\xFF\xC6\x00").unwrap();
+        assert_eq!(h.layer, Layer::Layer1);
+        assert_eq!(h.samples(), 384);
+        assert_eq!(h.frame_size(), 388);
+    }
+
+    #[test]
+    fn invalid_header() {
+        // No sync.
+        assert!(FrameHeader::from_bytes(b"\xFF\x1B\x90\x40").is_none());
+        // Reserved version.
+        assert!(FrameHeader::from_bytes(b"\xFF\xEB\x90\x40").is_none());
+        // Free format and invalid bitrate.
+        assert!(FrameHeader::from_bytes(b"\xFF\xFB\x00\x40").is_none());
+        assert!(FrameHeader::from_bytes(b"\xFF\xFB\xF0\x40").is_none());
+        // Reserved sample rate.
+        assert!(FrameHeader::from_bytes(b"\xFF\xFB\x9C\x40").is_none());
+    }
+}
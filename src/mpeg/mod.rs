@@ -0,0 +1,63 @@
+mod frame_header;
+mod mpeg_info;
+mod read_options;
+mod vbr_header;
+
+use self::{frame_header::FrameHeader, vbr_header::VbrHeader};
+
+pub use self::{
+    frame_header::{ChannelMode, Layer, Version},
+    mpeg_info::*,
+    read_options::*,
+    vbr_header::VbrHeaderKind,
+};
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Seek},
+    path::Path,
+};
+
+use crate::{DataType, Result, TagStore, trap::Trap};
+
+// Implementation is based on:
+// - http://www.mp3-tech.org/programmer/frame_header.html
+// - http://gabriel.mp3-tech.org/mp3infotag.html
+// - https://www.codeproject.com/Articles/8295/MPEG-Audio-Frame-Header
+
+/// Read length and audio properties of MPEG audio stream. Returns
+/// [`crate::Error::NoTag`] if there is no MPEG audio stream.
+pub fn from_seek(
+    r: &mut (impl BufRead + Seek),
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_seek_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read length and audio properties of MPEG audio stream with the given
+/// options. Returns [`crate::Error::NoTag`] if there is no MPEG audio stream.
+pub fn from_seek_with(
+    r: &mut (impl BufRead + Seek),
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    opts: &ReadOptions,
+) -> Result<()> {
+    let info = MpegInfo::from_seek(r, trap, opts)?;
+    if store.stores_data(DataType::Length) {
+        store.set_length(info.length);
+    }
+    if store.stores_data(DataType::AudioProperties) {
+        store.set_audio_properties(info.properties());
+    }
+    Ok(())
+}
+
+/// Read length and audio properties of MPEG audio stream from file.
+pub fn from_file(
+    f: impl AsRef<Path>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_seek(&mut BufReader::new(File::open(f)?), store, trap)
+}
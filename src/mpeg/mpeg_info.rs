@@ -0,0 +1,430 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    AudioProperties, Codec, Error, Result,
+    ape::{Footer, has_magic},
+    id3::v2::header::Header,
+    mpeg::{
+        ChannelMode, FrameHeader, Layer, LengthMode, ReadOptions, VbrHeader,
+        VbrHeaderKind, Version,
+    },
+    trap::Trap,
+};
+
+/// Information about MPEG audio stream.
+#[derive(Debug, Clone)]
+pub struct MpegInfo {
+    /// MPEG version of the first frame.
+    pub version: Version,
+    /// Layer of the first frame.
+    pub layer: Layer,
+    /// Channel mode of the first frame.
+    pub channel_mode: ChannelMode,
+    /// Sample rate in Hz.
+    pub sample_rate: u32,
+    /// Average bitrate in bits per second.
+    pub bitrate: u32,
+    /// Number of audio frames. [`None`] if the length is estimated.
+    pub frames: Option<u64>,
+    /// Length of the audio. Encoder delay and padding are not included if
+    /// they are known.
+    pub length: Duration,
+    /// How the length was determined.
+    pub length_source: LengthSource,
+    /// Number of samples added by the encoder at the start (from LAME
+    /// header).
+    pub encoder_delay: Option<u16>,
+    /// Number of samples added by the encoder at the end (from LAME header).
+    pub encoder_padding: Option<u16>,
+}
+
+/// How the length of MPEG audio stream was determined.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum LengthSource {
+    /// From the number of frames in Xing, Info or VBRI header.
+    VbrHeader(VbrHeaderKind),
+    /// By reading headers of all the frames.
+    Scan,
+    /// From the size of the stream assuming constant bitrate.
+    Estimate,
+}
+
+impl MpegInfo {
+    /// Number of frames after the first frame that must be valid for the
+    /// stream to be recognized.
+    const CHECK_FRAMES: usize = 3;
+
+    /// Read information about MPEG audio stream from file.
+    pub fn from_file(
+        f: impl AsRef<Path>,
+        trap: &impl Trap,
+        opts: &ReadOptions,
+    ) -> Result<Self> {
+        Self::from_seek(&mut BufReader::new(File::open(f)?), trap, opts)
+    }
+
+    /// Read information about MPEG audio stream. The stream may start with
+    /// ID3v2 tags and it may end with ID3v1 and APE tags. Returns
+    /// [`Error::NoTag`] if there is no MPEG audio stream.
+    pub fn from_seek(
+        r: &mut (impl BufRead + Seek),
+        trap: &impl Trap,
+        opts: &ReadOptions,
+    ) -> Result<Self> {
+        let end = stream_end(r)?;
+        let start = stream_start(r)?;
+        let Some((pos, header)) =
+            find_first_frame(r, start, end, opts.max_sync_search)?
+        else {
+            return Err(Error::NoTag);
+        };
+
+        r.seek(SeekFrom::Start(pos))?;
+        let mut frame = vec![0; header.frame_size()];
+        r.read_exact(&mut frame)?;
+        let vbr = VbrHeader::from_frame(&header, &frame);
+
+        let mut res = Self {
+            version: header.version,
+            layer: header.layer,
+            channel_mode: header.channel_mode,
+            sample_rate: header.sample_rate,
+            bitrate: header.bitrate,
+            frames: None,
+            length: Duration::ZERO,
+            length_source: LengthSource::Estimate,
+            encoder_delay: vbr.as_ref().and_then(|a| a.encoder_delay),
+            encoder_padding: vbr.as_ref().and_then(|a| a.encoder_padding),
+        };
+
+        // Frame with Xing or VBRI header doesn't contain audio.
+        let audio_start = if vbr.is_some() {
+            pos + frame.len() as u64
+        } else {
+            pos
+        };
+        let audio_len = end.saturating_sub(audio_start);
+
+        if let Some(v) = &vbr
+            && v.frames == Some(0)
+        {
+            trap.error(Error::InvalidLength)?;
+        }
+
+        match vbr {
+            Some(VbrHeader {
+                kind,
+                frames: Some(frames @ 1..),
+                bytes,
+                ..
+            }) => {
+                res.length_source = LengthSource::VbrHeader(kind);
+                res.set_frames(
+                    &header,
+                    frames as u64,
+                    bytes.map_or(audio_len, |b| b as u64),
+                );
+            }
+            _ if opts.length_mode == LengthMode::Scan => {
+                let (frames, bytes) = scan(r, &header, audio_start, end)?;
+                res.length_source = LengthSource::Scan;
+                res.set_frames(&header, frames, bytes);
+            }
+            _ => {
+                res.length = Duration::from_secs_f64(
+                    audio_len as f64 * 8. / header.bitrate as f64,
+                );
+            }
+        }
+
+        Ok(res)
+    }
+
+    /// Get the audio properties of the stream.
+    pub fn properties(&self) -> AudioProperties {
+        let codec = match self.layer {
+            Layer::Layer1 => Codec::Mp1,
+            Layer::Layer2 => Codec::Mp2,
+            Layer::Layer3 => Codec::Mp3,
+        };
+        let mut res = AudioProperties::new(codec);
+        res.sample_rate = Some(self.sample_rate);
+        res.channels = Some(match self.channel_mode {
+            ChannelMode::Mono => 1,
+            _ => 2,
+        });
+        res.bitrate = Some(self.bitrate);
+        res
+    }
+
+    /// Set the length and bitrate from the number of frames and the size of
+    /// the stream.
+    fn set_frames(&mut self, header: &FrameHeader, frames: u64, bytes: u64) {
+        self.frames = Some(frames);
+        let samples = (frames * header.samples() as u64)
+            .saturating_sub(self.encoder_delay.unwrap_or_default() as u64)
+            .saturating_sub(self.encoder_padding.unwrap_or_default() as u64);
+        self.length =
+            Duration::from_secs_f64(samples as f64 / self.sample_rate as f64);
+        if !self.length.is_zero() {
+            self.bitrate =
+                (bytes as f64 * 8. / self.length.as_secs_f64()) as u32;
+        }
+    }
+}
+
+/// Get the position after ID3v2 tags at the start of the stream.
+pub(crate) fn stream_start(r: &mut (impl Read + Seek)) -> Result<u64> {
+    let mut pos = 0;
+    loop {
+        r.seek(SeekFrom::Start(pos))?;
+        let mut buf = [0; Header::SIZE];
+        match r.read_exact(&mut buf) {
+            Err(e) if e.kind() == ErrorKind::UnexpectedEof => return Ok(pos),
+            res => res?,
+        }
+        if &buf[..3] != b"ID3" {
+            return Ok(pos);
+        }
+        pos += Header::from_bytes(buf[3..].try_into().unwrap()).tag_size();
+    }
+}
+
+/// Get the position of ID3v1 or APE tag at the end of the stream.
+fn stream_end(r: &mut (impl Read + Seek)) -> Result<u64> {
+    let len = r.seek(SeekFrom::End(0))?;
+
+    let mut end = len;
+    if has_magic(r, len, 128, b"TAG")? {
+        end -= 128;
+        if has_magic(r, len, 256, b"EXT")? {
+            end -= 128;
+        }
+    }
+
+    if let Some((pos, footer)) = Footer::find(r)?
+        && let Some(start) =
+            (pos + Footer::SIZE as u64).checked_sub(footer.tag_size())
+    {
+        end = end.min(start);
+    }
+
+    Ok(end)
+}
+
+/// Find the first frame within `max_search` bytes from `start`. The frame
+/// must be followed by valid frames.
+fn find_first_frame(
+    r: &mut (impl Read + Seek),
+    start: u64,
+    end: u64,
+    max_search: u64,
+) -> Result<Option<(u64, FrameHeader)>> {
+    r.seek(SeekFrom::Start(start))?;
+    let len =
+        (max_search + FrameHeader::SIZE as u64).min(end.saturating_sub(start));
+    let mut buf = vec![];
+    r.take(len).read_to_end(&mut buf)?;
+
+    for (i, d) in buf.windows(FrameHeader::SIZE).enumerate() {
+        let Some(header) = FrameHeader::from_bytes(d.try_into().unwrap())
+        else {
+            continue;
+        };
+        let pos = start + i as u64;
+        if is_stream(r, pos, end, &header)? {
+            return Ok(Some((pos, header)));
+        }
+    }
+
+    Ok(None)
+}
+
+/// Check that the frame at `pos` is followed by valid frames.
+fn is_stream(
+    r: &mut (impl Read + Seek),
+    mut pos: u64,
+    end: u64,
+    first: &FrameHeader,
+) -> Result<bool> {
+    pos += first.frame_size() as u64;
+    for _ in 0..MpegInfo::CHECK_FRAMES {
+        if pos + FrameHeader::SIZE as u64 > end {
+            return Ok(pos <= end);
+        }
+        r.seek(SeekFrom::Start(pos))?;
+        let mut buf = [0; FrameHeader::SIZE];
+        r.read_exact(&mut buf)?;
+        match FrameHeader::from_bytes(&buf) {
+            Some(h) if h.matches(first) => pos += h.frame_size() as u64,
+            _ => return Ok(false),
+        }
+    }
+    Ok(true)
+}
+
+/// Count the frames from `start`. Returns the number of frames and their
+/// total size.
+fn scan(
+    r: &mut (impl Read + Seek),
+    first: &FrameHeader,
+    start: u64,
+    end: u64,
+) -> Result<(u64, u64)> {
+    r.seek(SeekFrom::Start(start))?;
+    let mut pos = start;
+    let mut frames = 0;
+    while pos + FrameHeader::SIZE as u64 <= end {
+        let mut buf = [0; FrameHeader::SIZE];
+        r.read_exact(&mut buf)?;
+        let size = match FrameHeader::from_bytes(&buf) {
+            Some(h) if h.matches(first) => h.frame_size() as u64,
+            _ => break,
+        };
+        if pos + size > end {
+            break;
+        }
+        // Read instead of seek so that buffered readers keep their buffer.
+        io::copy(
+            &mut r.by_ref().take(size - FrameHeader::SIZE as u64),
+            &mut io::sink(),
+        )?;
+        frames += 1;
+        pos += size;
+    }
+    Ok((frames, pos - start))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    /// MPEG-1 layer III, 128 kbps, 44100 Hz, joint stereo
+    const HEADER: &[u8; 4] = b"\xFF\xFB\x90\x40";
+    const FRAME_SIZE: usize = 417;
+
+    /// Create stream with ID3v2 tag, `cnt` audio frames and ID3v1 tag. If
+    /// `vbr` is given, it is put to additional first frame after the side
+    /// information.
+    fn stream(vbr: Option<&[u8]>, cnt: usize) -> Vec<u8> {
+        let mut res = b"ID3\x04\0\0\0\0\0\x0A".to_vec();
+        res.resize(20, 0);
+        if let Some(vbr) = vbr {
+            let start = res.len();
+            res.extend(HEADER);
+            res.resize(start + 36, 0);
+            res.extend(vbr);
+            res.resize(start + FRAME_SIZE, 0);
+        }
+        for _ in 0..cnt {
+            let start = res.len();
+            res.extend(HEADER);
+            res.resize(start + FRAME_SIZE, 0x55);
+        }
+        let mut id3v1 = b"TAG".to_vec();
+        id3v1.resize(128, 0);
+        res.extend(id3v1);
+        res
+    }
+
+    fn read(d: &[u8], opts: &ReadOptions) -> Result<MpegInfo> {
+        MpegInfo::from_seek(&mut Cursor::new(d), &Strict, opts)
+    }
+
+    #[test]
+    fn xing_length() {
+        // 10 frames, 4170 bytes, delay 576, padding 1000
+        let mut vbr = b"Xing\0\0\0\x03\0\0\0\x0A\0\0\x10\x4A".to_vec();
+        vbr.extend(b"LAME3.100");
+        vbr.resize(vbr.len() + 12, 0);
+        vbr.extend(b"\x24\x03\xE8");
+
+        let info = read(&stream(Some(&vbr), 10), &Default::default()).unwrap();
+        let samples = 10 * 1152 - 576 - 1000;
+        assert_eq!(info.frames, Some(10));
+        assert_eq!(info.encoder_delay, Some(576));
+        assert_eq!(info.encoder_padding, Some(1000));
+        assert_eq!(
+            info.length_source,
+            LengthSource::VbrHeader(VbrHeaderKind::Xing)
+        );
+        assert_eq!(
+            info.length,
+            Duration::from_secs_f64(samples as f64 / 44100.)
+        );
+        assert_eq!(
+            info.bitrate,
+            (4170. * 8. / info.length.as_secs_f64()) as u32
+        );
+    }
+
+    #[test]
+    fn vbri_length() {
+        let mut vbr = b"VBRI\0\x01\0\0\0\x50\0\0\x10\x4A\0\0\0\x14".to_vec();
+        vbr.resize(30, 0);
+        let info = read(&stream(Some(&vbr), 10), &Default::default()).unwrap();
+        assert_eq!(info.frames, Some(20));
+        assert_eq!(
+            info.length_source,
+            LengthSource::VbrHeader(VbrHeaderKind::Vbri)
+        );
+        assert_eq!(info.length, Duration::from_secs_f64(20. * 1152. / 44100.));
+    }
+
+    #[test]
+    fn estimate_and_scan() {
+        let d = stream(None, 10);
+        let info = read(&d, &Default::default()).unwrap();
+        assert_eq!(info.length_source, LengthSource::Estimate);
+        assert_eq!(info.frames, None);
+        assert_eq!(info.length, Duration::from_secs_f64(4170. * 8. / 128000.));
+
+        let opts = ReadOptions {
+            length_mode: LengthMode::Scan,
+            ..Default::default()
+        };
+        let info = read(&d, &opts).unwrap();
+        assert_eq!(info.length_source, LengthSource::Scan);
+        assert_eq!(info.frames, Some(10));
+        assert_eq!(info.length, Duration::from_secs_f64(10. * 1152. / 44100.));
+    }
+
+    #[test]
+    fn zero_frames_in_header() {
+        let vbr = b"Xing\0\0\0\x01\0\0\0\0";
+        let d = stream(Some(vbr), 10);
+        assert!(matches!(
+            read(&d, &Default::default()),
+            Err(Error::InvalidLength)
+        ));
+
+        let info = MpegInfo::from_seek(
+            &mut Cursor::new(&d),
+            &Skip,
+            &Default::default(),
+        )
+        .unwrap();
+        assert_eq!(info.length_source, LengthSource::Estimate);
+    }
+
+    #[test]
+    fn no_stream() {
+        let d = vec![0x55; 1000];
+        let res = read(&d, &Default::default());
+        assert!(matches!(res, Err(Error::NoTag)));
+
+        // Single frame header without the following frames.
+        let mut d = HEADER.to_vec();
+        d.resize(2000, 0);
+        let res = read(&d, &Default::default());
+        assert!(matches!(res, Err(Error::NoTag)));
+    }
+}
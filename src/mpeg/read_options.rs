@@ -0,0 +1,30 @@
+/// Options for reading MPEG audio streams.
+#[derive(Debug, Clone)]
+pub struct ReadOptions {
+    /// How to determine the length of streams without Xing or VBRI header.
+    pub length_mode: LengthMode,
+    /// Maximum number of bytes after the ID3v2 tag that are searched for the
+    /// first frame.
+    pub max_sync_search: u64,
+}
+
+/// Method used to determine length of streams without Xing or VBRI header.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum LengthMode {
+    /// Assume constant bitrate and estimate the length from the first frame
+    /// and the size of the stream.
+    #[default]
+    Estimate,
+    /// Read headers of all the frames. This is slow but accurate also for
+    /// variable bitrate streams without header.
+    Scan,
+}
+
+impl Default for ReadOptions {
+    fn default() -> Self {
+        Self {
+            length_mode: LengthMode::default(),
+            max_sync_search: 64 * 1024,
+        }
+    }
+}
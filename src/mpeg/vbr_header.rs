@@ -0,0 +1,198 @@
+use crate::mpeg::{FrameHeader, Layer};
+
+/// Xing (or Info) or VBRI header in the first frame of the stream.
+#[derive(Debug, Default)]
+pub struct VbrHeader {
+    pub kind: VbrHeaderKind,
+    /// Number of frames.
+    pub frames: Option<u32>,
+    /// Size of the stream in bytes.
+    pub bytes: Option<u32>,
+    /// Encoder delay from LAME extension.
+    pub encoder_delay: Option<u16>,
+    /// Encoder padding from LAME extension.
+    pub encoder_padding: Option<u16>,
+}
+
+/// Type of the header with information about the stream.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum VbrHeaderKind {
+    /// Xing header. Used by variable bitrate streams.
+    #[default]
+    Xing,
+    /// Info header. It is the same as Xing header, but it is used by
+    /// constant bitrate streams.
+    Info,
+    /// VBRI header created by Fraunhofer encoder.
+    Vbri,
+}
+
+impl VbrHeader {
+    const XING_FRAMES: u32 = 1;
+    const XING_BYTES: u32 = 2;
+    const XING_TOC: u32 = 4;
+    const XING_QUALITY: u32 = 8;
+
+    /// Offset of VBRI header within the frame.
+    const VBRI_OFFSET: usize = 36;
+
+    /// Find Xing or VBRI header in the first frame. The frame includes the
+    /// frame header.
+    pub fn from_frame(header: &FrameHeader, d: &[u8]) -> Option<Self> {
+        if header.layer != Layer::Layer3 {
+            return None;
+        }
+
+        let pos = FrameHeader::SIZE + header.side_info_size();
+        Self::xing(d.get(pos..)?)
+            .or_else(|| Self::vbri(d.get(Self::VBRI_OFFSET..)?))
+    }
+
+    fn xing(d: &[u8]) -> Option<Self> {
+        // 0..4: `Xing` or `Info`
+        // 4..8: flags
+        // [4]: frames
+        // [4]: bytes
+        // [100]: table of contents
+        // [4]: quality
+        // LAME extension
+        let kind = match d.get(..4)? {
+            b"Xing" => VbrHeaderKind::Xing,
+            b"Info" => VbrHeaderKind::Info,
+            _ => return None,
+        };
+        let flags = be_u32(d, 4)?;
+        let mut res = Self {
+            kind,
+            ..Default::default()
+        };
+
+        let mut pos = 8;
+        if flags & Self::XING_FRAMES != 0 {
+            res.frames = Some(be_u32(d, pos)?);
+            pos += 4;
+        }
+        if flags & Self::XING_BYTES != 0 {
+            res.bytes = Some(be_u32(d, pos)?);
+            pos += 4;
+        }
+        if flags & Self::XING_TOC != 0 {
+            pos += 100;
+        }
+        if flags & Self::XING_QUALITY != 0 {
+            pos += 4;
+        }
+
+        // LAME extension:
+        // 0..9: encoder version
+        // 9: revision and VBR method
+        // 10: lowpass
+        // 11..15: peak
+        // 15..17: radio replay gain
+        // 17..19: audiophile replay gain
+        // 19: encoding flags and ATH type
+        // 20: bitrate
+        // 21..24: 12 bits of encoder delay and 12 bits of padding
+        if let Some(l) = d.get(pos..pos + 24)
+            && (l.starts_with(b"LAME") || l.starts_with(b"Lavc"))
+        {
+            res.encoder_delay =
+                Some(((l[21] as u16) << 4) | (l[22] >> 4) as u16);
+            res.encoder_padding =
+                Some(((l[22] as u16 & 0xF) << 8) | l[23] as u16);
+        }
+
+        Some(res)
+    }
+
+    fn vbri(d: &[u8]) -> Option<Self> {
+        // 0..4: `VBRI`
+        // 4..6: version
+        // 6..8: delay
+        // 8..10: quality
+        // 10..14: bytes
+        // 14..18: frames
+        if !d.starts_with(b"VBRI") {
+            return None;
+        }
+        Some(Self {
+            kind: VbrHeaderKind::Vbri,
+            bytes: Some(be_u32(d, 10)?),
+            frames: Some(be_u32(d, 14)?),
+            ..Default::default()
+        })
+    }
+}
+
+fn be_u32(d: &[u8], pos: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(d.get(pos..pos + 4)?.try_into().unwrap()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// MPEG-1 layer III, 128 kbps, 44100 Hz, joint stereo
+    const HEADER: &[u8; 4] = b"\xFF\xFB\x90\x40";
+
+    /// Create frame with the given data after the side information.
+    fn first_frame(d: &[u8]) -> (FrameHeader, Vec<u8>) {
+        let header = FrameHeader::from_bytes(HEADER).unwrap();
+        let mut res = HEADER.to_vec();
+        res.resize(FrameHeader::SIZE + header.side_info_size(), 0);
+        res.extend_from_slice(d);
+        res.resize(header.frame_size(), 0);
+        (header, res)
+    }
+
+    #[test]
+    fn xing_with_lame() {
+        let mut d = b"Xing\0\0\0\x03\0\0\0\x64\0\0\xA2\xE4".to_vec();
+        let mut lame = b"LAME3.100".to_vec();
+        lame.resize(21, 0);
+        // Delay 576, padding 1000.
+        lame.extend(b"\x24\x03\xE8");
+        d.extend(lame);
+
+        let (header, frame) = first_frame(&d);
+        let vbr = VbrHeader::from_frame(&header, &frame).unwrap();
+        assert_eq!(vbr.kind, VbrHeaderKind::Xing);
+        assert_eq!(vbr.frames, Some(100));
+        assert_eq!(vbr.bytes, Some(41700));
+        assert_eq!(vbr.encoder_delay, Some(576));
+        assert_eq!(vbr.encoder_padding, Some(1000));
+    }
+
+    #[test]
+    fn info_with_toc() {
+        let mut d = b"Info\0\0\0\x05\0\0\0\x64".to_vec();
+        d.resize(d.len() + 100, 0xAA);
+        let (header, frame) = first_frame(&d);
+        let vbr = VbrHeader::from_frame(&header, &frame).unwrap();
+        assert_eq!(vbr.kind, VbrHeaderKind::Info);
+        assert_eq!(vbr.frames, Some(100));
+        assert_eq!(vbr.bytes, None);
+        assert_eq!(vbr.encoder_delay, None);
+    }
+
+    #[test]
+    fn vbri() {
+        let (header, mut frame) = first_frame(b"");
+        let d = b"VBRI\0\x01\0\0\0\x50\0\0\xA2\xE4\0\0\0\x64";
+        frame[VbrHeader::VBRI_OFFSET..][..d.len()].copy_from_slice(d);
+        let vbr = VbrHeader::from_frame(&header, &frame).unwrap();
+        assert_eq!(vbr.kind, VbrHeaderKind::Vbri);
+        assert_eq!(vbr.frames, Some(100));
+        assert_eq!(vbr.bytes, Some(41700));
+    }
+
+    #[test]
+    fn no_header() {
+        let (header, frame) = first_frame(b"data");
+        assert!(VbrHeader::from_frame(&header, &frame).is_none());
+
+        // Truncated Xing header.
+        let (header, frame) = first_frame(b"Xing\0\0\0\x01");
+        assert!(VbrHeader::from_frame(&header, &frame[..44]).is_none());
+    }
+}
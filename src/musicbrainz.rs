@@ -0,0 +1,226 @@
+use uuid::Uuid;
+
+use crate::{
+    DataType, Error, Result, TagStore,
+    trap::{Trap, TrapExt},
+};
+
+/// Kind of MusicBrainz identifier.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MusicBrainzId {
+    Recording,
+    Track,
+    Release,
+    ReleaseGroup,
+    Artist,
+    AlbumArtist,
+    Work,
+    Disc,
+}
+
+impl MusicBrainzId {
+    /// Get the identifier from its name as used in ID3v2 `TXXX`, mp4
+    /// freeform atoms and ASF. The name is without the prefix
+    /// (`MusicBrainz ` or `MusicBrainz/`).
+    pub fn from_name(name: &str) -> Option<Self> {
+        let res = match name {
+            "Track Id" => Self::Recording,
+            "Release Track Id" => Self::Track,
+            "Album Id" => Self::Release,
+            "Release Group Id" => Self::ReleaseGroup,
+            "Artist Id" => Self::Artist,
+            "Album Artist Id" => Self::AlbumArtist,
+            "Work Id" => Self::Work,
+            "Disc Id" => Self::Disc,
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// Get the identifier from the key of vorbis comment.
+    pub fn from_vorbis(key: &str) -> Option<Self> {
+        let res = match key {
+            "MUSICBRAINZ_TRACKID" => Self::Recording,
+            "MUSICBRAINZ_RELEASETRACKID" => Self::Track,
+            "MUSICBRAINZ_ALBUMID" => Self::Release,
+            "MUSICBRAINZ_RELEASEGROUPID" => Self::ReleaseGroup,
+            "MUSICBRAINZ_ARTISTID" => Self::Artist,
+            "MUSICBRAINZ_ALBUMARTISTID" => Self::AlbumArtist,
+            "MUSICBRAINZ_WORKID" => Self::Work,
+            "MUSICBRAINZ_DISCID" => Self::Disc,
+            _ => return None,
+        };
+        Some(res)
+    }
+
+    /// Data type of the identifier.
+    pub fn data_type(self) -> DataType {
+        match self {
+            Self::Recording => DataType::MusicBrainzRecordingId,
+            Self::Track => DataType::MusicBrainzTrackId,
+            Self::Release => DataType::MusicBrainzReleaseId,
+            Self::ReleaseGroup => DataType::MusicBrainzReleaseGroupId,
+            Self::Artist => DataType::MusicBrainzArtistIds,
+            Self::AlbumArtist => DataType::MusicBrainzAlbumArtistIds,
+            Self::Work => DataType::MusicBrainzWorkId,
+            Self::Disc => DataType::MusicBrainzDiscId,
+        }
+    }
+
+    /// Check whether the store stores any MusicBrainz identifier.
+    pub fn stores_any(store: &impl TagStore) -> bool {
+        [
+            Self::Recording,
+            Self::Track,
+            Self::Release,
+            Self::ReleaseGroup,
+            Self::Artist,
+            Self::AlbumArtist,
+            Self::Work,
+            Self::Disc,
+        ]
+        .into_iter()
+        .any(|a| store.stores_data(a.data_type()))
+    }
+
+    /// Parse the values and set them to the store. Artist ids may also be
+    /// separated by `/` or `;` within single value. Identifiers that are not
+    /// valid UUIDs are trapped.
+    pub fn store(
+        self,
+        values: Vec<String>,
+        store: &mut impl TagStore,
+        trap: &impl Trap,
+    ) -> Result<()> {
+        if self == Self::Disc {
+            // Disc id is not UUID.
+            if let Some(id) = values.into_iter().next_back() {
+                store.set_musicbrainz_disc_id(id);
+            }
+            return Ok(());
+        }
+
+        let mut ids = vec![];
+        for v in values.iter().flat_map(|a| a.split(['/', ';'])) {
+            let id = Uuid::parse_str(v.trim()).map_err(Error::from);
+            ids.extend(trap.res(id)?);
+        }
+
+        // Single identifiers use the last value.
+        let Some(&last) = ids.last() else {
+            return Ok(());
+        };
+
+        match self {
+            Self::Recording => store.set_musicbrainz_recording_id(last),
+            Self::Track => store.set_musicbrainz_track_id(last),
+            Self::Release => store.set_musicbrainz_release_id(last),
+            Self::ReleaseGroup => store.set_musicbrainz_release_group_id(last),
+            Self::Artist => store.set_musicbrainz_artist_ids(ids),
+            Self::AlbumArtist => store.set_musicbrainz_album_artist_ids(ids),
+            Self::Work => store.set_musicbrainz_work_id(last),
+            Self::Disc => {}
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    #[derive(Default)]
+    struct Store {
+        recording: Option<Uuid>,
+        artists: Vec<Uuid>,
+        disc: Option<String>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::MusicBrainzRecordingId
+                    | DataType::MusicBrainzArtistIds
+                    | DataType::MusicBrainzDiscId
+            )
+        }
+
+        fn set_musicbrainz_recording_id(&mut self, id: Uuid) {
+            self.recording = Some(id);
+        }
+
+        fn set_musicbrainz_artist_ids(&mut self, ids: Vec<Uuid>) {
+            self.artists = ids;
+        }
+
+        fn set_musicbrainz_disc_id(&mut self, id: String) {
+            self.disc = Some(id);
+        }
+    }
+
+    const ID1: &str = "0383dadf-2a4e-4d10-a46a-e9e041da8eb3";
+    const ID2: &str = "b10bbbfc-cf9e-42e0-be17-e2c3e1d2600d";
+    const ID3: &str = "a74b1b7f-71a5-4011-9441-d0b5e4122711";
+
+    fn uuid(s: &str) -> Uuid {
+        Uuid::parse_str(s).unwrap()
+    }
+
+    fn read(id: MusicBrainzId, values: &[&str], trap: &impl Trap) -> Store {
+        let mut store = Store::default();
+        let values = values.iter().map(|a| a.to_string()).collect();
+        id.store(values, &mut store, trap).unwrap();
+        store
+    }
+
+    #[test]
+    fn split_artist_ids() {
+        let v = format!("{ID1}/{ID2}; {ID3}");
+        let store = read(MusicBrainzId::Artist, &[&v], &Strict);
+        assert_eq!(store.artists, [uuid(ID1), uuid(ID2), uuid(ID3)]);
+
+        let store = read(MusicBrainzId::Artist, &[ID1, ID2], &Strict);
+        assert_eq!(store.artists, [uuid(ID1), uuid(ID2)]);
+    }
+
+    #[test]
+    fn single_id_uses_last_value() {
+        let store = read(MusicBrainzId::Recording, &[ID1, ID2], &Strict);
+        assert_eq!(store.recording, Some(uuid(ID2)));
+    }
+
+    #[test]
+    fn invalid_id() {
+        let v = format!("{ID1}/invalid");
+        let mut store = Store::default();
+        let res =
+            MusicBrainzId::Artist.store(vec![v.clone()], &mut store, &Strict);
+        assert!(res.is_err());
+
+        let store = read(MusicBrainzId::Artist, &[&v], &Skip);
+        assert_eq!(store.artists, [uuid(ID1)]);
+    }
+
+    #[test]
+    fn disc_id_is_not_uuid() {
+        let id = "lwHl8fGzJyLXQR33ug60E8jhf4k-";
+        let store = read(MusicBrainzId::Disc, &[id], &Strict);
+        assert_eq!(store.disc.as_deref(), Some(id));
+    }
+
+    #[test]
+    fn names() {
+        assert_eq!(
+            MusicBrainzId::from_name("Album Artist Id"),
+            Some(MusicBrainzId::AlbumArtist)
+        );
+        assert_eq!(
+            MusicBrainzId::from_vorbis("MUSICBRAINZ_RELEASETRACKID"),
+            Some(MusicBrainzId::Track)
+        );
+        assert_eq!(MusicBrainzId::from_name("Unknown Id"), None);
+    }
+}
@@ -0,0 +1,344 @@
+mod packet_reader;
+mod page_header;
+
+use self::{packet_reader::*, page_header::*};
+
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Read, Seek, SeekFrom},
+    path::Path,
+    time::Duration,
+};
+
+use crate::{
+    AudioProperties, DataType, Error, Result, TagRead, TagStore, TagType,
+    bread::Bread, trap::Trap, vorbis,
+};
+
+// Implementation is based on:
+// - https://www.rfc-editor.org/rfc/rfc3533.html
+// - https://xiph.org/vorbis/doc/Vorbis_I_spec.html
+// - https://www.rfc-editor.org/rfc/rfc7845.html
+// - https://www.speex.org/docs/manual/speex-manual/node8.html
+
+/// Tag reader for ogg files. Supports vorbis, opus and speex streams.
+#[derive(Debug)]
+pub struct Ogg;
+
+impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Ogg {
+    fn extensions(&self) -> &[&str] {
+        &["ogg", "oga", "opus", "spx"]
+    }
+
+    fn store(&self, r: &mut R, store: &mut S, trap: &T) -> Result<()> {
+        from_seek(r, store, trap)
+    }
+}
+
+/// Read tags from ogg file.
+pub fn from_file(
+    f: impl AsRef<Path>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_read(BufReader::new(File::open(f)?), store, trap)
+}
+
+/// Read tags from ogg stream. Seeks to the correct position before reading.
+pub fn from_seek(
+    mut r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    r.rewind()?;
+    from_read(r, store, trap)
+}
+
+/// Read tags from ogg stream. Assumes that the stream is at the start of the
+/// first page.
+pub fn from_read(
+    mut r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    if !Bread::new(&mut r).expect(PageHeader::CAPTURE_PATTERN)? {
+        return Err(Error::NoTag);
+    }
+
+    let mut r = PacketReader::new(r)?;
+
+    store.set_tag_type(TagType::Ogg);
+
+    let mut ident = vec![];
+    (&mut r)
+        .take(Codec::MAX_IDENT_LEN)
+        .read_to_end(&mut ident)?;
+    let Some(codec) = Codec::from_ident(&ident) else {
+        return trap.error(Error::Unsupported(
+            "Ogg stream other than vorbis, opus and speex.",
+        ));
+    };
+    r.next_packet()?;
+
+    if store.done() {
+        return Ok(());
+    }
+
+    let mut br = Bread::new(&mut r);
+    if br.expect(codec.comment_prefix())? {
+        vorbis::from_bread(&mut br, store, trap, codec.framing_bit())?;
+    } else {
+        trap.error(Error::InvalidOggPacket)?;
+    }
+
+    let props = store.stores_data(DataType::AudioProperties);
+    if store.done() || !(props || store.stores_data(DataType::Length)) {
+        return Ok(());
+    }
+
+    let serial = r.serial();
+    let mut r = r.into_inner();
+    let length = match last_granule(&mut r, serial)?.map(|g| codec.length(g)) {
+        Some(Ok(l)) => Some(l),
+        Some(Err(e)) => {
+            trap.error(e)?;
+            None
+        }
+        None => None,
+    };
+
+    if let Some(l) = length
+        && store.stores_data(DataType::Length)
+    {
+        store.set_length(l);
+    }
+
+    if props {
+        let mut p = codec.properties();
+        // Use the average bitrate if the header doesn't contain nominal
+        // bitrate.
+        if p.bitrate.is_none()
+            && let Some(l) = length
+            && !l.is_zero()
+        {
+            let size = r.seek(SeekFrom::End(0))?;
+            p.bitrate = Some((size as f64 * 8. / l.as_secs_f64()) as u32);
+        }
+        store.set_audio_properties(p);
+    }
+
+    Ok(())
+}
+
+enum Codec {
+    Vorbis {
+        sample_rate: u32,
+        channels: u8,
+        bitrate: i32,
+    },
+    Opus {
+        pre_skip: u16,
+        channels: u8,
+    },
+    Speex {
+        sample_rate: u32,
+        channels: u32,
+        bitrate: i32,
+    },
+}
+
+impl Codec {
+    const MAX_IDENT_LEN: u64 = 512;
+    const OPUS_SAMPLE_RATE: u32 = 48000;
+
+    fn from_ident(d: &[u8]) -> Option<Self> {
+        if d.starts_with(b"\x01vorbis") && d.len() >= 16 {
+            // 7..11: version
+            // 11: channels
+            // 12..16: sample rate
+            // 16..20: maximum bitrate
+            // 20..24: nominal bitrate
+            // 24..28: minimum bitrate
+            Some(Self::Vorbis {
+                sample_rate: u32::from_le_bytes(d[12..16].try_into().unwrap()),
+                channels: d[11],
+                bitrate: d
+                    .get(20..24)
+                    .map_or(0, |b| i32::from_le_bytes(b.try_into().unwrap())),
+            })
+        } else if d.starts_with(b"OpusHead") && d.len() >= 12 {
+            // 8: version
+            // 9: channels
+            // 10..12: pre-skip
+            Some(Self::Opus {
+                pre_skip: u16::from_le_bytes(d[10..12].try_into().unwrap()),
+                channels: d[9],
+            })
+        } else if d.starts_with(b"Speex   ") && d.len() >= 40 {
+            // 8..28: version string
+            // 28..32: version id
+            // 32..36: header size
+            // 36..40: sample rate
+            // 40..44: mode
+            // 44..48: mode bitstream version
+            // 48..52: channels
+            // 52..56: bitrate
+            let get = |r: std::ops::Range<usize>| {
+                d.get(r).map(|b| i32::from_le_bytes(b.try_into().unwrap()))
+            };
+            Some(Self::Speex {
+                sample_rate: u32::from_le_bytes(d[36..40].try_into().unwrap()),
+                channels: get(48..52).unwrap_or(0) as u32,
+                bitrate: get(52..56).unwrap_or(-1),
+            })
+        } else {
+            None
+        }
+    }
+
+    fn comment_prefix(&self) -> &'static [u8] {
+        match self {
+            Self::Vorbis { .. } => b"\x03vorbis",
+            Self::Opus { .. } => b"OpusTags",
+            Self::Speex { .. } => b"",
+        }
+    }
+
+    fn framing_bit(&self) -> bool {
+        matches!(self, Self::Vorbis { .. })
+    }
+
+    fn properties(&self) -> AudioProperties {
+        let (codec, sample_rate, channels, bitrate) = match *self {
+            Self::Vorbis {
+                sample_rate,
+                channels,
+                bitrate,
+            } => (crate::Codec::Vorbis, sample_rate, channels as u32, bitrate),
+            Self::Opus { channels, .. } => (
+                crate::Codec::Opus,
+                Self::OPUS_SAMPLE_RATE,
+                channels as u32,
+                0,
+            ),
+            Self::Speex {
+                sample_rate,
+                channels,
+                bitrate,
+            } => (crate::Codec::Speex, sample_rate, channels, bitrate),
+        };
+
+        let mut res = AudioProperties::new(codec);
+        res.sample_rate = Some(sample_rate).filter(|a| *a != 0);
+        res.channels = u16::try_from(channels).ok().filter(|a| *a != 0);
+        res.bitrate = u32::try_from(bitrate).ok().filter(|a| *a != 0);
+        res
+    }
+
+    fn length(&self, granule: u64) -> Result<Duration> {
+        let (samples, rate) = match self {
+            Self::Vorbis { sample_rate, .. }
+            | Self::Speex { sample_rate, .. } => (granule, *sample_rate),
+            Self::Opus { pre_skip, .. } => (
+                granule.saturating_sub(*pre_skip as u64),
+                Self::OPUS_SAMPLE_RATE,
+            ),
+        };
+
+        if rate == 0 {
+            return Err(Error::InvalidLength);
+        }
+
+        Ok(Duration::from_secs_f64(samples as f64 / rate as f64))
+    }
+}
+
+/// Find the granule position of the last page of the given logical stream.
+fn last_granule(mut r: impl Read + Seek, serial: u32) -> Result<Option<u64>> {
+    let len = r.seek(SeekFrom::End(0))?;
+    let start = len.saturating_sub(PageHeader::MAX_PAGE_SIZE as u64);
+    r.seek(SeekFrom::Start(start))?;
+    let mut buf = vec![];
+    r.read_to_end(&mut buf)?;
+
+    let mut end = buf.len();
+    while let Some(p) = buf[..end]
+        .windows(PageHeader::CAPTURE_PATTERN.len())
+        .rposition(|a| a == PageHeader::CAPTURE_PATTERN)
+    {
+        end = p;
+        let d = &buf[p + PageHeader::CAPTURE_PATTERN.len()..];
+        if d.len() < PageHeader::SIZE {
+            continue;
+        }
+        let header =
+            PageHeader::from_bytes(d[..PageHeader::SIZE].try_into().unwrap());
+        if header.serial == serial && header.granule != PageHeader::NO_GRANULE
+        {
+            return Ok(Some(header.granule));
+        }
+    }
+
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::trap::Strict;
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        length: Option<Duration>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(typ, DataType::Title | DataType::Length)
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn set_length(&mut self, length: Duration) {
+            self.length = Some(length);
+        }
+    }
+
+    fn page(granule: u64, segments: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut res = PageHeader::CAPTURE_PATTERN.to_vec();
+        res.extend([0; 2]);
+        res.extend(granule.to_le_bytes());
+        res.extend(7u32.to_le_bytes());
+        res.extend([0; 8]);
+        res.push(segments.len() as u8);
+        res.extend_from_slice(segments);
+        res.extend_from_slice(data);
+        res
+    }
+
+    #[test]
+    fn opus_comments_span_pages() {
+        // version 1, 2 channels, pre-skip 312, 48 kHz, no gain, mapping 0
+        let head = b"OpusHead\x01\x02\x38\x01\x80\xBB\0\0\0\0\0";
+        let title = format!("TITLE={}", "t".repeat(290));
+        let mut tags = b"OpusTags\x03\0\0\0ven\x01\0\0\0".to_vec();
+        tags.extend((title.len() as u32).to_le_bytes());
+        tags.extend(title.as_bytes());
+        let (start, rest) = tags.split_at(255);
+
+        let mut data = page(0, &[head.len() as u8], head);
+        data.extend(page(PageHeader::NO_GRANULE, &[255], start));
+        data.extend(page(0, &[rest.len() as u8], rest));
+        data.extend(page(48000 + 312, &[1], b"a"));
+
+        let mut store = Store::default();
+        from_read(Cursor::new(&data), &mut store, &Strict).unwrap();
+        assert_eq!(store.title, Some("t".repeat(290)));
+        assert_eq!(store.length, Some(Duration::from_secs(1)));
+    }
+}
@@ -0,0 +1,212 @@
+use std::io::{self, BufRead, ErrorKind, Read, Seek};
+
+use crate::ogg::page_header::PageHeader;
+
+/// Reads packets of single logical stream from ogg pages. Packets may span
+/// multiple pages, but they are never buffered as whole.
+pub struct PacketReader<R> {
+    read: R,
+    serial: u32,
+    segments: Vec<u8>,
+    segment_idx: usize,
+    /// Bytes remaining in the current run of segments.
+    remaining: usize,
+    /// The current run of segments is the last of the packet.
+    packet_end: bool,
+}
+
+impl<R: BufRead + Seek> PacketReader<R> {
+    /// Create new packet reader. The reader must be positioned just after the
+    /// capture pattern of the first page.
+    pub fn new(mut read: R) -> io::Result<Self> {
+        let (header, segments) = read_page_header(&mut read)?;
+        Ok(Self {
+            read,
+            serial: header.serial,
+            segments,
+            segment_idx: 0,
+            remaining: 0,
+            packet_end: false,
+        })
+    }
+
+    pub fn serial(&self) -> u32 {
+        self.serial
+    }
+
+    pub fn into_inner(self) -> R {
+        self.read
+    }
+
+    /// Skip the rest of the current packet and move to the next one.
+    pub fn next_packet(&mut self) -> io::Result<()> {
+        loop {
+            if self.remaining != 0 {
+                self.read.seek_relative(self.remaining as i64)?;
+                self.remaining = 0;
+            }
+            if self.packet_end {
+                break;
+            }
+            self.next_run()?;
+        }
+        self.packet_end = false;
+        Ok(())
+    }
+
+    fn next_run(&mut self) -> io::Result<()> {
+        if self.segment_idx >= self.segments.len() {
+            self.next_page()?;
+        }
+
+        while self.segment_idx < self.segments.len() {
+            let len = self.segments[self.segment_idx];
+            self.segment_idx += 1;
+            self.remaining += len as usize;
+            if len < u8::MAX {
+                self.packet_end = true;
+                break;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn next_page(&mut self) -> io::Result<()> {
+        loop {
+            let mut capture = [0; 4];
+            self.read.read_exact(&mut capture)?;
+            if capture != PageHeader::CAPTURE_PATTERN {
+                return Err(io::Error::new(
+                    ErrorKind::InvalidData,
+                    "Invalid ogg capture pattern.",
+                ));
+            }
+
+            let (header, segments) = read_page_header(&mut self.read)?;
+            if header.serial == self.serial {
+                self.segments = segments;
+                self.segment_idx = 0;
+                return Ok(());
+            }
+
+            // Page of other logical stream.
+            let len: i64 = segments.iter().map(|a| *a as i64).sum();
+            self.read.seek_relative(len)?;
+        }
+    }
+}
+
+impl<R: BufRead + Seek> Read for PacketReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let data = self.fill_buf()?;
+        let len = data.len().min(buf.len());
+        buf[..len].copy_from_slice(&data[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: BufRead + Seek> BufRead for PacketReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.remaining == 0 {
+            if self.packet_end {
+                return Ok(&[]);
+            }
+            self.next_run()?;
+        }
+
+        let buf = self.read.fill_buf()?;
+        let len = buf.len().min(self.remaining);
+        Ok(&buf[..len])
+    }
+
+    fn consume(&mut self, amount: usize) {
+        self.read.consume(amount);
+        self.remaining -= amount;
+    }
+}
+
+fn read_page_header(
+    read: &mut impl Read,
+) -> io::Result<(PageHeader, Vec<u8>)> {
+    let mut header = [0; PageHeader::SIZE];
+    read.read_exact(&mut header)?;
+    let header = PageHeader::from_bytes(&header);
+    let mut segments = vec![0; header.segment_cnt as usize];
+    read.read_exact(&mut segments)?;
+    Ok((header, segments))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    /// Create ogg page with the given segment table and data.
+    fn page(serial: u32, segments: &[u8], data: &[u8]) -> Vec<u8> {
+        let mut res = PageHeader::CAPTURE_PATTERN.to_vec();
+        // version, header type and granule position
+        res.extend([0; 10]);
+        res.extend(serial.to_le_bytes());
+        // sequence number and CRC
+        res.extend([0; 8]);
+        res.push(segments.len() as u8);
+        res.extend_from_slice(segments);
+        res.extend_from_slice(data);
+        res
+    }
+
+    /// Packet of 300 bytes in the first page and packet of 265 bytes that
+    /// continues in the third page. The second page is of other stream.
+    fn stream() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let first: Vec<u8> = (0..300).map(|a| a as u8).collect();
+        let second: Vec<u8> = (0..265).map(|a| (a * 3) as u8).collect();
+        let mut data = first.clone();
+        data.extend_from_slice(&second[..255]);
+
+        let mut res = page(1, &[255, 45, 255], &data);
+        res.extend(page(2, &[3], b"xyz"));
+        res.extend(page(1, &[10], &second[255..]));
+        (res, first, second)
+    }
+
+    fn reader(d: &[u8]) -> PacketReader<Cursor<&[u8]>> {
+        let mut r = Cursor::new(d);
+        r.set_position(PageHeader::CAPTURE_PATTERN.len() as u64);
+        PacketReader::new(r).unwrap()
+    }
+
+    #[test]
+    fn packet_spans_pages() {
+        let (d, first, second) = stream();
+        let mut r = reader(&d);
+
+        let mut packet = vec![];
+        r.read_to_end(&mut packet).unwrap();
+        assert_eq!(packet, first);
+        r.next_packet().unwrap();
+
+        packet.clear();
+        r.read_to_end(&mut packet).unwrap();
+        assert_eq!(packet, second);
+    }
+
+    #[test]
+    fn next_packet_skips_unread_data() {
+        let (d, _, second) = stream();
+        let mut r = reader(&d);
+
+        let mut head = [0; 10];
+        r.read_exact(&mut head).unwrap();
+        r.next_packet().unwrap();
+
+        let mut packet = vec![];
+        r.read_to_end(&mut packet).unwrap();
+        assert_eq!(packet, second);
+
+        r.next_packet().unwrap();
+        assert!(r.fill_buf().is_err());
+    }
+}
@@ -0,0 +1,30 @@
+#[derive(Debug)]
+pub struct PageHeader {
+    pub granule: u64,
+    pub serial: u32,
+    pub segment_cnt: u8,
+}
+
+impl PageHeader {
+    pub const CAPTURE_PATTERN: &[u8] = b"OggS";
+    /// Size of the header without the capture pattern and segment table.
+    pub const SIZE: usize = 23;
+    /// Maximum size of whole page including the header.
+    pub const MAX_PAGE_SIZE: usize = 27 + 255 + 255 * 255;
+    pub const NO_GRANULE: u64 = u64::MAX;
+
+    pub fn from_bytes(d: &[u8; Self::SIZE]) -> Self {
+        // 0: version
+        // 1: header type
+        // 2..10: granule position
+        // 10..14: serial number
+        // 14..18: page sequence number
+        // 18..22: CRC checksum
+        // 22: number of segments
+        Self {
+            granule: u64::from_le_bytes(d[2..10].try_into().unwrap()),
+            serial: u32::from_le_bytes(d[10..14].try_into().unwrap()),
+            segment_cnt: d[22],
+        }
+    }
+}
@@ -6,16 +6,10 @@ use encoding::{
 };
 
 use crate::{
-    Error, Result,
+    DateTime, Error, Result,
     trap::{Trap, TrapExt},
 };
 
-pub struct DateTime {
-    pub year: Option<i32>,
-    pub date: Option<(u8, u8)>,
-    pub time: Option<Duration>,
-}
-
 pub fn syncsafe_be_u32(d: &[u8; 4]) -> u32 {
     (d[0] as u32) << 21
         | (d[1] as u32) << 14
@@ -27,6 +21,18 @@ pub fn be_u24(d: &[u8; 3]) -> u32 {
     (d[0] as u32) << 16 | (d[1] as u32) << 8 | d[2] as u32
 }
 
+/// Parse 80 bit IEEE 754 extended precision float.
+pub fn be_f80(d: &[u8; 10]) -> f64 {
+    let exp = u16::from_be_bytes([d[0], d[1]]);
+    let mantissa = u64::from_be_bytes(d[2..].try_into().unwrap());
+    let sign = if exp & 0x8000 == 0 { 1. } else { -1. };
+    let exp = (exp & 0x7fff) as i32;
+    if exp == 0 && mantissa == 0 {
+        return 0.;
+    }
+    sign * mantissa as f64 * 2f64.powi(exp - 16383 - 63)
+}
+
 pub fn ascii(d: &[u8], trap: &impl Trap) -> Result<String> {
     ASCII
         .decode(d, trap.decoder_trap())
@@ -146,15 +152,6 @@ pub fn utf_16_le_nt(d: &[u8], trap: &impl Trap) -> Result<(usize, String)> {
     Ok((len, utf_16_le(&d[..end], trap)?))
 }
 
-pub fn utf_16_bom(d: &[u8], trap: &impl Trap) -> Result<String> {
-    match d {
-        [] => Ok(String::new()),
-        [0xfe, 0xff, d @ ..] => utf_16_be(d, trap),
-        [0xff, 0xfe, d @ ..] => utf_16_le(d, trap),
-        _ => Err(Error::InvalidEncoding),
-    }
-}
-
 pub fn utf_16_bom_mnt(d: &[u8], trap: &impl Trap) -> Result<(usize, String)> {
     let (l, s) = match d {
         [] => return Ok((0, String::new())),
@@ -0,0 +1,220 @@
+use crate::{
+    DataType, Result, TagStore, parsers,
+    trap::{Trap, TrapExt},
+};
+
+/// Difference between the reference loudness of R128 (-23 LUFS) and
+/// ReplayGain (-18 LUFS) in dB.
+const R128_TO_REPLAY_GAIN: f32 = 5.;
+
+/// ReplayGain value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReplayGainField {
+    TrackGain,
+    TrackPeak,
+    AlbumGain,
+    AlbumPeak,
+}
+
+/// ReplayGain values collected from tag. Explicit ReplayGain values have
+/// precedence over values derived from other fields (R128, `RVA2`).
+#[derive(Debug, Default)]
+pub(crate) struct ReplayGain {
+    track_gain: Option<f32>,
+    track_peak: Option<f32>,
+    album_gain: Option<f32>,
+    album_peak: Option<f32>,
+}
+
+impl ReplayGainField {
+    const ALL: [Self; 4] = [
+        Self::TrackGain,
+        Self::TrackPeak,
+        Self::AlbumGain,
+        Self::AlbumPeak,
+    ];
+
+    /// Get the field from its key (`REPLAYGAIN_TRACK_GAIN`, ...). The key is
+    /// case insensitive.
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|a| key.eq_ignore_ascii_case(a.key()))
+    }
+
+    /// Get the gain field from R128 key (`R128_TRACK_GAIN` or
+    /// `R128_ALBUM_GAIN`). The key is case insensitive.
+    pub fn from_r128_key(key: &str) -> Option<Self> {
+        if key.eq_ignore_ascii_case("R128_TRACK_GAIN") {
+            Some(Self::TrackGain)
+        } else if key.eq_ignore_ascii_case("R128_ALBUM_GAIN") {
+            Some(Self::AlbumGain)
+        } else {
+            None
+        }
+    }
+
+    /// Data type of the field.
+    pub fn data_type(self) -> DataType {
+        match self {
+            Self::TrackGain => DataType::TrackGain,
+            Self::TrackPeak => DataType::TrackPeak,
+            Self::AlbumGain => DataType::AlbumGain,
+            Self::AlbumPeak => DataType::AlbumPeak,
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::TrackGain => "REPLAYGAIN_TRACK_GAIN",
+            Self::TrackPeak => "REPLAYGAIN_TRACK_PEAK",
+            Self::AlbumGain => "REPLAYGAIN_ALBUM_GAIN",
+            Self::AlbumPeak => "REPLAYGAIN_ALBUM_PEAK",
+        }
+    }
+}
+
+impl ReplayGain {
+    /// Check whether the store stores any of the ReplayGain values.
+    pub fn stores_any(store: &impl TagStore) -> bool {
+        ReplayGainField::ALL
+            .into_iter()
+            .any(|a| store.stores_data(a.data_type()))
+    }
+
+    /// Parse and set ReplayGain value. Gain may have the `dB` suffix. Invalid
+    /// values are trapped.
+    pub fn set(
+        &mut self,
+        field: ReplayGainField,
+        value: &str,
+        trap: &impl Trap,
+    ) -> Result<()> {
+        if let Some(v) = trap.res(parse_value(value))? {
+            *self.field_mut(field) = Some(v);
+        }
+        Ok(())
+    }
+
+    /// Parse and set R128 gain. R128 gain is integer in Q7.8 format relative
+    /// to -23 LUFS. It is used only if there is no ReplayGain value. Invalid
+    /// values are trapped.
+    pub fn set_r128(
+        &mut self,
+        field: ReplayGainField,
+        value: &str,
+        trap: &impl Trap,
+    ) -> Result<()> {
+        if let Some(v) = trap.res(parsers::num::<i16>(value.trim()))? {
+            self.set_fallback(field, v as f32 / 256. + R128_TO_REPLAY_GAIN);
+        }
+        Ok(())
+    }
+
+    /// Set the value only if it is not already set.
+    pub fn set_fallback(&mut self, field: ReplayGainField, value: f32) {
+        self.field_mut(field).get_or_insert(value);
+    }
+
+    /// Set the values to the store.
+    pub fn store(self, store: &mut impl TagStore) {
+        if let Some(v) = self.track_gain {
+            store.set_track_gain(v);
+        }
+        if let Some(v) = self.track_peak {
+            store.set_track_peak(v);
+        }
+        if let Some(v) = self.album_gain {
+            store.set_album_gain(v);
+        }
+        if let Some(v) = self.album_peak {
+            store.set_album_peak(v);
+        }
+    }
+
+    fn field_mut(&mut self, field: ReplayGainField) -> &mut Option<f32> {
+        match field {
+            ReplayGainField::TrackGain => &mut self.track_gain,
+            ReplayGainField::TrackPeak => &mut self.track_peak,
+            ReplayGainField::AlbumGain => &mut self.album_gain,
+            ReplayGainField::AlbumPeak => &mut self.album_peak,
+        }
+    }
+}
+
+/// Parse gain or peak. Accepts values such as `-6.54 dB`, `+3.2dB` or
+/// `0,988`.
+fn parse_value(s: &str) -> Result<f32> {
+    let s = s.trim();
+    let s = if s.len() >= 2
+        && s.is_char_boundary(s.len() - 2)
+        && s[s.len() - 2..].eq_ignore_ascii_case("db")
+    {
+        s[..s.len() - 2].trim_end()
+    } else {
+        s
+    };
+    Ok(s.replace(',', ".").parse()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trap::{Skip, Strict};
+
+    #[test]
+    fn parse_values() {
+        assert_eq!(parse_value("-6.54 dB").unwrap(), -6.54);
+        assert_eq!(parse_value(" +3.2dB ").unwrap(), 3.2);
+        assert_eq!(parse_value("-1.5 DB").unwrap(), -1.5);
+        assert_eq!(parse_value("0,988").unwrap(), 0.988);
+        assert_eq!(parse_value("1").unwrap(), 1.);
+        assert!(parse_value("dB").is_err());
+        assert!(parse_value("loud").is_err());
+    }
+
+    #[test]
+    fn r128_gain() {
+        use ReplayGainField::*;
+
+        let mut rg = ReplayGain::default();
+        // -2.5 dB in Q7.8 relative to -23 LUFS.
+        rg.set_r128(TrackGain, "-640", &Strict).unwrap();
+        rg.set_r128(AlbumGain, "256", &Strict).unwrap();
+        assert_eq!(rg.track_gain, Some(2.5));
+        assert_eq!(rg.album_gain, Some(6.));
+
+        assert!(rg.set_r128(TrackGain, "40000", &Strict).is_err());
+        rg.set_r128(TrackGain, "gain", &Skip).unwrap();
+        assert_eq!(rg.track_gain, Some(2.5));
+    }
+
+    #[test]
+    fn replay_gain_has_precedence() {
+        use ReplayGainField::*;
+
+        let mut rg = ReplayGain::default();
+        rg.set_r128(TrackGain, "-640", &Strict).unwrap();
+        rg.set(TrackGain, "-6.54 dB", &Strict).unwrap();
+        assert_eq!(rg.track_gain, Some(-6.54));
+
+        rg.set(AlbumGain, "-1 dB", &Strict).unwrap();
+        rg.set_r128(AlbumGain, "-640", &Strict).unwrap();
+        rg.set_fallback(AlbumPeak, 0.5);
+        assert_eq!(rg.album_gain, Some(-1.));
+        assert_eq!(rg.album_peak, Some(0.5));
+    }
+
+    #[test]
+    fn keys() {
+        assert_eq!(
+            ReplayGainField::from_key("replaygain_album_peak"),
+            Some(ReplayGainField::AlbumPeak)
+        );
+        assert_eq!(
+            ReplayGainField::from_r128_key("R128_TRACK_GAIN"),
+            Some(ReplayGainField::TrackGain)
+        );
+        assert_eq!(ReplayGainField::from_key("R128_TRACK_GAIN"), None);
+    }
+}
@@ -30,3 +30,7 @@ pub(super) const FMT: u32 = u32::from_be_bytes(*b"fmt ");
 pub const WAVE: u32 = u32::from_be_bytes(*b"WAVE");
 /// Data chunk.
 pub(super) const DATA: u32 = u32::from_be_bytes(*b"data");
+/// ID3 tag.
+pub(super) const ID3: u32 = u32::from_be_bytes(*b"ID3 ");
+/// ID3 tag (alternative lower case id).
+pub(super) const ID3_LOWER: u32 = u32::from_be_bytes(*b"id3 ");
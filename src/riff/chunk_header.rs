@@ -5,7 +5,7 @@ use crate::{
     bread::{Bread, Breadable},
 };
 
-#[derive(Debug)]
+#[derive(Debug, Copy, Clone)]
 pub struct ChunkHeader {
     pub id: u32,
     pub size: u32,
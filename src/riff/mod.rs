@@ -1,13 +1,16 @@
 /// Chunks within riff.
 pub mod chunk;
 mod chunk_header;
+mod read_options;
 mod wave_fmt;
 
 use self::{chunk_header::*, wave_fmt::*};
 
+pub use self::read_options::*;
+
 use std::{
     fs::File,
-    io::{BufRead, BufReader, Seek},
+    io::{BufRead, BufReader, Seek, SeekFrom},
     num::ParseIntError,
     path::Path,
     str::FromStr,
@@ -15,10 +18,12 @@ use std::{
 };
 
 use crate::{
-    Comment, DataType, Error, Result, TagRead, TagStore, TagStoreExt, TagType,
+    Comment, CustomField, DataType, DateTime, Error, Result, TagRead,
+    TagStore, TagStoreExt, TagType,
     bread::Bread,
-    parsers::{self, DateTime},
-    trap::Trap,
+    file_edit::{self, Edit},
+    id3, parsers,
+    trap::{Trap, TrapExt},
 };
 
 // Implementation based on:
@@ -44,12 +49,23 @@ impl<R: BufRead + Seek, S: TagStore, T: Trap> TagRead<R, S, T> for Riff {
 
 /// Read riff tag from stream. Will seek to correct position before reading.
 pub fn from_seek(
+    r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+) -> Result<()> {
+    from_seek_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read riff tag from stream with the given options. Will seek to correct
+/// position before reading.
+pub fn from_seek_with(
     mut r: impl BufRead + Seek,
     store: &mut impl TagStore,
     trap: &impl Trap,
+    opts: &ReadOptions,
 ) -> Result<()> {
     r.rewind()?;
-    from_read(r, store, trap)
+    from_read_with(r, store, trap, opts)
 }
 
 /// Read riff tags from file.
@@ -66,6 +82,17 @@ pub fn from_read(
     r: impl BufRead + Seek,
     store: &mut impl TagStore,
     trap: &impl Trap,
+) -> Result<()> {
+    from_read_with(r, store, trap, &ReadOptions::default())
+}
+
+/// Read riff tags from stream with the given options. Doesn't seek before
+/// reading.
+pub fn from_read_with(
+    r: impl BufRead + Seek,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    opts: &ReadOptions,
 ) -> Result<()> {
     let mut r = Bread::new(r);
 
@@ -83,6 +110,10 @@ pub fn from_read(
     let mut avg_bytes_per_sec = None;
     let mut data_size = None;
 
+    // Chunks with the tag that has precedence are read last, so that they
+    // override the values from the other tag.
+    let mut deferred = vec![];
+
     while !store.done() && pos + 8 < header.size {
         let header: ChunkHeader = r.get()?;
         pos += header.size + (header.size & 1) + 8;
@@ -90,13 +121,30 @@ pub fn from_read(
         let hsize = header.size as usize;
 
         match header.id {
+            chunk::LIST if opts.precedence == Precedence::Info => {
+                deferred.push((r.seek(SeekFrom::Current(0))?, header));
+                r.seek_by(header.size as i64)?;
+            }
+            chunk::ID3 | chunk::ID3_LOWER
+                if opts.precedence == Precedence::Id3 =>
+            {
+                deferred.push((r.seek(SeekFrom::Current(0))?, header));
+                r.seek_by(header.size as i64)?;
+            }
             chunk::LIST => read_list(&mut r, store, trap, header.size as i64)?,
+            chunk::ID3 | chunk::ID3_LOWER => {
+                read_id3(&mut r, store, trap, header.size)?;
+            }
             chunk::FMT
                 if typ == chunk::WAVE
-                    && store.stores_data(DataType::Length) =>
+                    && (store.stores_data(DataType::Length)
+                        || store.stores_data(DataType::AudioProperties)) =>
             {
                 if let Some(fmt) = r.witht(hsize, trap, read_wave_fmt)? {
                     avg_bytes_per_sec = Some(fmt.avg_bytes_per_sec);
+                    if store.stores_data(DataType::AudioProperties) {
+                        store.set_audio_properties(fmt.properties());
+                    }
                 }
             }
             chunk::DATA
@@ -118,6 +166,73 @@ pub fn from_read(
         }
     }
 
+    for (pos, header) in deferred {
+        if store.done() {
+            break;
+        }
+        r.seek(SeekFrom::Start(pos))?;
+        if header.id == chunk::LIST {
+            read_list(&mut r, store, trap, header.size as i64)?;
+        } else {
+            read_id3(&mut r, store, trap, header.size)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove all `LIST/INFO` chunks from the riff file and fix the size of the
+/// riff chunk. Returns the type of the removed tag or `None` if the file has
+/// no `LIST/INFO` chunk.
+pub fn remove_info(f: impl AsRef<Path>) -> Result<Option<TagType>> {
+    let f = f.as_ref();
+    let mut r = Bread::new(BufReader::new(File::open(f)?));
+    if !r.expect(b"RIFF")? {
+        return Ok(None);
+    }
+
+    let riff_size: u32 = r.get_le()?;
+    let typ: u32 = r.get_be()?;
+    let len = r.seek(SeekFrom::End(0))?;
+    let end = (riff_size as u64 + 8).min(len);
+
+    let mut edits = vec![];
+    let mut removed = 0;
+    let mut pos = 12;
+    while pos + 12 <= end {
+        r.seek(SeekFrom::Start(pos))?;
+        let header: ChunkHeader = r.get()?;
+        let next = pos + 8 + header.size as u64 + (header.size as u64 & 1);
+        if header.id == chunk::LIST && r.get_be::<u32>()? == chunk::INFO {
+            let chunk_end = next.min(end);
+            edits.push(Edit::new(pos..chunk_end, vec![]));
+            removed += chunk_end - pos;
+        }
+        pos = next;
+    }
+
+    if edits.is_empty() {
+        return Ok(None);
+    }
+
+    let riff_size = riff_size.saturating_sub(removed as u32);
+    edits.insert(0, Edit::new(4..8, riff_size.to_le_bytes().to_vec()));
+    file_edit::apply(f, &edits)?;
+    Ok(Some(TagType::Riff(typ)))
+}
+
+fn read_id3(
+    r: &mut Bread<impl BufRead + Seek>,
+    store: &mut impl TagStore,
+    trap: &impl Trap,
+    size: u32,
+) -> Result<()> {
+    let start = r.seek(SeekFrom::Current(0))?;
+    match id3::v2::from_read(r.get_mut(), store, trap) {
+        Err(Error::NoTag) => {}
+        res => trap.prop(res)?,
+    }
+    r.seek(SeekFrom::Start(start + size as u64))?;
     Ok(())
 }
 
@@ -207,6 +322,17 @@ fn read_list(
                     store.set_disc_count(c);
                 }
             }
+            id if store.stores_data(DataType::CustomFields)
+                && !is_known_info(id) =>
+            {
+                if let Some((_, v)) =
+                    r.witht(hsize, trap, parsers::ascii_nt)?
+                {
+                    let key = String::from_utf8_lossy(&id.to_be_bytes())
+                        .into_owned();
+                    store.add_custom_field(CustomField::text(key, v));
+                }
+            }
             _ => r.seek_by(header.size as i64)?,
         }
 
@@ -217,11 +343,41 @@ fn read_list(
     Ok(())
 }
 
+/// Check whether the `INFO` id is stored as known data type.
+fn is_known_info(id: u32) -> bool {
+    matches!(
+        id,
+        chunk::IART
+            | chunk::ICMT
+            | chunk::ICOP
+            | chunk::IGNR
+            | chunk::ICRD
+            | chunk::INAM
+            | chunk::IPRD
+            | chunk::IPRT
+            | chunk::PRT1
+            | chunk::PRT2
+    )
+}
+
 fn read_wave_fmt(d: &[u8], _: &impl Trap) -> Result<WaveFmt> {
     if d.len() < 14 {
         return Err(Error::InvalidLength);
     }
-    Ok(WaveFmt::from_bytes(d[..14].try_into().unwrap()))
+    let mut res = WaveFmt::from_bytes(d[..14].try_into().unwrap());
+    // 14..16: bits per sample (missing in the oldest format)
+    if d.len() >= 16 {
+        res.bits_per_sample =
+            Some(u16::from_le_bytes(d[14..16].try_into().unwrap()));
+    }
+    // 16..18: size of extension
+    // 18..20: valid bits per sample
+    // 20..24: channel mask
+    // 24..40: sub format GUID that starts with the format tag
+    if res.format_tag == WaveFmt::EXTENSIBLE && d.len() >= 26 {
+        res.format_tag = u16::from_le_bytes(d[24..26].try_into().unwrap());
+    }
+    Ok(res)
 }
 
 fn read_date(d: &[u8], trap: &impl Trap) -> Result<DateTime> {
@@ -236,3 +392,121 @@ fn read_int<T: FromStr<Err = ParseIntError>>(
     let (_, s) = parsers::ascii_nt(d, trap)?;
     parsers::num(&s)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+    use crate::{Codec, CustomValue, trap::Strict};
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        artists: Vec<String>,
+        custom: Vec<CustomField>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(
+                typ,
+                DataType::Title | DataType::Artists | DataType::CustomFields
+            )
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn set_artists(&mut self, artists: Vec<String>) {
+            self.artists = artists;
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push(field);
+        }
+    }
+
+    fn chunk(id: &[u8; 4], d: &[u8]) -> Vec<u8> {
+        let mut res = id.to_vec();
+        res.extend((d.len() as u32).to_le_bytes());
+        res.extend_from_slice(d);
+        if d.len() & 1 == 1 {
+            res.push(0);
+        }
+        res
+    }
+
+    /// Create wave file with ID3 chunk before `LIST/INFO` chunk. Both set
+    /// the title, only `INFO` sets the artist and the unknown `ISFT`.
+    fn file() -> Vec<u8> {
+        let mut id3 = b"ID3\x04\0\0\0\0\0\x0E".to_vec();
+        id3.extend(b"TIT2\0\0\0\x04\0\0\x03Id3");
+        let mut info = b"INFO".to_vec();
+        info.extend(chunk(b"INAM", b"Info\0"));
+        info.extend(chunk(b"IART", b"Artist\0"));
+        info.extend(chunk(b"ISFT", b"Encoder\0"));
+
+        let mut riff = b"WAVE".to_vec();
+        riff.extend(chunk(b"id3 ", &id3));
+        riff.extend(chunk(b"LIST", &info));
+        chunk(b"RIFF", &riff)
+    }
+
+    fn read(precedence: Precedence) -> Store {
+        let mut store = Store::default();
+        let opts = ReadOptions { precedence };
+        from_read_with(Cursor::new(file()), &mut store, &Strict, &opts)
+            .unwrap();
+        store
+    }
+
+    #[test]
+    fn id3_precedence() {
+        let store = read(Precedence::Id3);
+        assert_eq!(store.title.as_deref(), Some("Id3"));
+        assert_eq!(store.artists, ["Artist"]);
+    }
+
+    #[test]
+    fn info_precedence() {
+        let store = read(Precedence::Info);
+        assert_eq!(store.title.as_deref(), Some("Info"));
+        assert_eq!(store.artists, ["Artist"]);
+    }
+
+    #[test]
+    fn unknown_info_is_custom_field() {
+        let store = read(Precedence::Id3);
+        let [field] = store.custom.as_slice() else {
+            panic!("Expected one custom field, got {:?}", store.custom);
+        };
+        assert_eq!(field.namespace, None);
+        assert_eq!(field.key, "ISFT");
+        assert_eq!(field.value, CustomValue::Text("Encoder".into()));
+    }
+
+    #[test]
+    fn wave_fmt() {
+        // PCM, 2 channels, 44100 Hz, 176400 B/s, block align 4, 16 bits.
+        let mut d =
+            b"\x01\0\x02\0\x44\xAC\0\0\x10\xB1\x02\0\x04\0\x10\0".to_vec();
+        let p = read_wave_fmt(&d, &Strict).unwrap().properties();
+        assert_eq!(p.codec, Some(Codec::Pcm));
+        assert_eq!(p.sample_rate, Some(44100));
+        assert_eq!(p.channels, Some(2));
+        assert_eq!(p.bits_per_sample, Some(16));
+        assert_eq!(p.bitrate, Some(1411200));
+        assert_eq!(p.lossless, Some(true));
+
+        // Extensible format with float sub format.
+        d[..2].copy_from_slice(&WaveFmt::EXTENSIBLE.to_le_bytes());
+        d.extend(b"\x16\0\x20\0\x03\0\0\0\x03\0");
+        d.resize(40, 0);
+        let p = read_wave_fmt(&d, &Strict).unwrap().properties();
+        assert_eq!(p.codec, Some(Codec::FloatPcm));
+
+        assert!(read_wave_fmt(&d[..12], &Strict).is_err());
+    }
+}
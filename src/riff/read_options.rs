@@ -0,0 +1,17 @@
+/// Options for reading riff tags.
+#[derive(Debug, Default, Clone)]
+pub struct ReadOptions {
+    /// Tag that takes precedence if the file contains both ID3 chunk and
+    /// `LIST/INFO` chunk.
+    pub precedence: Precedence,
+}
+
+/// Tag within riff file that takes precedence.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub enum Precedence {
+    /// Values from ID3 chunk override values from `LIST/INFO` chunk.
+    #[default]
+    Id3,
+    /// Values from `LIST/INFO` chunk override values from ID3 chunk.
+    Info,
+}
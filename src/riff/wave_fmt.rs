@@ -1,14 +1,43 @@
+use crate::{AudioProperties, Codec};
+
 #[derive(Debug)]
 pub struct WaveFmt {
+    pub format_tag: u16,
+    pub channels: u16,
+    pub sample_rate: u32,
     pub avg_bytes_per_sec: u32,
+    pub bits_per_sample: Option<u16>,
 }
 
 impl WaveFmt {
+    /// Format tag of `WAVE_FORMAT_EXTENSIBLE`. The actual format is in the
+    /// sub format.
+    pub const EXTENSIBLE: u16 = 0xFFFE;
+
     pub fn from_bytes(d: &[u8; 14]) -> Self {
+        // 0..2: format tag
+        // 2..4: channels
+        // 4..8: sample rate
+        // 8..12: average bytes per second
+        // 12..14: block align
         Self {
+            format_tag: u16::from_le_bytes(d[0..2].try_into().unwrap()),
+            channels: u16::from_le_bytes(d[2..4].try_into().unwrap()),
+            sample_rate: u32::from_le_bytes(d[4..8].try_into().unwrap()),
             avg_bytes_per_sec: u32::from_le_bytes(
                 d[8..12].try_into().unwrap(),
             ),
+            bits_per_sample: None,
         }
     }
+
+    pub fn properties(&self) -> AudioProperties {
+        let mut res =
+            AudioProperties::new(Codec::from_wave_format(self.format_tag));
+        res.sample_rate = Some(self.sample_rate);
+        res.channels = Some(self.channels);
+        res.bits_per_sample = self.bits_per_sample.filter(|a| *a != 0);
+        res.bitrate = Some(self.avg_bytes_per_sec.saturating_mul(8));
+        res
+    }
 }
@@ -1,6 +1,6 @@
 use std::{path::Path, time::Duration};
 
-use crate::{DataType, Result, TagStore, read_tag_from_file, trap};
+use crate::{DataType, Result, TagSource, TagStore, read_tag_from_file, trap};
 
 /// Tag storage with basic information.
 #[derive(Debug, Default)]
@@ -88,3 +88,41 @@ impl TagStore for Basic {
         self.length = Some(length);
     }
 }
+
+impl TagSource for Basic {
+    fn title(&self) -> Option<&str> {
+        self.title.as_deref()
+    }
+
+    fn album(&self) -> Option<&str> {
+        self.album.as_deref()
+    }
+
+    fn artists(&self) -> &[String] {
+        &self.artists
+    }
+
+    fn album_artist(&self) -> Option<&str> {
+        self.album_artist.as_deref()
+    }
+
+    fn genres(&self) -> &[String] {
+        &self.genres
+    }
+
+    fn track(&self) -> Option<u32> {
+        self.track
+    }
+
+    fn year(&self) -> Option<i32> {
+        self.year
+    }
+
+    fn disc(&self) -> Option<u32> {
+        self.disc
+    }
+
+    fn length(&self) -> Option<Duration> {
+        self.length
+    }
+}
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use crate::{Comment, DataType, Picture, Rating};
+
+/// Generic source of data for writing tags. This is the counterpart of
+/// [`crate::TagStore`]. Each getter returns the value that should be written
+/// into the tag. Data that is not provided is kept in the tag as it is,
+/// unless [`TagSource::removes`] returns `true` for its type.
+///
+/// Data types that are written together (e.g. track and track count in
+/// ID3v2 `TRCK`) are merged, so the values that are not provided are taken
+/// from the existing tag.
+#[allow(unused_variables)]
+pub trait TagSource {
+    /// Title of the track.
+    fn title(&self) -> Option<&str> {
+        None
+    }
+
+    /// Album in which the song is.
+    fn album(&self) -> Option<&str> {
+        None
+    }
+
+    /// Artists within the song.
+    fn artists(&self) -> &[String] {
+        &[]
+    }
+
+    /// Album artist.
+    fn album_artist(&self) -> Option<&str> {
+        None
+    }
+
+    /// Genres of the song.
+    fn genres(&self) -> &[String] {
+        &[]
+    }
+
+    /// Track number of the song within album.
+    fn track(&self) -> Option<u32> {
+        None
+    }
+
+    /// Total number of tracks within album.
+    fn track_count(&self) -> Option<u32> {
+        None
+    }
+
+    /// Year of release of the song.
+    fn year(&self) -> Option<i32> {
+        None
+    }
+
+    /// Month and day of release of the song.
+    fn date(&self) -> Option<(u8, u8)> {
+        None
+    }
+
+    /// Time of release.
+    fn time(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Disc number.
+    fn disc(&self) -> Option<u32> {
+        None
+    }
+
+    /// Total number of discs.
+    fn disc_count(&self) -> Option<u32> {
+        None
+    }
+
+    /// Length of the track.
+    fn length(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Comments.
+    fn comments(&self) -> &[Comment] {
+        &[]
+    }
+
+    /// Pictures.
+    fn pictures(&self) -> &[Picture] {
+        &[]
+    }
+
+    /// Copyright of the track.
+    fn copyright(&self) -> Option<&str> {
+        None
+    }
+
+    /// User ratings.
+    fn ratings(&self) -> &[Rating] {
+        &[]
+    }
+
+    /// Check whether data of the given type should be removed from the tag
+    /// if it is not provided. Only the data types with getter in this trait
+    /// can be removed. Pictures are replaced all together, so this is called
+    /// with [`DataType::Picture`] with all the kinds.
+    fn removes(&self, typ: DataType) -> bool {
+        false
+    }
+}
+
+pub(crate) trait TagSourceExt {
+    /// Get all the data types that are provided by the source.
+    fn provided_data(&self) -> Vec<DataType>;
+
+    /// Get data types that are provided by the source, but are not supported
+    /// by a tag format.
+    fn unsupported_data(
+        &self,
+        supports: impl Fn(DataType) -> bool,
+    ) -> Vec<DataType> {
+        let mut res = self.provided_data();
+        res.retain(|a| !supports(*a));
+        res
+    }
+
+    /// Check whether the data of the given type in the tag should be
+    /// replaced with the data from the source. That is when the source
+    /// provides the data or removes it. Pictures are provided if there is
+    /// picture of any of the given kinds.
+    fn replaces(&self, typ: DataType) -> bool;
+
+    /// Get the date and time in the format `YYYY-MM-DDTHH:MM:SS`. Date and
+    /// time are included only if they are known.
+    fn date_time_string(&self) -> Option<String>;
+}
+
+impl<T: TagSource> TagSourceExt for T {
+    fn provided_data(&self) -> Vec<DataType> {
+        let mut res = vec![];
+        let mut add = |typ, provided| {
+            if provided {
+                res.push(typ);
+            }
+        };
+
+        add(DataType::Title, self.title().is_some());
+        add(DataType::Album, self.album().is_some());
+        add(DataType::Artists, !self.artists().is_empty());
+        add(DataType::AlbumArtist, self.album_artist().is_some());
+        add(DataType::Genres, !self.genres().is_empty());
+        add(DataType::Track, self.track().is_some());
+        add(DataType::TrackCount, self.track_count().is_some());
+        add(DataType::Year, self.year().is_some());
+        add(DataType::Date, self.date().is_some());
+        add(DataType::Time, self.time().is_some());
+        add(DataType::Disc, self.disc().is_some());
+        add(DataType::DiscCount, self.disc_count().is_some());
+        add(DataType::Length, self.length().is_some());
+        add(DataType::Comments, !self.comments().is_empty());
+        add(DataType::Copyright, self.copyright().is_some());
+        add(DataType::Ratings, !self.ratings().is_empty());
+
+        for p in self.pictures() {
+            let typ = DataType::Picture(p.kind);
+            if !res.contains(&typ) {
+                res.push(typ);
+            }
+        }
+
+        res
+    }
+
+    fn replaces(&self, typ: DataType) -> bool {
+        let provided = match typ {
+            DataType::Picture(k) => {
+                self.pictures().iter().any(|a| a.kind.intersects(k))
+            }
+            t => self.provided_data().contains(&t),
+        };
+        provided || self.removes(typ)
+    }
+
+    fn date_time_string(&self) -> Option<String> {
+        let mut res = format!("{:04}", self.year()?);
+        if let Some((m, d)) = self.date() {
+            res += &format!("-{m:02}-{d:02}");
+            if let Some(t) = self.time().map(|t| t.as_secs()) {
+                res += &format!(
+                    "T{:02}:{:02}:{:02}",
+                    t / 3600,
+                    t / 60 % 60,
+                    t % 60
+                );
+            }
+        }
+        Some(res)
+    }
+}
@@ -1,6 +1,9 @@
 use std::time::Duration;
 
-use crate::{Comment, DataType, Picture, Rating, TagType, parsers::DateTime};
+use crate::{
+    AudioProperties, Comment, CustomField, DataType, DateTime, Lyrics,
+    Picture, Rating, SyncedLyrics, TagType, Uuid,
+};
 
 /// Generic storage for data from tag.
 #[allow(unused_variables)]
@@ -40,6 +43,10 @@ pub trait TagStore {
 
     /// Set year of release of the song. Note that in some cases `set_date` is
     /// called but `set_year` not.
+    ///
+    /// If the tag distinguishes multiple dates, year, date and time are set
+    /// from the first available of recording time, release time and original
+    /// release time.
     fn set_year(&mut self, year: i32) {}
 
     /// Set the date of release of the song.
@@ -68,6 +75,150 @@ pub trait TagStore {
 
     /// Set user ratings.
     fn set_ratings(&mut self, ratings: Vec<Rating>) {}
+
+    /// Set composers of the song.
+    fn set_composers(&mut self, composers: Vec<String>) {}
+
+    /// Set conductor of the performance.
+    fn set_conductor(&mut self, conductor: String) {}
+
+    /// Set the artist who remixed or modified the song.
+    fn set_remixer(&mut self, remixer: String) {}
+
+    /// Set lyricists of the song.
+    fn set_lyricists(&mut self, lyricists: Vec<String>) {}
+
+    /// Set beats per minute.
+    fn set_bpm(&mut self, bpm: u32) {}
+
+    /// Set initial musical key of the song.
+    fn set_initial_key(&mut self, key: String) {}
+
+    /// Set the International Standard Recording Code.
+    fn set_isrc(&mut self, isrc: String) {}
+
+    /// Set the person or organization that encoded the file.
+    fn set_encoded_by(&mut self, encoded_by: String) {}
+
+    /// Set the software and settings used for encoding.
+    fn set_encoder_settings(&mut self, settings: String) {}
+
+    /// Set the publisher or label.
+    fn set_publisher(&mut self, publisher: String) {}
+
+    /// Set the content group of the song.
+    fn set_grouping(&mut self, grouping: String) {}
+
+    /// Set the subtitle.
+    fn set_subtitle(&mut self, subtitle: String) {}
+
+    /// Set the mood of the song.
+    fn set_mood(&mut self, mood: String) {}
+
+    /// Set languages of the lyrics.
+    fn set_languages(&mut self, languages: Vec<String>) {}
+
+    /// Set title of the original album.
+    fn set_original_album(&mut self, album: String) {}
+
+    /// Set artists of the original song.
+    fn set_original_artists(&mut self, artists: Vec<String>) {}
+
+    /// Set the original file name.
+    fn set_original_filename(&mut self, filename: String) {}
+
+    /// Set title used for sorting.
+    fn set_title_sort(&mut self, title: String) {}
+
+    /// Set album used for sorting.
+    fn set_album_sort(&mut self, album: String) {}
+
+    /// Set artist used for sorting.
+    fn set_artist_sort(&mut self, artist: String) {}
+
+    /// Set album artist used for sorting.
+    fn set_album_artist_sort(&mut self, artist: String) {}
+
+    /// Set composer used for sorting.
+    fn set_composer_sort(&mut self, composer: String) {}
+
+    /// Set whether the song is part of compilation.
+    fn set_compilation(&mut self, compilation: bool) {}
+
+    /// Set the time when the file was encoded.
+    fn set_encoding_time(&mut self, time: DateTime) {}
+
+    /// Set the time of the recording. This is set in addition to the year,
+    /// date and time.
+    fn set_recording_time(&mut self, time: DateTime) {}
+
+    /// Set the time of the release. This is set in addition to the year,
+    /// date and time.
+    fn set_release_time(&mut self, time: DateTime) {}
+
+    /// Set the time of the original release. This is set in addition to the
+    /// year, date and time.
+    fn set_original_release_time(&mut self, time: DateTime) {}
+
+    /// Set the unsynchronized lyrics.
+    fn set_lyrics(&mut self, lyrics: Vec<Lyrics>) {}
+
+    /// Set the lyrics synchronized with the audio.
+    fn set_synced_lyrics(&mut self, lyrics: Vec<SyncedLyrics>) {}
+
+    /// Add field that is not covered by the other data types.
+    fn add_custom_field(&mut self, field: CustomField) {}
+
+    /// Set the MusicBrainz recording id.
+    fn set_musicbrainz_recording_id(&mut self, id: Uuid) {}
+
+    /// Set the MusicBrainz track id. This identifies the track within the
+    /// release.
+    fn set_musicbrainz_track_id(&mut self, id: Uuid) {}
+
+    /// Set the MusicBrainz release id.
+    fn set_musicbrainz_release_id(&mut self, id: Uuid) {}
+
+    /// Set the MusicBrainz release group id.
+    fn set_musicbrainz_release_group_id(&mut self, id: Uuid) {}
+
+    /// Set the MusicBrainz ids of the artists.
+    fn set_musicbrainz_artist_ids(&mut self, ids: Vec<Uuid>) {}
+
+    /// Set the MusicBrainz ids of the album artists.
+    fn set_musicbrainz_album_artist_ids(&mut self, ids: Vec<Uuid>) {}
+
+    /// Set the MusicBrainz work id.
+    fn set_musicbrainz_work_id(&mut self, id: Uuid) {}
+
+    /// Set the MusicBrainz disc id. Disc id is computed from the table of
+    /// contents of CD, so it is not UUID.
+    fn set_musicbrainz_disc_id(&mut self, id: String) {}
+
+    /// Set the ReplayGain gain of the track in dB.
+    ///
+    /// In opus, the gain may be derived from `R128_TRACK_GAIN`. It is then
+    /// relative to the output gain from the opus header, which is not
+    /// included, because decoders apply it on their own.
+    fn set_track_gain(&mut self, gain: f32) {}
+
+    /// Set the ReplayGain peak of the track. The peak is linear where `1.0`
+    /// is full scale.
+    fn set_track_peak(&mut self, peak: f32) {}
+
+    /// Set the ReplayGain gain of the album in dB.
+    ///
+    /// In opus, the gain may be derived from `R128_ALBUM_GAIN`. It is then
+    /// relative to the output gain from the opus header, same as for
+    /// [`TagStore::set_track_gain`].
+    fn set_album_gain(&mut self, gain: f32) {}
+
+    /// Set the ReplayGain peak of the album. The peak is linear where `1.0`
+    /// is full scale.
+    fn set_album_peak(&mut self, peak: f32) {}
+
+    /// Set the technical properties of the audio.
+    fn set_audio_properties(&mut self, props: AudioProperties) {}
 }
 
 pub(crate) trait TagStoreExt {
@@ -0,0 +1,28 @@
+use std::path::Path;
+
+use crate::{DataType, Result};
+
+/// Generic tag format that can be written to file. This is the counterpart
+/// of [`crate::TagRead`].
+pub trait TagWrite<S> {
+    /// Extensions that are usually asociated with this format. This format
+    /// will be prioritized for files with one of these extensions.
+    fn extensions(&self) -> &[&str];
+
+    /// Check whether the given data type can be represented by this format.
+    fn supports_data(&self, typ: DataType) -> bool;
+
+    /// Write the data from `src` to the file. Data in the file that is not
+    /// provided or removed by [`crate::TagSource`] should be kept.
+    ///
+    /// Returns data types that are provided by `src` but this format cannot
+    /// represent them, so they were not written.
+    ///
+    /// # Errors
+    /// - [`Error::NoTag`] if the given file is not of this format. In this
+    ///   case, implementation must not modify the file.
+    /// - Other errors.
+    ///
+    /// [`Error::NoTag`]: crate::Error::NoTag
+    fn write(&self, path: &Path, src: &S) -> Result<Vec<DataType>>;
+}
@@ -3,9 +3,10 @@ pub use encoding::DecoderTrap;
 use crate::{Error, Result};
 
 mod skip;
+mod strict;
 mod warn;
 
-pub use self::{skip::*, warn::*};
+pub use self::{skip::*, strict::*, warn::*};
 
 /// Trap decides what will happen on recoverable errors.
 pub trait Trap {
@@ -0,0 +1,16 @@
+use encoding::DecoderTrap;
+
+use crate::{Error, Result, trap::Trap};
+
+/// Trap that returns all errors.
+pub struct Strict;
+
+impl Trap for Strict {
+    fn error(&self, e: Error) -> Result<()> {
+        Err(e)
+    }
+
+    fn decoder_trap(&self) -> DecoderTrap {
+        DecoderTrap::Strict
+    }
+}
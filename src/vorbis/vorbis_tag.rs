@@ -1,10 +1,14 @@
 use std::{collections::HashMap, io::BufRead};
 
 use crate::{
-    Comment, DataType, Error, Result, TagStore, TagStoreExt, TagType,
+    Comment, CustomField, DataType, DateTime, Error, Lyrics, Result,
+    TagSource, TagSourceExt, TagStore, TagStoreExt, TagType,
     bread::Bread,
-    parsers::{self, DateTime},
-    trap::{Trap, TrapExt},
+    merged_source::{Merged, SourceData},
+    musicbrainz::MusicBrainzId,
+    parsers,
+    replay_gain::{ReplayGain, ReplayGainField},
+    trap::{Skip, Trap, TrapExt},
 };
 
 /// Tag storing vorbis comments.
@@ -17,6 +21,52 @@ pub struct VorbisTag {
 }
 
 impl VorbisTag {
+    /// Keys of comments that are set from [`TagSource`] with the data types
+    /// that they contain.
+    const SOURCE_KEYS: &[(&str, &[DataType])] = &[
+        ("TITLE", &[DataType::Title]),
+        ("ALBUM", &[DataType::Album]),
+        ("ARTIST", &[DataType::Artists]),
+        ("ALBUMARTIST", &[DataType::AlbumArtist]),
+        ("ALBUM ARTIST", &[DataType::AlbumArtist]),
+        ("GENRE", &[DataType::Genres]),
+        ("DATE", &[DataType::Year, DataType::Date, DataType::Time]),
+        ("TRACKNUMBER", &[DataType::Track]),
+        ("TRACKTOTAL", &[DataType::TrackCount]),
+        ("DISCNUMBER", &[DataType::Disc]),
+        ("DISCTOTAL", &[DataType::DiscCount]),
+        ("COMMENT", &[DataType::Comments]),
+        ("COPYRIGHT", &[DataType::Copyright]),
+    ];
+
+    /// Keys of comments that are stored as known data types. Other comments
+    /// are stored as custom fields.
+    const KNOWN_KEYS: &[&str] = &[
+        "TITLE",
+        "ALBUM",
+        "ARTIST",
+        "ALBUMARTIST",
+        "ALBUM ARTIST",
+        "GENRE",
+        "DATE",
+        "TRACKNUMBER",
+        "TRACKTOTAL",
+        "DISCNUMBER",
+        "DISCTOTAL",
+        "COMMENT",
+        "COPYRIGHT",
+        "LYRICS",
+        "UNSYNCEDLYRICS",
+    ];
+
+    /// Create empty vorbis comments with the given vendor string.
+    pub fn new(vendor: String) -> Self {
+        Self {
+            vendor,
+            comments: HashMap::new(),
+        }
+    }
+
     /// Read vorbis comments from reader. The reader must be correctly
     /// positioned.
     pub fn from_read(r: impl BufRead, trap: &impl Trap) -> Result<Self> {
@@ -62,6 +112,84 @@ impl VorbisTag {
         Ok(Self { vendor, comments })
     }
 
+    /// Serialize the comments. Framing bit is used by vorbis streams in ogg.
+    /// Comments are sorted by their names.
+    pub fn to_bytes(&self, framing_bit: bool) -> Vec<u8> {
+        let mut res = vec![];
+        res.extend((self.vendor.len() as u32).to_le_bytes());
+        res.extend_from_slice(self.vendor.as_bytes());
+
+        let mut keys: Vec<_> = self.comments.keys().collect();
+        keys.sort();
+        let cnt: usize = self.comments.values().map(|a| a.len()).sum();
+        res.extend((cnt as u32).to_le_bytes());
+
+        for k in keys {
+            for v in &self.comments[k] {
+                res.extend(((k.len() + v.len() + 1) as u32).to_le_bytes());
+                res.extend_from_slice(k.as_bytes());
+                res.push(b'=');
+                res.extend_from_slice(v.as_bytes());
+            }
+        }
+
+        if framing_bit {
+            res.push(1);
+        }
+
+        res
+    }
+
+    /// Replace comments with data from the tag source. Comments with data
+    /// that the source doesn't provide or remove are kept.
+    pub fn update(&mut self, src: &impl TagSource) {
+        // The old values are needed for `DATE` that contains more data types.
+        let mut old = SourceData::default();
+        let tag = Self {
+            vendor: String::new(),
+            comments: self.comments.clone(),
+        };
+        _ = tag.store(&mut old, &Skip);
+        let merged = Merged::new(src, &old);
+
+        fn one(v: Option<impl ToString>) -> Vec<String> {
+            v.into_iter().map(|a| a.to_string()).collect()
+        }
+
+        for (k, types) in Self::SOURCE_KEYS {
+            if !types.iter().any(|t| src.replaces(*t)) {
+                continue;
+            }
+            self.comments.remove(*k);
+
+            let values = match *k {
+                "TITLE" => one(merged.title()),
+                "ALBUM" => one(merged.album()),
+                "ARTIST" => merged.artists().to_vec(),
+                "ALBUMARTIST" => one(merged.album_artist()),
+                "GENRE" => merged.genres().to_vec(),
+                "DATE" => one(merged.date_time_string()),
+                "TRACKNUMBER" => one(merged.track()),
+                "TRACKTOTAL" => one(merged.track_count()),
+                "DISCNUMBER" => one(merged.disc()),
+                "DISCTOTAL" => one(merged.disc_count()),
+                "COMMENT" => {
+                    merged.comments().iter().map(|a| a.value.clone()).collect()
+                }
+                "COPYRIGHT" => one(merged.copyright()),
+                _ => continue,
+            };
+            self.set(k, values);
+        }
+    }
+
+    fn set(&mut self, key: &str, mut values: Vec<String>) {
+        values.retain(|a| !a.is_empty());
+        if !values.is_empty() {
+            self.comments.insert(key.to_string(), values);
+        }
+    }
+
     /// Store data from the read comments into a tag store.
     pub fn store(
         self,
@@ -77,10 +205,32 @@ impl VorbisTag {
             v.into_iter().next().unwrap()
         }
 
+        let mut lyrics = vec![];
+        let mut replay_gain = ReplayGain::default();
+
         for (k, v) in self.comments {
             if v.is_empty() {
                 continue;
             }
+
+            if let Some(id) = MusicBrainzId::from_vorbis(&k)
+                && store.stores_data(id.data_type())
+            {
+                id.store(v.clone(), store, trap)?;
+            }
+
+            if let Some(f) = ReplayGainField::from_key(&k)
+                && store.stores_data(f.data_type())
+            {
+                replay_gain.set(f, v.last().unwrap(), trap)?;
+            }
+
+            if let Some(f) = ReplayGainField::from_r128_key(&k)
+                && store.stores_data(f.data_type())
+            {
+                replay_gain.set_r128(f, v.last().unwrap(), trap)?;
+            }
+
             match k.as_str() {
                 "TITLE" => store.set_title(last(v)),
                 "ALBUM" => store.set_album(last(v)),
@@ -137,6 +287,11 @@ impl VorbisTag {
                         v.into_iter().map(Comment::from_value).collect(),
                     );
                 }
+                "LYRICS" | "UNSYNCEDLYRICS"
+                    if store.stores_data(DataType::Lyrics) =>
+                {
+                    lyrics.extend(v.into_iter().map(Lyrics::from_text));
+                }
                 "COPYRIGHT" if store.stores_data(DataType::Copyright) => {
                     store.set_copyright(last(v));
                 }
@@ -145,10 +300,24 @@ impl VorbisTag {
                 {
                     store.set_album_artist(last(v));
                 }
+                _ if store.stores_data(DataType::CustomFields)
+                    && !Self::KNOWN_KEYS.contains(&k.as_str()) =>
+                {
+                    for v in v {
+                        store
+                            .add_custom_field(CustomField::text(k.clone(), v));
+                    }
+                }
                 _ => {}
             }
         }
 
+        if !lyrics.is_empty() {
+            store.set_lyrics(lyrics);
+        }
+
+        replay_gain.store(store);
+
         Ok(())
     }
 }
@@ -156,3 +325,96 @@ impl VorbisTag {
 fn parse_date(s: &str, trap: &impl Trap) -> Result<DateTime> {
     parsers::year(&s[..s.find(' ').unwrap_or(s.len())], trap)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{CustomValue, trap::Strict};
+
+    #[derive(Default)]
+    struct Store {
+        title: Option<String>,
+        custom: Vec<CustomField>,
+    }
+
+    impl TagStore for Store {
+        fn stores_data(&self, typ: DataType) -> bool {
+            matches!(typ, DataType::Title | DataType::CustomFields)
+        }
+
+        fn set_title(&mut self, title: String) {
+            self.title = Some(title);
+        }
+
+        fn add_custom_field(&mut self, field: CustomField) {
+            self.custom.push(field);
+        }
+    }
+
+    /// Create vorbis comments with framing bit.
+    fn comments(comments: &[&str]) -> Vec<u8> {
+        let mut res = 3_u32.to_le_bytes().to_vec();
+        res.extend(b"ven");
+        res.extend((comments.len() as u32).to_le_bytes());
+        for c in comments {
+            res.extend((c.len() as u32).to_le_bytes());
+            res.extend(c.as_bytes());
+        }
+        res.push(1);
+        res
+    }
+
+    fn read(d: &[u8]) -> Store {
+        let tag = VorbisTag::from_read(d, &Strict).unwrap();
+        let mut store = Store::default();
+        tag.store(&mut store, &Strict).unwrap();
+        store
+    }
+
+    /// Source that provides only the year and removes title.
+    struct YearSrc;
+
+    impl TagSource for YearSrc {
+        fn year(&self) -> Option<i32> {
+            Some(2005)
+        }
+
+        fn removes(&self, typ: DataType) -> bool {
+            typ == DataType::Title
+        }
+    }
+
+    #[test]
+    fn update_merges_and_removes() {
+        let mut tag = VorbisTag::new("ven".into());
+        for (k, v) in [
+            ("TITLE", "Old"),
+            ("ARTIST", "Artist"),
+            ("DATE", "2001-02-03"),
+            ("COMMENT", "Comment"),
+        ] {
+            tag.comments.insert(k.into(), vec![v.into()]);
+        }
+
+        tag.update(&YearSrc);
+        assert_eq!(tag.comments.get("TITLE"), None);
+        assert_eq!(tag.comments["ARTIST"], ["Artist"]);
+        assert_eq!(tag.comments["DATE"], ["2005-02-03"]);
+        assert_eq!(tag.comments["COMMENT"], ["Comment"]);
+    }
+    #[test]
+    fn unknown_keys_are_custom_fields() {
+        let store = read(&comments(&[
+            "TITLE=Title",
+            "catalognumber=CAT-1",
+            "CATALOGNUMBER=CAT-2",
+        ]));
+        assert_eq!(store.title.as_deref(), Some("Title"));
+        assert_eq!(store.custom.len(), 2);
+        for (f, v) in store.custom.iter().zip(["CAT-1", "CAT-2"]) {
+            assert_eq!(f.namespace, None);
+            assert_eq!(f.key, "CATALOGNUMBER");
+            assert_eq!(f.value, CustomValue::Text(v.into()));
+        }
+    }
+}